@@ -5,7 +5,7 @@ use plasma_state::amm::Amm;
 
 fuzz_target!(|actions: (u32, Vec<AmmAction>)| {
     let (fee, actions) = actions;
-    let mut amm = Amm::new(fee % 26, 5, 4, 0);
+    let mut amm = Amm::new(fee % 26, 500, 4, 0);
     let base = 279_900_000_000_000;
     let quote = 100_000_000_000;
 
@@ -1,11 +1,32 @@
 #[cfg(feature = "borsh")]
 use borsh::{BorshDeserialize, BorshSerialize};
 use bytemuck::{Pod, Zeroable};
+use std::cmp::Ordering;
 
-use crate::{errors::PlasmaStateError, fixed::I80F48, Downcast, Upcast};
+use crate::{errors::PlasmaStateError, fixed::I80F48, integer_sqrt, Downcast, Upcast};
 
 pub const BPS_BASE: u128 = 10000;
 
+/// Largest decrease in `base_reserves * quote_reserves` a swap may cause before it is rejected as an
+/// invariant violation. Rounding the limit order fill can shave a single unit off the product, which
+/// is harmless; anything beyond one base*quote unit is treated as value leaving the pool.
+pub const INVARIANT_TOLERANCE: u128 = 1;
+
+/// Checks that a swap did not decrease the constant product by more than [`INVARIANT_TOLERANCE`].
+pub fn check_invariant(k_start: u128, k_end: u128) -> Result<(), PlasmaStateError> {
+    if k_end.saturating_add(INVARIANT_TOLERANCE) < k_start {
+        return Err(PlasmaStateError::InvariantViolation(k_start, k_end));
+    }
+    Ok(())
+}
+
+/// Returns the part of the LP fee that LPs keep after the protocol's allocation, in basis points
+/// of the swap notional, rounded down. Allocations above 10000 bps leave LPs nothing.
+pub fn effective_lp_fee_bps(lp_fee_in_bps: u64, protocol_fee_allocation_in_bps: u64) -> u64 {
+    (lp_fee_in_bps.upcast() * BPS_BASE.saturating_sub(protocol_fee_allocation_in_bps.upcast())
+        / BPS_BASE) as u64
+}
+
 use super::SlotWindow;
 
 #[cfg_attr(feature = "borsh", derive(BorshDeserialize, BorshSerialize))]
@@ -21,6 +42,13 @@ pub struct SwapResult {
     pub fee_in_quote: u64,
 }
 
+fn leg_price(base: u64, quote: u64) -> Option<I80F48> {
+    if base == 0 {
+        return None;
+    }
+    Some(I80F48::from_fraction(quote, base))
+}
+
 impl SwapResult {
     fn new_empty_with_side(side: Side) -> Self {
         Self {
@@ -34,6 +62,134 @@ impl SwapResult {
             fee_in_quote: 0,
         }
     }
+
+    /// Returns the fee paid on this swap in basis points of the quote notional traded.
+    ///
+    /// The fee is always charged on the gross quote amount. For buys this is the quote
+    /// transferred in by the trader, for sells it is the quote transferred out plus the fee.
+    /// Returns `None` when no quote was traded.
+    pub fn realized_fee_bps(&self) -> Option<u64> {
+        let quote_notional = match self.side {
+            Side::Buy => self.quote_amount_to_transfer.upcast(),
+            Side::Sell => self.quote_amount_to_transfer.upcast() + self.fee_in_quote.upcast(),
+        };
+        if quote_notional == 0 {
+            return None;
+        }
+        (self.fee_in_quote.upcast() * BPS_BASE / quote_notional)
+            .downcast()
+            .ok()
+    }
+
+    /// Returns the amount the trader receives before fees are deducted.
+    ///
+    /// For a sell this is the quote matched against the pool, i.e. the quote transferred out plus
+    /// `fee_in_quote`. For a buy the trader receives base, and since the fee is charged on the quote
+    /// sent in, this is the base transferred out.
+    pub fn gross_quote_out(&self) -> u64 {
+        match self.side {
+            Side::Buy => self.base_amount_to_transfer,
+            Side::Sell => self.quote_amount_to_transfer + self.fee_in_quote,
+        }
+    }
+
+    /// Returns the amount the trader receives after fees are deducted.
+    ///
+    /// For a sell this is the quote transferred out. For a buy this is the base transferred out,
+    /// which is the same as `gross_quote_out` because buys pay the fee in quote on top.
+    pub fn net_quote_out(&self) -> u64 {
+        match self.side {
+            Side::Buy => self.base_amount_to_transfer,
+            Side::Sell => self.quote_amount_to_transfer,
+        }
+    }
+
+    /// Returns the price (quote per base, before fees) at which the virtual limit order was
+    /// matched, or `None` if no base was matched against it.
+    pub fn limit_order_price(&self) -> Option<I80F48> {
+        leg_price(
+            self.base_matched_as_limit_order,
+            self.quote_matched_as_limit_order,
+        )
+    }
+
+    /// Returns the average price (quote per base, before fees) of the portion matched against the
+    /// constant-product curve, or `None` if no base was matched against it.
+    pub fn swap_price(&self) -> Option<I80F48> {
+        leg_price(self.base_matched_as_swap, self.quote_matched_as_swap)
+    }
+
+    /// Returns the signed `(base_delta, quote_delta)` of the trader for this swap, where a
+    /// positive value means the trader received tokens.
+    ///
+    /// The transfer amounts already account for fees: buys pay the fee on top of the quote
+    /// sent, and sells receive the quote net of the fee.
+    pub fn trader_deltas(&self) -> (i128, i128) {
+        let base = self.base_amount_to_transfer as i128;
+        let quote = self.quote_amount_to_transfer as i128;
+        match self.side {
+            Side::Buy => (base, -quote),
+            Side::Sell => (-base, quote),
+        }
+    }
+
+    /// Returns the fraction of `requested_out` that this swap delivered, i.e. the base received
+    /// over `requested_out` for buys and the quote received over `requested_out` for sells. A
+    /// full fill returns 1.
+    ///
+    /// Returns zero if `requested_out` is zero.
+    pub fn fill_ratio(&self, requested_out: u64) -> I80F48 {
+        if requested_out == 0 {
+            return I80F48::ZERO;
+        }
+        let delivered = match self.side {
+            Side::Buy => self.base_amount_to_transfer,
+            Side::Sell => self.quote_amount_to_transfer,
+        };
+        I80F48::from_fraction(delivered, requested_out)
+    }
+
+    /// Classifies how this swap moved the pool's marginal price (quote per base), given the
+    /// reserves before the swap. The post-swap reserves are derived from the matched amounts of
+    /// both legs, ignoring fees.
+    ///
+    /// Returns `Flat` if either side of the pre- or post-swap reserves is empty, since the price
+    /// is undefined there.
+    pub fn moves_price(&self, pre_base: u64, pre_quote: u64) -> PriceDirection {
+        let base_matched =
+            self.base_matched_as_limit_order.upcast() + self.base_matched_as_swap.upcast();
+        let quote_matched =
+            self.quote_matched_as_limit_order.upcast() + self.quote_matched_as_swap.upcast();
+        let (pre_base, pre_quote) = (pre_base.upcast(), pre_quote.upcast());
+        let post_reserves = match self.side {
+            Side::Buy => pre_base
+                .checked_sub(base_matched)
+                .map(|base| (base, pre_quote + quote_matched)),
+            Side::Sell => pre_quote
+                .checked_sub(quote_matched)
+                .map(|quote| (pre_base + base_matched, quote)),
+        };
+        let Some((post_base, post_quote)) = post_reserves else {
+            return PriceDirection::Flat;
+        };
+        if pre_base == 0 || pre_quote == 0 || post_base == 0 || post_quote == 0 {
+            return PriceDirection::Flat;
+        }
+        // post_quote / post_base vs pre_quote / pre_base, cross-multiplied
+        match (post_quote * pre_base).cmp(&(pre_quote * post_base)) {
+            Ordering::Greater => PriceDirection::Up,
+            Ordering::Less => PriceDirection::Down,
+            Ordering::Equal => PriceDirection::Flat,
+        }
+    }
+}
+
+/// Direction in which a swap moved the pool's marginal price
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceDirection {
+    Up,
+    Down,
+    Flat,
 }
 
 #[cfg_attr(feature = "borsh", derive(BorshDeserialize, BorshSerialize))]
@@ -43,6 +199,18 @@ pub enum Side {
     Sell,
 }
 
+impl Side {
+    /// Maps a Borsh discriminant byte to a `Side`, returning `UnexpectedArgument` for any byte
+    /// other than 0 (`Buy`) or 1 (`Sell`)
+    pub fn try_from_u8(b: u8) -> Result<Side, PlasmaStateError> {
+        match b {
+            0 => Ok(Side::Buy),
+            1 => Ok(Side::Sell),
+            _ => Err(PlasmaStateError::UnexpectedArgument),
+        }
+    }
+}
+
 /// Enum to differentiate between base and quote tokens
 pub enum TokenType {
     Base,
@@ -54,7 +222,10 @@ pub enum TokenType {
 #[repr(C)]
 pub struct Amm {
     pub fee_in_bps: u32,
-    protocol_allocation_in_pct: u32,
+    // Pools created before basis points were supported only set the percentage. Both halves
+    // share the slot of the original u32 percentage, so those pools read back unchanged.
+    protocol_allocation_in_pct: u16,
+    protocol_allocation_in_bps: u16,
     pub lp_vesting_window: u64,
     pub reward_factor: I80F48,
     pub total_lp_shares: u64,
@@ -68,15 +239,39 @@ pub struct Amm {
 }
 
 impl Amm {
+    /// Creates an empty pool that allocates `protocol_allocation_in_pct` percent of the swap fees
+    /// to the protocol
     pub fn new(
         fee_in_bps: u32,
         protocol_allocation_in_pct: u32,
         lp_vesting_window: u64,
         slot_snapshot: u64,
+    ) -> Self {
+        Self {
+            protocol_allocation_in_pct: u16::try_from(protocol_allocation_in_pct)
+                .unwrap_or(u16::MAX),
+            ..Self::new_with_protocol_allocation_bps(
+                fee_in_bps,
+                0,
+                lp_vesting_window,
+                slot_snapshot,
+            )
+        }
+    }
+
+    /// Creates an empty pool that allocates `protocol_allocation_in_bps` basis points of the swap
+    /// fees to the protocol
+    pub fn new_with_protocol_allocation_bps(
+        fee_in_bps: u32,
+        protocol_allocation_in_bps: u32,
+        lp_vesting_window: u64,
+        slot_snapshot: u64,
     ) -> Self {
         Self {
             fee_in_bps,
-            protocol_allocation_in_pct,
+            protocol_allocation_in_pct: 0,
+            protocol_allocation_in_bps: u16::try_from(protocol_allocation_in_bps)
+                .unwrap_or(u16::MAX),
             lp_vesting_window,
             reward_factor: I80F48::ZERO,
             total_lp_shares: 0,
@@ -120,10 +315,348 @@ impl Amm {
 }
 
 impl Amm {
+    /// Returns the geometric mean of the effective buy and sell prices (quote per base, including
+    /// fees) for swapping `size` base tokens against the current pool state.
+    ///
+    /// The effective prices sit on either side of this mid, and the gap between them is the
+    /// spread the pool charges at that size.
+    pub fn implied_mid(&self, size: u64) -> Result<I80F48, PlasmaStateError> {
+        if size == 0 {
+            return Err(PlasmaStateError::MissingExpectedArgument);
+        }
+        let (buy, sell) = self.two_sided_quote(size)?;
+        let quote_in = buy.quote_amount_to_transfer;
+        let quote_out = sell.quote_amount_to_transfer;
+
+        // sqrt(quote_in / size * quote_out / size) = sqrt(quote_in * quote_out) / size
+        // The product is shifted left by an even number of bits to keep precision in the root
+        let product = quote_in.upcast() * quote_out.upcast();
+        let shift = product.leading_zeros().min(96) & !1;
+        let root = integer_sqrt(product << shift);
+        let root_fractional_bits = shift / 2;
+        let mid_bits = (root << (48 - root_fractional_bits)) / size.upcast();
+        Ok(I80F48::from_bits(
+            i128::try_from(mid_bits).map_err(|_| PlasmaStateError::Overflow)?,
+        ))
+    }
+
+    /// Returns the results of buying and of selling `size` base against the current pool state,
+    /// both at the current snapshot and without mutating the pool. The quote paid by the buy and
+    /// received by the sell, fees included, are the effective ask and bid for that size.
+    pub fn two_sided_quote(&self, size: u64) -> Result<(SwapResult, SwapResult), PlasmaStateError> {
+        let slot = self.get_slot();
+        let buy = self.clone().buy_exact_out(slot, size)?;
+        let sell = self.clone().sell_exact_in(slot, size)?;
+        Ok((buy, sell))
+    }
+
+    /// Returns the value of one LP share in atomic quote, with the base reserves valued at the
+    /// spot price: `(quote_reserves + base_reserves * spot_price) / total_lp_shares`. At the spot
+    /// price both sides are worth the same, so this is `2 * quote_reserves / total_lp_shares`.
+    pub fn lp_share_nav_in_quote(&self) -> Result<I80F48, PlasmaStateError> {
+        if self.total_lp_shares == 0 {
+            return Err(PlasmaStateError::UninitializedPool);
+        }
+        let nav_bits = ((2 * self.quote_reserves.upcast()) << 48) / self.total_lp_shares.upcast();
+        Ok(I80F48::from_bits(nav_bits as i128))
+    }
+
+    /// Returns the pool's TVL in atomic quote, with the base reserves valued at the spot price.
+    /// At the spot price both sides are worth the same, so this is `2 * quote_reserves`.
+    pub fn quote_tvl(&self) -> u128 {
+        2 * self.quote_reserves.upcast()
+    }
+
+    /// Returns an upper bound on the quote fees earned in a day if the quote reserves turn over
+    /// `turnovers_per_day` times at `lp_fee_in_bps`, i.e.
+    /// `quote_reserves * lp_fee_in_bps / 10000 * turnovers_per_day`, saturating at `u64::MAX`.
+    ///
+    /// This is a planning heuristic: it ignores price impact and the reserves changing as they
+    /// trade.
+    pub fn max_daily_fee_revenue(&self, lp_fee_in_bps: u64, turnovers_per_day: u64) -> u64 {
+        let fee_per_turnover = self.quote_reserves.upcast() * lp_fee_in_bps.upcast() / BPS_BASE;
+        (fee_per_turnover * turnovers_per_day.upcast()).min(u64::MAX.upcast()) as u64
+    }
+
+    /// Returns the pool's quote-denominated TVL as a fraction of `max_quote_tvl`. A cap of zero
+    /// means the pool is uncapped, in which case the utilization is zero.
+    pub fn utilization(&self, max_quote_tvl: u64) -> I80F48 {
+        if max_quote_tvl == 0 {
+            return I80F48::ZERO;
+        }
+        I80F48::from_bits(((self.quote_tvl() << 48) / max_quote_tvl.upcast()) as i128)
+    }
+
+    /// Returns `(base_value - quote_value) / (base_value + quote_value)`, where the base reserves
+    /// are valued at the snapshot price (the price of the pool's virtual limit order). Zero means
+    /// the pool holds equal value on both sides and a positive value means it is base-heavy.
+    ///
+    /// The base reserves are not valued at the curve's marginal price, because at that price
+    /// both sides of a constant-product pool are always worth the same.
+    pub fn inventory_skew(&self) -> I80F48 {
+        if self.base_reserves_snapshot == 0 {
+            return I80F48::ZERO;
+        }
+        let base_value = self.base_reserves.upcast() * self.quote_reserves_snapshot.upcast()
+            / self.base_reserves_snapshot.upcast();
+        let quote_value = self.quote_reserves.upcast();
+        let total_value = base_value + quote_value;
+        if total_value == 0 {
+            return I80F48::ZERO;
+        }
+        let skew_bits = ((base_value.abs_diff(quote_value) << 48) / total_value) as i128;
+        I80F48::from_bits(if base_value >= quote_value {
+            skew_bits
+        } else {
+            -skew_bits
+        })
+    }
+
+    /// Returns the fraction by which the ownership of every existing LP share shrinks if
+    /// `base_in` and `quote_in` were deposited now, i.e. the new shares' fraction of the total
+    /// share supply after the deposit.
+    pub fn dilution_from_deposit(
+        &self,
+        base_in: u64,
+        quote_in: u64,
+    ) -> Result<I80F48, PlasmaStateError> {
+        if self.total_lp_shares == 0 {
+            return Err(PlasmaStateError::UninitializedPool);
+        }
+        let (_, _, lp_shares_minted) =
+            self.clone()
+                .mint(self.get_slot(), base_in, quote_in, None)?;
+        let total_lp_shares = self
+            .total_lp_shares
+            .checked_add(lp_shares_minted)
+            .ok_or(PlasmaStateError::Overflow)?;
+        Ok(I80F48::from_fraction(lp_shares_minted, total_lp_shares))
+    }
+
+    /// Approximates the swap size at which the fee paid equals the price impact cost, both
+    /// denominated in quote. The size is in units of the input token (quote for buys, base for
+    /// sells).
+    ///
+    /// Price impact is measured against the pool price before the swap. This is an approximation
+    /// found with a bounded binary search that assumes impact grows faster than fees with size, so
+    /// the result is the smallest size whose impact cost is at least its fee.
+    pub fn fee_impact_crossover(&self, side: Side) -> Result<u64, PlasmaStateError> {
+        if self.base_reserves == 0 || self.quote_reserves == 0 {
+            return Err(PlasmaStateError::UninitializedPool);
+        }
+        let (mut low, mut high) = match side {
+            Side::Buy => (1, self.quote_reserves),
+            Side::Sell => (1, self.base_reserves),
+        };
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let (fee, impact) = self.fee_and_impact_in_quote(side, mid)?;
+            if impact >= fee {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        Ok(low)
+    }
+
+    /// Returns the fee and the price impact cost (in quote) of swapping `size` input tokens
+    pub fn fee_and_impact_in_quote(
+        &self,
+        side: Side,
+        size: u64,
+    ) -> Result<(u128, u128), PlasmaStateError> {
+        let swap_result = match side {
+            Side::Buy => self.simulate_buy_exact_in(size)?,
+            Side::Sell => self.simulate_sell_exact_in(size)?,
+        };
+        let fee = swap_result.fee_in_quote.upcast();
+        let base_at_pool_price_in_quote = swap_result.base_amount_to_transfer.upcast()
+            * self.quote_reserves.upcast()
+            / self.base_reserves.upcast();
+        let impact = match side {
+            Side::Buy => (swap_result.quote_amount_to_transfer.upcast() - fee)
+                .saturating_sub(base_at_pool_price_in_quote),
+            Side::Sell => base_at_pool_price_in_quote
+                .saturating_sub(swap_result.quote_amount_to_transfer.upcast() + fee),
+        };
+        Ok((fee, impact))
+    }
+
+    /// Returns the quote a buy at the current slot must spend to receive `base_out` base, including
+    /// the LP fee charged on top of the quote if `include_fee` is set. Without the fee this is the
+    /// quote matched against the virtual limit order and the curve.
+    ///
+    /// The fee-inclusive amount is rounded up, so a `buy_exact_in` of the returned amount delivers
+    /// at least `base_out`. Errors if `base_out` can't be bought from the pool.
+    pub fn quote_in_for_base_out(
+        &self,
+        base_out: u64,
+        include_fee: bool,
+    ) -> Result<u64, PlasmaStateError> {
+        let swap_result = self.clone().buy_exact_out(self.get_slot(), base_out)?;
+        let quote_post_fee = swap_result.quote_matched_as_limit_order.upcast()
+            + swap_result.quote_matched_as_swap.upcast();
+        if !include_fee {
+            return quote_post_fee.downcast();
+        }
+        // The fee is deducted from the input rounded down, so the input must be at least
+        // quote_post_fee * 10000 / (10000 - fee)
+        let quote_in = (quote_post_fee * BPS_BASE).div_ceil(BPS_BASE - self.fee_in_bps.upcast());
+        quote_in.downcast()
+    }
+
+    /// Returns the side and input size (quote for buys, base for sells) of the smallest swap that
+    /// moves the marginal price (`quote_reserves / base_reserves`) to at least `target_price` when
+    /// buying, or to at most `target_price` when selling. The side is the one that moves the price
+    /// toward the target, and a target equal to the current price returns a buy of zero.
+    ///
+    /// The size is found with a binary search over simulated swaps, so it includes fees and any
+    /// fill against the virtual limit order. Errors if the target can't be reached with a swap
+    /// that fits in the pool's reserves.
+    pub fn amount_to_reach_price(
+        &self,
+        target_price: I80F48,
+    ) -> Result<(Side, u64), PlasmaStateError> {
+        if self.base_reserves == 0 || self.quote_reserves == 0 {
+            return Err(PlasmaStateError::UninitializedPool);
+        }
+        if target_price <= I80F48::ZERO {
+            return Err(PlasmaStateError::UnexpectedArgument);
+        }
+        let target_bits = target_price.to_bits() as u128;
+        let side = if self.marginal_price_at_least(target_bits) {
+            if !self.marginal_price_at_least(target_bits + 1) {
+                return Ok((Side::Buy, 0));
+            }
+            Side::Sell
+        } else {
+            Side::Buy
+        };
+
+        // Whether a swap of `size` leaves the marginal price on the far side of the target
+        let reaches_target = |size: u64| -> Result<bool, PlasmaStateError> {
+            Ok(match side {
+                Side::Buy => {
+                    let mut pool = *self;
+                    pool.buy_exact_in(self.get_slot(), size)?;
+                    pool.marginal_price_at_least(target_bits)
+                }
+                Side::Sell => {
+                    let mut pool = *self;
+                    pool.sell_exact_in(self.get_slot(), size)?;
+                    !pool.marginal_price_at_least(target_bits + 1)
+                }
+            })
+        };
+
+        let (mut low, mut high) = match side {
+            Side::Buy => (1, u64::MAX - self.quote_reserves),
+            Side::Sell => (1, u64::MAX - self.base_reserves),
+        };
+        if !reaches_target(high)? {
+            return Err(PlasmaStateError::SwapExactInTooLarge);
+        }
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if reaches_target(mid)? {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        Ok((side, low))
+    }
+
+    /// Returns `(side, optimal_input, expected_profit_quote)` for the most profitable swap against
+    /// the pool when the other leg is traded externally at `external_price` (quote per base). When
+    /// the pool is below the external price the arbitrage buys base from the pool with quote, and
+    /// when it is above it sells base into the pool. The input is quote for buys and base for
+    /// sells, and the profit is valued in quote at `external_price`.
+    ///
+    /// The profit of a swap is concave in its size and peaks before the pool reaches the external
+    /// price, since fees make the last units unprofitable. The optimum is found with a ternary
+    /// search over simulated swaps up to the size returned by `amount_to_reach_price`. Returns a
+    /// buy of zero with zero profit when the pool is at the external price or fees exceed the gap.
+    pub fn arbitrage_profit(
+        &self,
+        external_price: I80F48,
+    ) -> Result<(Side, u64, u64), PlasmaStateError> {
+        let (side, max_input) = self.amount_to_reach_price(external_price)?;
+        let price_bits = external_price.to_bits() as u128;
+
+        // Profit in quote of an input of `size`, or a negative value for a loss
+        let profit = |size: u64| -> Result<i128, PlasmaStateError> {
+            if size == 0 {
+                return Ok(0);
+            }
+            let mut pool = *self;
+            Ok(match side {
+                Side::Buy => {
+                    let result = pool.buy_exact_in(self.get_slot(), size)?;
+                    let base_value = result
+                        .base_amount_to_transfer
+                        .upcast()
+                        .checked_mul(price_bits)
+                        .ok_or(PlasmaStateError::Overflow)?
+                        >> 48;
+                    base_value as i128 - result.quote_amount_to_transfer as i128
+                }
+                Side::Sell => {
+                    let result = pool.sell_exact_in(self.get_slot(), size)?;
+                    let base_cost = result
+                        .base_amount_to_transfer
+                        .upcast()
+                        .checked_mul(price_bits)
+                        .ok_or(PlasmaStateError::Overflow)?
+                        >> 48;
+                    result.quote_amount_to_transfer as i128 - base_cost as i128
+                }
+            })
+        };
+
+        let (mut low, mut high) = (0, max_input);
+        while high - low > 2 {
+            let third = (high - low) / 3;
+            let (m1, m2) = (low + third, high - third);
+            if profit(m1)? < profit(m2)? {
+                low = m1 + 1;
+            } else {
+                high = m2 - 1;
+            }
+        }
+        let mut best = (0, 0);
+        for size in low..=high {
+            let size_profit = profit(size)?;
+            if size_profit > best.1 {
+                best = (size, size_profit);
+            }
+        }
+        if best.1 <= 0 {
+            return Ok((Side::Buy, 0, 0));
+        }
+        Ok((side, best.0, best.1 as u64))
+    }
+
+    /// Whether `quote_reserves / base_reserves` is at least the price with raw fixed-point bits
+    /// `price_bits`
+    fn marginal_price_at_least(&self, price_bits: u128) -> bool {
+        match price_bits.checked_mul(self.base_reserves.upcast()) {
+            Some(scaled_base) => self.quote_reserves.upcast() << 48 >= scaled_base,
+            None => false,
+        }
+    }
+
     pub fn get_slot(&self) -> SlotWindow {
         self.slot_snapshot
     }
 
+    /// Returns whether the pool has LP shares and nonzero reserves on both sides, i.e. whether
+    /// it can be swapped against
+    pub fn has_liquidity(&self) -> bool {
+        self.total_lp_shares > 0 && self.base_reserves > 0 && self.quote_reserves > 0
+    }
+
     pub fn deposit_amount_quote(&self, amount_base: u64) -> u128 {
         amount_base.upcast() * self.quote_reserves.upcast() / self.base_reserves.upcast()
     }
@@ -131,6 +664,78 @@ impl Amm {
     pub fn deposit_amount_base(&self, amount_quote: u64) -> u128 {
         amount_quote.upcast() * self.base_reserves.upcast() / self.quote_reserves.upcast()
     }
+
+    /// Returns `cumulative_price` advanced by the current spot price (quote per base, from the
+    /// live reserves) held for `elapsed_slots`. The accumulator wraps on overflow, which
+    /// `Amm::twap` accounts for, so only differences between two readings are meaningful.
+    ///
+    /// A pool without reserves on both sides has no price and leaves the accumulator unchanged.
+    pub fn accumulate_price(&self, cumulative_price: I80F48, elapsed_slots: u64) -> I80F48 {
+        if self.base_reserves == 0 || self.quote_reserves == 0 {
+            return cumulative_price;
+        }
+        // (quote << 48) / base is below 2^112, so it fits in an i128
+        let spot_price_bits =
+            ((self.quote_reserves.upcast() << 48) / self.base_reserves.upcast()) as i128;
+        I80F48::from_bits(
+            cumulative_price
+                .to_bits()
+                .wrapping_add(spot_price_bits.wrapping_mul(elapsed_slots as i128)),
+        )
+    }
+
+    /// Returns the time-weighted average spot price between two readings of a pool's cumulative
+    /// price, i.e. `(curr_cumulative - prev_cumulative) / (curr_slot - prev_slot)`. The
+    /// difference is taken with wrapping arithmetic, so the result is correct across a single
+    /// overflow of the accumulator.
+    ///
+    /// Returns zero if `curr_slot` is not after `prev_slot`.
+    pub fn twap(
+        prev_cumulative: I80F48,
+        prev_slot: u64,
+        curr_cumulative: I80F48,
+        curr_slot: u64,
+    ) -> I80F48 {
+        if curr_slot <= prev_slot {
+            return I80F48::ZERO;
+        }
+        let delta_bits = curr_cumulative
+            .to_bits()
+            .wrapping_sub(prev_cumulative.to_bits());
+        I80F48::from_bits(delta_bits / (curr_slot - prev_slot) as i128)
+    }
+
+    /// Number of LP shares that can still be minted before `total_lp_shares` overflows
+    pub fn shares_headroom(&self) -> u64 {
+        u64::MAX - self.total_lp_shares
+    }
+
+    /// Returns the base and quote reserves backing the LP shares that are not vesting, i.e. the
+    /// liquidity LPs could withdraw right away, rounded down. `total_vesting_shares` is clamped to
+    /// the pool's total shares.
+    ///
+    /// The pool does not track vesting, so `total_vesting_shares` has to be aggregated by the
+    /// caller from the pending shares of every LP position of the pool.
+    pub fn unlocked_reserves(&self, total_vesting_shares: u64) -> (u64, u64) {
+        if self.total_lp_shares == 0 {
+            return (0, 0);
+        }
+        let unlocked_shares =
+            (self.total_lp_shares - total_vesting_shares.min(self.total_lp_shares)).upcast();
+        let total_shares = self.total_lp_shares.upcast();
+        (
+            (self.base_reserves.upcast() * unlocked_shares / total_shares) as u64,
+            (self.quote_reserves.upcast() * unlocked_shares / total_shares) as u64,
+        )
+    }
+
+    /// Amount of base and quote that can still be added to the reserves before they overflow
+    pub fn reserves_headroom(&self) -> (u64, u64) {
+        (
+            u64::MAX - self.base_reserves,
+            u64::MAX - self.quote_reserves,
+        )
+    }
 }
 
 pub struct LimitOrderConfiguration {
@@ -311,9 +916,22 @@ impl Amm {
 }
 
 impl Amm {
+    /// Share of the swap fees allocated to the protocol, in basis points.
+    ///
+    /// Allocations of pools created before basis points were supported are stored as a whole
+    /// percentage and are converted here.
+    pub fn protocol_allocation_in_bps(&self) -> u32 {
+        if self.protocol_allocation_in_bps != 0 {
+            self.protocol_allocation_in_bps as u32
+        } else {
+            self.protocol_allocation_in_pct as u32 * 100
+        }
+    }
+
     fn get_fee_splits(&self, total_fees: u64) -> (u64, u64) {
         // This will round down so LPs get any remainders
-        let protocol_fees = (total_fees * self.protocol_allocation_in_pct as u64) / 100;
+        let protocol_fees =
+            (total_fees.upcast() * self.protocol_allocation_in_bps().upcast() / BPS_BASE) as u64;
         let lp_fees = total_fees - protocol_fees;
         (lp_fees, protocol_fees)
     }
@@ -344,6 +962,24 @@ impl Amm {
 }
 
 impl Amm {
+    /// Adds `quote_amount` to the quote reserves without minting LP shares, so the donation
+    /// accrues to the existing LPs.
+    pub fn donate_quote(
+        &mut self,
+        slot: SlotWindow,
+        quote_amount: u64,
+    ) -> Result<(), PlasmaStateError> {
+        if self.total_lp_shares == 0 {
+            return Err(PlasmaStateError::UninitializedPool);
+        }
+        self.maybe_update_snapshot(slot);
+        self.quote_reserves = self
+            .quote_reserves
+            .checked_add(quote_amount)
+            .ok_or(PlasmaStateError::Overflow)?;
+        Ok(())
+    }
+
     pub fn mint(
         &mut self,
         slot: SlotWindow,
@@ -597,9 +1233,7 @@ impl Amm {
         };
 
         let k_end = updated_base_reserves * updated_quote_reserves;
-        if k_start > k_end {
-            return Err(PlasmaStateError::InvariantViolation(k_start, k_end));
-        }
+        check_invariant(k_start, k_end)?;
 
         if swap_result.base_amount_to_transfer
             != swap_result.base_matched_as_limit_order + swap_result.base_matched_as_swap
@@ -723,9 +1357,7 @@ impl Amm {
         };
 
         let k_end = updated_base_reserves * updated_quote_reserves;
-        if k_start > k_end {
-            return Err(PlasmaStateError::InvariantViolation(k_start, k_end));
-        }
+        check_invariant(k_start, k_end)?;
 
         if swap_result.base_amount_to_transfer
             != swap_result.base_matched_as_limit_order + swap_result.base_matched_as_swap
@@ -849,9 +1481,7 @@ impl Amm {
             fee_in_quote: quote_fee.downcast()?,
         };
         let k_end = updated_base_reserves * updated_quote_reserves;
-        if k_start > k_end {
-            return Err(PlasmaStateError::InvariantViolation(k_start, k_end));
-        }
+        check_invariant(k_start, k_end)?;
 
         if swap_result.base_amount_to_transfer
             != swap_result.base_matched_as_limit_order + swap_result.base_matched_as_swap
@@ -971,9 +1601,7 @@ impl Amm {
         };
 
         let k_end = updated_base_reserves * updated_quote_reserves;
-        if k_start > k_end {
-            return Err(PlasmaStateError::InvariantViolation(k_start, k_end));
-        }
+        check_invariant(k_start, k_end)?;
 
         if swap_result.base_amount_to_transfer
             != swap_result.base_matched_as_limit_order + swap_result.base_matched_as_swap
@@ -993,3 +1621,553 @@ impl Amm {
         Ok(swap_result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::new_pool;
+
+    #[test]
+    fn test_realized_fee_bps() {
+        for fee_in_bps in [1, 5, 30, 100, 250] {
+            let mut amm = new_pool(fee_in_bps);
+            let results = [
+                amm.buy_exact_in(1, 10_000_000).unwrap(),
+                amm.sell_exact_in(2, 10_000_000).unwrap(),
+                amm.buy_exact_out(3, 5_000_000).unwrap(),
+                amm.sell_exact_out(4, 5_000_000).unwrap(),
+            ];
+            for result in results {
+                let realized = result.realized_fee_bps().unwrap();
+                assert!(
+                    realized.abs_diff(fee_in_bps as u64) <= 1,
+                    "expected {} bps, got {}",
+                    fee_in_bps,
+                    realized
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_trader_deltas() {
+        let mut amm = new_pool(30);
+
+        let buy = amm.buy_exact_in(1, 10_000_000).unwrap();
+        let (base_delta, quote_delta) = buy.trader_deltas();
+        assert!(base_delta > 0);
+        assert_eq!(quote_delta, -10_000_000);
+
+        let sell = amm.sell_exact_in(2, 10_000_000).unwrap();
+        let (base_delta, quote_delta) = sell.trader_deltas();
+        assert_eq!(base_delta, -10_000_000);
+        assert!(quote_delta > 0);
+        // The fee is withheld from the quote received
+        assert_eq!(
+            quote_delta,
+            (sell.quote_matched_as_limit_order + sell.quote_matched_as_swap) as i128
+        );
+    }
+
+    #[test]
+    fn test_headroom() {
+        let mut amm = new_pool(30);
+        assert_eq!(amm.shares_headroom(), u64::MAX - 1_000_000_000);
+        assert_eq!(
+            amm.reserves_headroom(),
+            (u64::MAX - 1_000_000_000, u64::MAX - 1_000_000_000)
+        );
+
+        amm.total_lp_shares = u64::MAX - 10;
+        amm.base_reserves = u64::MAX - 5;
+        amm.quote_reserves = u64::MAX;
+        assert_eq!(amm.shares_headroom(), 10);
+        assert_eq!(amm.reserves_headroom(), (5, 0));
+    }
+
+    #[test]
+    fn test_realized_fee_bps_without_volume() {
+        let mut amm = new_pool(30);
+        assert!(amm.buy_exact_in(1, 0).unwrap().realized_fee_bps().is_none());
+        assert!(amm
+            .sell_exact_in(1, 0)
+            .unwrap()
+            .realized_fee_bps()
+            .is_none());
+    }
+
+    #[test]
+    fn test_effective_lp_fee_bps() {
+        // The protocol takes 20% of a 30 bps fee
+        assert_eq!(effective_lp_fee_bps(30, 2_000), 24);
+        assert_eq!(effective_lp_fee_bps(30, 0), 30);
+        assert_eq!(effective_lp_fee_bps(30, 10_000), 0);
+        assert_eq!(effective_lp_fee_bps(30, 20_000), 0);
+        // Rounded down
+        assert_eq!(effective_lp_fee_bps(25, 500), 23);
+    }
+
+    #[test]
+    fn test_lp_share_nav_in_quote() {
+        let mut amm = Amm::new(30, 0, 0, 0);
+        assert_eq!(
+            amm.lp_share_nav_in_quote().unwrap_err(),
+            PlasmaStateError::UninitializedPool
+        );
+
+        // 4000 base at a spot price of 0.25 and 1000 quote split over 500 shares
+        amm.mint(0, 4_000, 1_000, Some(2_000)).unwrap();
+        amm.total_lp_shares = 500;
+        assert_eq!(amm.lp_share_nav_in_quote().unwrap(), I80F48::from_num(4));
+
+        amm.total_lp_shares = 3;
+        assert_eq!(
+            amm.lp_share_nav_in_quote().unwrap(),
+            I80F48::from_fraction(2_000, 3)
+        );
+    }
+
+    #[test]
+    fn test_inventory_skew() {
+        let mut amm = new_pool(30);
+        assert_eq!(amm.inventory_skew(), I80F48::ZERO);
+
+        // Selling base into the pool within the same slot window leaves it base-heavy
+        amm.sell_exact_in(0, 100_000_000).unwrap();
+        let skew = amm.inventory_skew();
+        assert!(skew > I80F48::ZERO);
+        assert!(skew < I80F48::from_fraction(1, 5));
+
+        // Buying it back past the snapshot price makes it quote-heavy
+        amm.buy_exact_in(0, 300_000_000).unwrap();
+        assert!(amm.inventory_skew() < I80F48::ZERO);
+
+        assert_eq!(Amm::new(30, 0, 0, 0).inventory_skew(), I80F48::ZERO);
+    }
+
+    #[test]
+    fn test_dilution_from_deposit() {
+        let amm = new_pool(30);
+        // Depositing as much as the pool holds doubles the share supply
+        let dilution = amm
+            .dilution_from_deposit(amm.base_reserves, amm.quote_reserves)
+            .unwrap();
+        assert!((dilution - I80F48::from_fraction(1, 2)).to_bits().abs() < 1 << 20);
+
+        let small = amm.dilution_from_deposit(10_000_000, 10_000_000).unwrap();
+        assert!(small < dilution);
+        assert!(small > I80F48::ZERO);
+
+        assert_eq!(
+            Amm::new(30, 0, 0, 0)
+                .dilution_from_deposit(1_000, 1_000)
+                .unwrap_err(),
+            PlasmaStateError::UninitializedPool
+        );
+    }
+
+    #[test]
+    fn test_implied_mid() {
+        let mut amm = new_pool(30);
+        // Move the pool price away from 1 so the mid is not trivial
+        amm.sell_exact_in(1, 200_000_000).unwrap();
+        let pool_price = I80F48::from_fraction(amm.quote_reserves, amm.base_reserves);
+
+        let mut last_spread = I80F48::ZERO;
+        for size in [1_000_000, 10_000_000, 100_000_000] {
+            let mid = amm.implied_mid(size).unwrap();
+            let slot = amm.get_slot();
+            let buy_price = I80F48::from_fraction(
+                amm.clone()
+                    .buy_exact_out(slot, size)
+                    .unwrap()
+                    .quote_amount_to_transfer,
+                size,
+            );
+            let sell_price = I80F48::from_fraction(
+                amm.clone()
+                    .sell_exact_in(slot, size)
+                    .unwrap()
+                    .quote_amount_to_transfer,
+                size,
+            );
+            assert!(sell_price < mid && mid < buy_price);
+            assert!(sell_price < pool_price && pool_price < buy_price);
+
+            // Larger sizes widen the spread around the mid
+            let spread = buy_price - sell_price;
+            assert!(spread > last_spread);
+            last_spread = spread;
+        }
+    }
+
+    #[test]
+    fn test_fee_impact_crossover() {
+        let amm = new_pool(30);
+        for side in [Side::Buy, Side::Sell] {
+            let size = amm.fee_impact_crossover(side).unwrap();
+            let (fee, impact) = amm.fee_and_impact_in_quote(side, size).unwrap();
+            assert!(fee > 0);
+            assert!(
+                fee.abs_diff(impact) <= fee / 100 + 2,
+                "fee {} and impact {} diverge at size {}",
+                fee,
+                impact,
+                size
+            );
+
+            // Below the crossover fees dominate, above it price impact does
+            let (fee, impact) = amm.fee_and_impact_in_quote(side, size / 2).unwrap();
+            assert!(fee > impact);
+            let (fee, impact) = amm.fee_and_impact_in_quote(side, size * 2).unwrap();
+            assert!(impact > fee);
+        }
+    }
+
+    #[test]
+    fn test_protocol_fee_allocation_in_bps() {
+        let amm = Amm::new_with_protocol_allocation_bps(30, 250, 0, 0);
+        assert_eq!(amm.protocol_allocation_in_bps(), 250);
+        // 2.5% of the fees go to the protocol, rounded down
+        assert_eq!(amm.get_fee_splits(10_000), (9_750, 250));
+        assert_eq!(amm.get_fee_splits(39), (39, 0));
+        assert_eq!(amm.get_fee_splits(41), (40, 1));
+
+        let amm = Amm::new_with_protocol_allocation_bps(30, 10_000, 0, 0);
+        assert_eq!(amm.get_fee_splits(12_345), (0, 12_345));
+
+        let amm = Amm::new(30, 0, 0, 0);
+        assert_eq!(amm.get_fee_splits(12_345), (12_345, 0));
+
+        // `new` keeps taking a whole percentage, which is also what pools that predate basis
+        // points store
+        assert_eq!(Amm::new(30, 20, 0, 0).protocol_allocation_in_bps(), 2_000);
+        let mut legacy_amm = new_pool(30);
+        bytemuck::bytes_of_mut(&mut legacy_amm)[4..8].copy_from_slice(&25_u32.to_le_bytes());
+        assert_eq!(legacy_amm.protocol_allocation_in_pct, 25);
+        assert_eq!(legacy_amm.protocol_allocation_in_bps(), 2_500);
+        assert_eq!(legacy_amm.get_fee_splits(1_000), (750, 250));
+
+        let swap_result = legacy_amm.buy_exact_in(2, 10_000_000).unwrap();
+        assert_eq!(
+            legacy_amm.cumulative_quote_protocol_fees,
+            swap_result.fee_in_quote / 4
+        );
+    }
+
+    #[test]
+    fn test_gross_and_net_quote_out() {
+        let mut amm = new_pool(30);
+        let sell = amm.sell_exact_in(1, 10_000_000).unwrap();
+        assert!(sell.fee_in_quote > 0);
+        assert_eq!(sell.net_quote_out(), sell.quote_amount_to_transfer);
+        assert_eq!(
+            sell.gross_quote_out(),
+            sell.quote_amount_to_transfer + sell.fee_in_quote
+        );
+
+        let buy = amm.buy_exact_in(2, 10_000_000).unwrap();
+        assert!(buy.fee_in_quote > 0);
+        assert_eq!(buy.gross_quote_out(), buy.base_amount_to_transfer);
+        assert_eq!(buy.net_quote_out(), buy.base_amount_to_transfer);
+    }
+
+    #[test]
+    fn test_check_invariant_tolerance_boundary() {
+        let k_start = 1_000_000u128;
+        assert!(check_invariant(k_start, k_start).is_ok());
+        assert!(check_invariant(k_start, k_start + 1).is_ok());
+        assert!(check_invariant(k_start, k_start - INVARIANT_TOLERANCE).is_ok());
+        assert!(matches!(
+            check_invariant(k_start, k_start - INVARIANT_TOLERANCE - 1),
+            Err(PlasmaStateError::InvariantViolation(start, end))
+                if start == k_start && end == k_start - INVARIANT_TOLERANCE - 1
+        ));
+        assert!(check_invariant(u128::MAX, u128::MAX - 1).is_ok());
+    }
+
+    #[test]
+    fn test_utilization() {
+        let mut amm = Amm::new(30, 0, 0, 0);
+        assert!(amm.utilization(1_000) == I80F48::ZERO);
+
+        amm.mint(0, 1_000, 250, Some(500)).unwrap();
+        assert_eq!(amm.quote_tvl(), 500);
+        assert!(amm.utilization(1_000) == I80F48::from_fraction(1, 2));
+        assert!(amm.utilization(500) == I80F48::from_num(1));
+        // An uncapped pool reports no utilization
+        assert!(amm.utilization(0) == I80F48::ZERO);
+    }
+
+    #[test]
+    fn test_amount_to_reach_price() {
+        let amm = new_pool(30);
+
+        // Drive the price up to 1.21
+        let target = I80F48::from_fraction(121, 100);
+        let (side, quote_in) = amm.amount_to_reach_price(target).unwrap();
+        assert_eq!(side, Side::Buy);
+        let mut pool = amm;
+        pool.buy_exact_in(0, quote_in).unwrap();
+        assert!(pool.marginal_price_at_least(target.to_bits() as u128));
+        let mut pool = amm;
+        pool.buy_exact_in(0, quote_in - 1).unwrap();
+        assert!(!pool.marginal_price_at_least(target.to_bits() as u128));
+        // Fees make the input larger than the fee-free amount of 100_000_000
+        assert!(quote_in > 100_000_000);
+
+        // Drive the price down to 0.81
+        let target = I80F48::from_fraction(81, 100);
+        let (side, base_in) = amm.amount_to_reach_price(target).unwrap();
+        assert_eq!(side, Side::Sell);
+        let mut pool = amm;
+        pool.sell_exact_in(0, base_in).unwrap();
+        assert!(!pool.marginal_price_at_least(target.to_bits() as u128 + 1));
+        let mut pool = amm;
+        pool.sell_exact_in(0, base_in - 1).unwrap();
+        assert!(pool.marginal_price_at_least(target.to_bits() as u128 + 1));
+
+        // The pool already sits at the target
+        assert_eq!(
+            amm.amount_to_reach_price(I80F48::from_num(1)).unwrap(),
+            (Side::Buy, 0)
+        );
+        assert_eq!(
+            amm.amount_to_reach_price(I80F48::ZERO),
+            Err(PlasmaStateError::UnexpectedArgument)
+        );
+        assert_eq!(
+            Amm::new(30, 0, 0, 0).amount_to_reach_price(I80F48::from_num(1)),
+            Err(PlasmaStateError::UninitializedPool)
+        );
+    }
+
+    #[test]
+    fn test_quote_in_for_base_out() {
+        let mut amm = new_pool(30);
+        // Selling leaves an ask at the snapshot price, so buys match the limit order first
+        amm.sell_exact_in(1, 100_000_000).unwrap();
+
+        for base_out in [1, 1_000, 1_000_000, 50_000_000, 200_000_000] {
+            let without_fee = amm.quote_in_for_base_out(base_out, false).unwrap();
+            let with_fee = amm.quote_in_for_base_out(base_out, true).unwrap();
+            assert!(with_fee > without_fee);
+            // The fee is about 30 bps of the input
+            assert!(with_fee - without_fee <= with_fee * 30 / 10_000 + 1);
+
+            let swap_result = amm.simulate_buy_exact_in(with_fee).unwrap();
+            assert!(swap_result.base_amount_to_transfer >= base_out);
+        }
+
+        assert_eq!(amm.quote_in_for_base_out(0, true).unwrap(), 0);
+        assert!(amm
+            .quote_in_for_base_out(amm.base_reserves + 1, true)
+            .is_err());
+    }
+
+    #[test]
+    fn test_fill_ratio() {
+        let mut amm = new_pool(30);
+        let buy = amm.buy_exact_out(1, 1_000_000).unwrap();
+        assert_eq!(buy.base_amount_to_transfer, 1_000_000);
+        assert!(buy.fill_ratio(1_000_000) == I80F48::from_num(1));
+
+        let sell = amm.sell_exact_out(1, 1_000_000).unwrap();
+        assert!(sell.fill_ratio(1_000_000) == I80F48::from_num(1));
+
+        // A hypothetical swap that only delivered 3/4 of the requested base
+        let mut partial = SwapResult::new_empty_with_side(Side::Buy);
+        partial.base_amount_to_transfer = 750;
+        partial.quote_amount_to_transfer = 2_000;
+        assert!(partial.fill_ratio(1_000) == I80F48::from_fraction(3, 4));
+        assert!(partial.fill_ratio(0) == I80F48::ZERO);
+    }
+
+    #[test]
+    fn test_leg_prices() {
+        let mut amm = new_pool(30);
+        // Buying moves the pool above the snapshot price, which leaves a bid at the snapshot price
+        amm.buy_exact_in(1, 100_000_000).unwrap();
+        let sell = amm.sell_exact_in(1, 200_000_000).unwrap();
+        assert!(sell.base_matched_as_limit_order > 0);
+        assert!(sell.base_matched_as_swap > 0);
+
+        let limit_order_price = sell.limit_order_price().unwrap();
+        let swap_price = sell.swap_price().unwrap();
+        assert!(
+            limit_order_price
+                == I80F48::from_fraction(
+                    sell.quote_matched_as_limit_order,
+                    sell.base_matched_as_limit_order
+                )
+        );
+        assert!(
+            swap_price
+                == I80F48::from_fraction(sell.quote_matched_as_swap, sell.base_matched_as_swap)
+        );
+        // The limit order fills at the snapshot price of 1, better than the curve below it
+        assert!(limit_order_price > swap_price);
+        assert!(limit_order_price <= I80F48::from_num(1));
+
+        let buy = new_pool(30).buy_exact_in(1, 1_000).unwrap();
+        assert!(buy.limit_order_price().is_none());
+        assert!(buy.swap_price().is_some());
+        assert!(SwapResult::new_empty_with_side(Side::Buy)
+            .swap_price()
+            .is_none());
+    }
+
+    #[test]
+    fn test_twap() {
+        let mut amm = new_pool(30);
+        // 10 slots at a price of 1
+        let cumulative = amm.accumulate_price(I80F48::ZERO, 10);
+        assert!(cumulative == I80F48::from_num(10));
+        assert!(Amm::twap(I80F48::ZERO, 0, cumulative, 10) == I80F48::from_num(1));
+
+        // 10 more slots at a price of 4
+        amm.quote_reserves = 4_000_000_000;
+        let later = amm.accumulate_price(cumulative, 10);
+        assert!(later == I80F48::from_num(50));
+        assert!(Amm::twap(cumulative, 10, later, 20) == I80F48::from_num(4));
+        assert!(Amm::twap(I80F48::ZERO, 0, later, 20) == I80F48::from_fraction(5, 2));
+
+        // The average is still correct after the accumulator wraps around
+        let near_max = I80F48::from_bits(i128::MAX - I80F48::from_num(1).to_bits());
+        let wrapped = amm.accumulate_price(near_max, 10);
+        assert!(wrapped < near_max);
+        assert!(Amm::twap(near_max, 0, wrapped, 10) == I80F48::from_num(4));
+
+        // An empty interval has no average, and a pool without reserves has no price
+        assert!(Amm::twap(cumulative, 10, later, 10) == I80F48::ZERO);
+        assert!(Amm::new(30, 0, 0, 0).accumulate_price(later, 10) == later);
+    }
+
+    #[test]
+    fn test_moves_price() {
+        let mut amm = new_pool(30);
+        let (pre_base, pre_quote) = (amm.base_reserves, amm.quote_reserves);
+        let buy = amm.buy_exact_in(1, 10_000_000).unwrap();
+        assert_eq!(buy.moves_price(pre_base, pre_quote), PriceDirection::Up);
+
+        let (pre_base, pre_quote) = (amm.base_reserves, amm.quote_reserves);
+        let sell = amm.sell_exact_in(1, 50_000_000).unwrap();
+        assert_eq!(sell.moves_price(pre_base, pre_quote), PriceDirection::Down);
+
+        // Nothing matched, and reserves that cannot have backed the swap
+        let empty = SwapResult::new_empty_with_side(Side::Buy);
+        assert_eq!(empty.moves_price(pre_base, pre_quote), PriceDirection::Flat);
+        assert_eq!(buy.moves_price(0, pre_quote), PriceDirection::Flat);
+    }
+
+    #[test]
+    fn test_max_daily_fee_revenue() {
+        let amm = new_pool(30);
+        // 1_000_000_000 * 30 / 10000 = 3_000_000 per turnover
+        assert_eq!(amm.max_daily_fee_revenue(30, 1), 3_000_000);
+        assert_eq!(amm.max_daily_fee_revenue(30, 24), 72_000_000);
+        assert_eq!(amm.max_daily_fee_revenue(0, 24), 0);
+        assert_eq!(amm.max_daily_fee_revenue(30, u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn test_side_try_from_u8() {
+        assert_eq!(Side::try_from_u8(0), Ok(Side::Buy));
+        assert_eq!(Side::try_from_u8(1), Ok(Side::Sell));
+        assert_eq!(
+            Side::try_from_u8(2),
+            Err(PlasmaStateError::UnexpectedArgument)
+        );
+    }
+
+    #[test]
+    fn test_arbitrage_profit() {
+        let amm = new_pool(30);
+        // Buying base from the pool with quote, valued at the external price
+        let buy_profit = |size: u64| {
+            let result = amm.clone().buy_exact_in(amm.get_slot(), size).unwrap();
+            result.base_amount_to_transfer as i128 * 11 / 10
+                - result.quote_amount_to_transfer as i128
+        };
+        // Selling base into the pool, bought externally
+        let sell_profit = |size: u64| {
+            let result = amm.clone().sell_exact_in(amm.get_slot(), size).unwrap();
+            result.quote_amount_to_transfer as i128
+                - result.base_amount_to_transfer as i128 * 9 / 10
+        };
+
+        // The pool is below the external price, so the arbitrage buys
+        let (side, input, profit) = amm.arbitrage_profit(I80F48::from_fraction(11, 10)).unwrap();
+        assert_eq!(side, Side::Buy);
+        assert!(profit > 0);
+        assert!((buy_profit(input) - profit as i128).abs() <= 1);
+        for size in [input / 2, input * 99 / 100, input * 101 / 100, input * 2] {
+            assert!(buy_profit(size) <= profit as i128 + 1);
+        }
+        let (_, max_input) = amm
+            .amount_to_reach_price(I80F48::from_fraction(11, 10))
+            .unwrap();
+        assert!(input < max_input);
+
+        // The pool is above the external price, so the arbitrage sells
+        let (side, input, profit) = amm.arbitrage_profit(I80F48::from_fraction(9, 10)).unwrap();
+        assert_eq!(side, Side::Sell);
+        assert!(profit > 0);
+        assert!((sell_profit(input) - profit as i128).abs() <= 1);
+        for size in [input / 2, input * 99 / 100, input * 101 / 100, input * 2] {
+            assert!(sell_profit(size) <= profit as i128 + 1);
+        }
+
+        // No profit at the pool price, or when the gap is smaller than the fee
+        assert_eq!(
+            amm.arbitrage_profit(I80F48::from_num(1)).unwrap(),
+            (Side::Buy, 0, 0)
+        );
+        assert_eq!(
+            amm.arbitrage_profit(I80F48::from_fraction(1_001, 1_000))
+                .unwrap(),
+            (Side::Buy, 0, 0)
+        );
+        assert!(Amm::new(30, 0, 0, 0)
+            .arbitrage_profit(I80F48::from_num(1))
+            .is_err());
+    }
+
+    #[test]
+    fn test_unlocked_reserves() {
+        let amm = new_pool(30);
+        assert_eq!(
+            amm.unlocked_reserves(0),
+            (amm.base_reserves, amm.quote_reserves)
+        );
+        assert_eq!(
+            amm.unlocked_reserves(amm.total_lp_shares / 2),
+            (amm.base_reserves / 2, amm.quote_reserves / 2)
+        );
+        assert_eq!(amm.unlocked_reserves(u64::MAX), (0, 0));
+        assert_eq!(Amm::new(30, 0, 0, 0).unlocked_reserves(0), (0, 0));
+    }
+
+    #[test]
+    fn test_two_sided_quote() {
+        let amm = new_pool(30);
+        let size = 10_000_000;
+        let (buy, sell) = amm.two_sided_quote(size).unwrap();
+        assert_eq!(buy.side, Side::Buy);
+        assert_eq!(sell.side, Side::Sell);
+        assert_eq!(buy.base_amount_to_transfer, size);
+        assert_eq!(sell.base_amount_to_transfer, size);
+
+        // ask / bid is about (1 + fee) / (1 - fee) times the impact of trading `size` each way,
+        // which on the curve is (reserves + size) / (reserves - size)
+        let ask = buy.quote_amount_to_transfer as f64 / size as f64;
+        let bid = sell.quote_amount_to_transfer as f64 / size as f64;
+        assert!(ask > bid);
+        let impact = (1_000_000_000.0 + size as f64) / (1_000_000_000.0 - size as f64);
+        let expected = (1.0 + 0.003) / (1.0 - 0.003) * impact;
+        assert!((ask / bid - expected).abs() < 1e-3);
+
+        // The pool is not modified
+        assert_eq!(amm.base_reserves, 1_000_000_000);
+    }
+}
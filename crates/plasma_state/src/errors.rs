@@ -1,5 +1,9 @@
 use std::fmt::Display;
 
+/// Custom program error code the Plasma program returns when a swap runs against a pool without
+/// liquidity, i.e. `PlasmaStateError::UninitializedPool`
+pub const UNINITIALIZED_POOL_ERROR_CODE: u32 = 1;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PlasmaStateError {
     InvariantViolation(u128, u128),
@@ -21,6 +25,23 @@ pub enum PlasmaStateError {
     SwapExactOutTooLarge,
     SwapExactInTooLarge,
     SwapOutputGreaterThanOrEqualToReserves(u128, u128),
+    BelowMinimumDeposit {
+        quote_amount_deposited: u64,
+        min_quote_deposit: u64,
+    },
+    InconsistentLpPosition {
+        lp_shares: u64,
+        withdrawable_lp_shares: u64,
+        lp_shares_to_vest: u64,
+    },
+    TvlCapExceeded {
+        quote_tvl: u128,
+        max_quote_tvl: u64,
+    },
+    DeadlineExceeded {
+        deadline_slot: u64,
+        current_slot: u64,
+    },
 }
 
 impl Display for PlasmaStateError {
@@ -74,6 +95,39 @@ impl Display for PlasmaStateError {
                     input, reserves
                 )
             }
+            PlasmaStateError::BelowMinimumDeposit {
+                quote_amount_deposited,
+                min_quote_deposit,
+            } => write!(
+                f,
+                "Deposit of {} quote is below the pool minimum of {} quote",
+                quote_amount_deposited, min_quote_deposit
+            ),
+            PlasmaStateError::InconsistentLpPosition {
+                lp_shares,
+                withdrawable_lp_shares,
+                lp_shares_to_vest,
+            } => write!(
+                f,
+                "LP position has {} shares but {} withdrawable and {} vesting",
+                lp_shares, withdrawable_lp_shares, lp_shares_to_vest
+            ),
+            PlasmaStateError::TvlCapExceeded {
+                quote_tvl,
+                max_quote_tvl,
+            } => write!(
+                f,
+                "Pool TVL of {} quote exceeds the cap of {} quote",
+                quote_tvl, max_quote_tvl
+            ),
+            PlasmaStateError::DeadlineExceeded {
+                deadline_slot,
+                current_slot,
+            } => write!(
+                f,
+                "Deadline slot {} has passed (current slot {})",
+                deadline_slot, current_slot
+            ),
         }
     }
 }
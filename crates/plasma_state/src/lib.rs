@@ -4,6 +4,9 @@ pub mod amm;
 pub mod errors;
 pub mod fixed;
 pub mod lp;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+pub mod transfer_fee;
 
 pub type SlotWindow = u64;
 
@@ -22,8 +25,8 @@ impl Downcast<u64> for u128 {
     }
 }
 
-/// Private trait for upcasting a larger integer type
-pub(crate) trait Upcast<To> {
+/// Trait for losslessly upcasting to a larger integer type
+pub trait Upcast<To> {
     fn upcast(&self) -> To;
 }
 
@@ -38,3 +41,49 @@ impl Upcast<u128> for u32 {
         *self as u128
     }
 }
+
+impl Upcast<u128> for u16 {
+    fn upcast(&self) -> u128 {
+        *self as u128
+    }
+}
+
+/// Returns the largest integer `r` such that `r * r <= n`.
+pub fn integer_sqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    let mut x = n / 2 + 1;
+    let mut y = (x + n / x) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_sqrt() {
+        for n in [
+            0,
+            1,
+            2,
+            3,
+            4,
+            15,
+            16,
+            17,
+            1 << 64,
+            u64::MAX as u128,
+            u128::MAX,
+        ] {
+            let r = integer_sqrt(n);
+            assert!(r * r <= n);
+            assert!(r == u64::MAX as u128 || (r + 1) * (r + 1) > n);
+        }
+    }
+}
@@ -1,6 +1,11 @@
 use bytemuck::{Pod, Zeroable};
 
-use crate::{amm::Amm, errors::PlasmaStateError, fixed::I80F48};
+use crate::{
+    amm::{Amm, BPS_BASE},
+    errors::PlasmaStateError,
+    fixed::I80F48,
+    integer_sqrt, Downcast, Upcast,
+};
 
 use super::SlotWindow;
 
@@ -47,6 +52,17 @@ impl PendingSharesToVest {
         }
     }
 
+    /// Returns the slot at which the pending shares unlock and how many shares unlock then, given
+    /// the pool's vesting `window`. Vesting is a cliff rather than a linear drip: nothing unlocks
+    /// before `deposit_slot + window` and every pending share unlocks at that slot. Returns `None`
+    /// if nothing is vesting.
+    pub fn unlock_profile(&self, window: u64) -> Option<(SlotWindow, u64)> {
+        if self.deposit_slot == 0 {
+            return None;
+        }
+        Some((self.deposit_slot + window, self.lp_shares_to_vest))
+    }
+
     /// Force vest the shares, this is only used when transferring liquidity
     pub(crate) fn force_vest_shares(&mut self) -> u64 {
         let lp_shares = self.lp_shares_to_vest;
@@ -82,6 +98,62 @@ impl LpPosition {
     pub fn is_empty(&self) -> bool {
         self.lp_shares == 0
     }
+
+    pub fn reward_factor_snapshot(&self) -> I80F48 {
+        self.reward_factor_snapshot
+    }
+
+    pub fn uncollected_fees(&self) -> u64 {
+        self.uncollected_fees
+    }
+
+    pub fn collected_fees(&self) -> u64 {
+        self.collected_fees
+    }
+
+    /// Immediately vests all pending LP shares, making them withdrawable, and returns the number
+    /// of shares vested.
+    ///
+    /// Errors without modifying the position if its withdrawable and vesting shares do not add up
+    /// to its total LP shares.
+    pub fn force_vest(&mut self) -> Result<u64, PlasmaStateError> {
+        let lp_shares_to_vest = self.pending_shares_to_vest.lp_shares_to_vest;
+        if !self.is_fully_transferable() {
+            return Err(PlasmaStateError::InconsistentLpPosition {
+                lp_shares: self.lp_shares,
+                withdrawable_lp_shares: self.withdrawable_lp_shares,
+                lp_shares_to_vest,
+            });
+        }
+        let vested_lp_shares = self.pending_shares_to_vest.force_vest_shares();
+        self.withdrawable_lp_shares += vested_lp_shares;
+        Ok(vested_lp_shares)
+    }
+
+    /// Returns whether the withdrawable and vesting shares add up to the position's total LP
+    /// shares, which `transfer_liquidity` requires to move the whole position.
+    pub fn is_fully_transferable(&self) -> bool {
+        self.withdrawable_lp_shares
+            .checked_add(self.pending_shares_to_vest.lp_shares_to_vest)
+            == Some(self.lp_shares)
+    }
+
+    /// Returns the LP shares `transfer_liquidity` would move out of this position, or zero if the
+    /// position is not fully transferable.
+    pub fn transferable_shares(&self) -> u64 {
+        if self.is_fully_transferable() {
+            self.lp_shares
+        } else {
+            0
+        }
+    }
+
+    /// Vests any pending shares that have unlocked by `slot` and accrues fees, without otherwise
+    /// modifying the position. Returns the number of shares vested.
+    pub fn crank_vesting(&mut self, slot: SlotWindow, amm: &Amm) -> Result<u64, PlasmaStateError> {
+        let (vested_lp_shares, _) = self.preprocess_lp_position(slot, amm)?;
+        Ok(vested_lp_shares)
+    }
 }
 
 impl LpPosition {
@@ -98,7 +170,11 @@ impl LpPosition {
             if accumulated_reward < I80F48::ZERO {
                 return Err(PlasmaStateError::Overflow);
             }
-            (accumulated_reward * I80F48::from_num(self.lp_shares)).floor()
+            Self::fees_earned_between(
+                self.reward_factor_snapshot,
+                current_reward_factor,
+                self.lp_shares,
+            )
         } else {
             0
         };
@@ -109,10 +185,255 @@ impl LpPosition {
 }
 
 impl LpPosition {
-    pub fn get_withdrawable_base_and_quote_amounts(&self, amm: &Amm) -> (u64, u64) {
-        let base_amount = self.withdrawable_lp_shares * amm.base_reserves / amm.total_lp_shares;
-        let quote_amount = self.withdrawable_lp_shares * amm.quote_reserves / amm.total_lp_shares;
-        (base_amount, quote_amount)
+    /// Returns the quote fees earned by `shares_held` shares while the pool's reward factor grew
+    /// from `start_factor` to `end_factor`, i.e. `(end_factor - start_factor) * shares_held`
+    /// rounded down. This is the same computation positions use when accruing fees, so tooling
+    /// can replay accrual over arbitrary snapshots, e.g. splitting a window where the share count
+    /// changed into one call per constant share count.
+    ///
+    /// Returns zero if the reward factor did not grow.
+    pub fn fees_earned_between(start_factor: I80F48, end_factor: I80F48, shares_held: u64) -> u64 {
+        if end_factor <= start_factor {
+            return 0;
+        }
+        ((end_factor - start_factor) * I80F48::from_num(shares_held)).floor()
+    }
+
+    /// Returns the total quote fees this position has earned, including fees that have
+    /// accrued in the pool's reward factor but have not been recorded on the position yet.
+    ///
+    /// This is the growth in position value purely from fees, independent of any change
+    /// in the pool's reserves. The sum is returned as a `u128` since the collected, uncollected
+    /// and pending fees can together exceed `u64::MAX`.
+    pub fn value_growth_from_fees(&self, amm: &Amm) -> u128 {
+        let accumulated_reward = amm.reward_factor - self.reward_factor_snapshot;
+        let pending_fees = if accumulated_reward > I80F48::ZERO {
+            (accumulated_reward * I80F48::from_num(self.lp_shares)).floor()
+        } else {
+            0
+        };
+        self.collected_fees.upcast() + self.uncollected_fees.upcast() + pending_fees.upcast()
+    }
+
+    /// Returns the annualized fee yield of this position minus its annualized impermanent loss,
+    /// as a fraction of the current position value (e.g. 0.05 is 5% a year).
+    ///
+    /// This assumes that:
+    /// - The fees earned so far (see `value_growth_from_fees`) accrued over `slots_elapsed` and
+    ///   keep accruing at the same rate, so they are extrapolated linearly to `slots_per_year`.
+    /// - Impermanent loss is measured between `entry_spot_price` and the pool's current spot
+    ///   price (atomic quote per atomic base) relative to holding the deposit, and is also
+    ///   extrapolated linearly.
+    ///
+    /// Returns zero if `slots_elapsed` is zero or the position has no value.
+    pub fn net_yield(
+        &self,
+        amm: &Amm,
+        entry_spot_price: I80F48,
+        slots_elapsed: u64,
+        slots_per_year: u64,
+    ) -> I80F48 {
+        if slots_elapsed == 0 || amm.total_lp_shares == 0 || amm.base_reserves == 0 {
+            return I80F48::ZERO;
+        }
+        // At the spot price the base side is worth as much as the quote side
+        let position_value = 2
+            * (amm.quote_reserves.upcast() * self.lp_shares.upcast()
+                / amm.total_lp_shares.upcast());
+        if position_value == 0 {
+            return I80F48::ZERO;
+        }
+        let fee_yield_bits = ((self.value_growth_from_fees(amm) << 48) / position_value)
+            .min(i128::MAX as u128) as i128;
+
+        // IL = 1 - 2 * sqrt(r) / (1 + r) = 1 - 2 * sqrt(price * entry) / (price + entry), where
+        // r = price / entry. Both prices are shifted right by the same amount so that their
+        // product fits in a u128, which preserves their ratio.
+        let spot_price_bits =
+            (I80F48::from_fraction(amm.quote_reserves, amm.base_reserves).to_bits()) as u128;
+        let entry_price_bits = u128::try_from(entry_spot_price.to_bits()).unwrap_or(0);
+        let impermanent_loss_bits = if entry_price_bits == 0 {
+            0
+        } else {
+            let shift =
+                (128 - spot_price_bits.max(entry_price_bits).leading_zeros()).saturating_sub(63);
+            let (price, entry) = (spot_price_bits >> shift, entry_price_bits >> shift);
+            let ratio_bits = ((2 * integer_sqrt(price * entry)) << 48) / (price + entry);
+            (1_i128 << 48) - ratio_bits as i128
+        };
+
+        let net_bits = fee_yield_bits - impermanent_loss_bits;
+        let annualized_bits = net_bits
+            .checked_mul(slots_per_year as i128)
+            .map(|bits| bits / slots_elapsed as i128)
+            .unwrap_or_else(|| net_bits / slots_elapsed as i128 * slots_per_year as i128);
+        I80F48::from_bits(annualized_bits)
+    }
+
+    /// Fraction of the pool's LP shares owned by this position. Returns zero if the pool has no
+    /// LP shares.
+    pub fn ownership_fraction(&self, amm: &Amm) -> I80F48 {
+        if amm.total_lp_shares == 0 {
+            return I80F48::ZERO;
+        }
+        I80F48::from_fraction(self.lp_shares, amm.total_lp_shares)
+    }
+
+    /// Share of the pool's LP shares owned by this position in basis points, rounded down.
+    /// Returns zero if the pool has no LP shares.
+    pub fn ownership_bps(&self, amm: &Amm) -> u64 {
+        if amm.total_lp_shares == 0 {
+            return 0;
+        }
+        (self.lp_shares.upcast() * BPS_BASE / amm.total_lp_shares.upcast()) as u64
+    }
+
+    /// Fraction of the pool's LP shares owned by this position, which is also its share of the
+    /// LP fees of every future swap. Returns zero if the pool has no LP shares.
+    pub fn fee_share_fraction(&self, amm: &Amm) -> I80F48 {
+        self.ownership_fraction(amm)
+    }
+
+    /// Returns the part of `swap_fee_quote` (the LP fee of a swap, excluding the protocol's
+    /// allocation) that accrues to this position at its current share of the pool, rounded down.
+    pub fn expected_fee_from_swap(&self, amm: &Amm, swap_fee_quote: u64) -> u64 {
+        if amm.total_lp_shares == 0 {
+            return 0;
+        }
+        (swap_fee_quote.upcast() * self.lp_shares.upcast() / amm.total_lp_shares.upcast()) as u64
+    }
+}
+
+impl LpPosition {
+    /// Returns the base and quote backing this position's withdrawable shares, rounded down.
+    /// Returns `(0, 0)` if the pool has no LP shares, e.g. after its last LP withdrew.
+    ///
+    /// The products are computed in `u128`. Errors with `Overflow` if an amount does not fit in a
+    /// `u64`, which can only happen if the position holds more shares than the pool.
+    pub fn get_withdrawable_base_and_quote_amounts(
+        &self,
+        amm: &Amm,
+    ) -> Result<(u64, u64), PlasmaStateError> {
+        if amm.total_lp_shares == 0 {
+            return Ok((0, 0));
+        }
+        let shares = self.withdrawable_lp_shares.upcast();
+        let total_shares = amm.total_lp_shares.upcast();
+        let base_amount = (shares * amm.base_reserves.upcast() / total_shares).downcast()?;
+        let quote_amount = (shares * amm.quote_reserves.upcast() / total_shares).downcast()?;
+        Ok((base_amount, quote_amount))
+    }
+
+    /// Returns the number of withdrawable shares that can be removed in one go without the pool
+    /// rejecting the withdrawal as dust, i.e. without either side rounding down to zero.
+    ///
+    /// The amounts withdrawn grow with the share count, so this is all withdrawable shares when
+    /// they yield at least 1 of each token and zero otherwise.
+    pub fn max_removable_shares(&self, amm: &Amm) -> u64 {
+        if amm.total_lp_shares == 0 {
+            return 0;
+        }
+        let shares = self.withdrawable_lp_shares.upcast();
+        let total_shares = amm.total_lp_shares.upcast();
+        let smaller_reserves = amm.base_reserves.min(amm.quote_reserves).upcast();
+        if shares * smaller_reserves / total_shares == 0 {
+            0
+        } else {
+            self.withdrawable_lp_shares
+        }
+    }
+
+    /// Returns the base and quote amounts released by removing `max_removable_shares`, which is
+    /// the largest withdrawal that yields at least 1 of each token. Returns `(0, 0)` if every
+    /// withdrawal from this position would be rejected as dust.
+    pub fn max_balanced_withdrawal(&self, amm: &Amm) -> (u64, u64) {
+        let shares = self.max_removable_shares(amm);
+        if shares == 0 {
+            return (0, 0);
+        }
+        let total_shares = amm.total_lp_shares.upcast();
+        (
+            (amm.base_reserves.upcast() * shares.upcast() / total_shares) as u64,
+            (amm.quote_reserves.upcast() * shares.upcast() / total_shares) as u64,
+        )
+    }
+
+    /// Returns the base and quote amounts this position could withdraw if the pool's spot price
+    /// (atomic quote per atomic base) moved to `hypothetical_spot_price` while preserving `k`,
+    /// rounded down. Returns `(0, 0)` if the price is not positive or the pool has no shares.
+    pub fn value_at_price(&self, amm: &Amm, hypothetical_spot_price: I80F48) -> (u64, u64) {
+        let price_bits = match u128::try_from(hypothetical_spot_price.to_bits()) {
+            Ok(bits) if bits > 0 => bits,
+            _ => return (0, 0),
+        };
+        if amm.total_lp_shares == 0 {
+            return (0, 0);
+        }
+        let k = amm.base_reserves.upcast() * amm.quote_reserves.upcast();
+
+        // base = sqrt(k / price). k is shifted left by an even number of bits before dividing
+        // by the price to keep precision in the root
+        let k_shift = k.leading_zeros() & !1;
+        let base_root = integer_sqrt((k << k_shift) / price_bits);
+        let base_reserves = if k_shift >= 48 {
+            base_root >> ((k_shift - 48) / 2)
+        } else {
+            base_root << ((48 - k_shift) / 2)
+        };
+
+        // quote = sqrt(k) * sqrt(price)
+        let price_shift = price_bits.leading_zeros() & !1;
+        let price_root = integer_sqrt(price_bits << price_shift);
+        let quote_reserves = (integer_sqrt(k) * price_root) >> (24 + price_shift / 2);
+
+        let share_of = |reserves: u128| {
+            let amount = reserves.saturating_mul(self.withdrawable_lp_shares.upcast())
+                / amm.total_lp_shares.upcast();
+            u64::try_from(amount).unwrap_or(u64::MAX)
+        };
+        (share_of(base_reserves), share_of(quote_reserves))
+    }
+
+    /// Returns the spot price (atomic quote per atomic base) at which this position's withdrawable
+    /// shares are worth `target_quote_value` in quote, with the base valued at that price and the
+    /// pool rebalanced along its constant product as in `value_at_price`.
+    ///
+    /// At price `p` the pool holds `sqrt(k * p)` quote and the same value in base, so the position
+    /// is worth `2 * sqrt(k * p) * shares / total_shares`. Solving for `p` gives
+    /// `(target * total_shares / (2 * shares))^2 / k`: the value grows with the square root of the
+    /// price, so doubling the target quadruples the price.
+    ///
+    /// Errors with `UninitializedPool` if the pool is empty, `UnexpectedArgument` if the position
+    /// has no withdrawable shares, and `Overflow` if the price does not fit in an `I80F48`.
+    pub fn price_for_target_value(
+        &self,
+        amm: &Amm,
+        target_quote_value: u64,
+    ) -> Result<I80F48, PlasmaStateError> {
+        let k = amm.base_reserves.upcast() * amm.quote_reserves.upcast();
+        if amm.total_lp_shares == 0 || k == 0 {
+            return Err(PlasmaStateError::UninitializedPool);
+        }
+        if self.withdrawable_lp_shares == 0 {
+            return Err(PlasmaStateError::UnexpectedArgument);
+        }
+
+        // Quote reserves of the pool at which the position is worth the target
+        let quote_reserves = target_quote_value.upcast() * amm.total_lp_shares.upcast()
+            / (2 * self.withdrawable_lp_shares.upcast());
+        let quote_squared = quote_reserves
+            .checked_mul(quote_reserves)
+            .ok_or(PlasmaStateError::Overflow)?;
+
+        // price = quote^2 / k with 48 fractional bits. The numerator is shifted as far as it fits
+        // before dividing and the quotient makes up the remaining shift.
+        let shift = quote_squared.leading_zeros().min(48);
+        let quotient = (quote_squared << shift) / k;
+        let remaining_shift = 48 - shift;
+        if quotient.leading_zeros() <= remaining_shift {
+            return Err(PlasmaStateError::Overflow);
+        }
+        Ok(I80F48::from_bits((quotient << remaining_shift) as i128))
     }
 }
 
@@ -146,6 +467,43 @@ impl LpPosition {
         })
     }
 
+    /// Simulates `add_liquidity` into an already initialized pool without mutating the position
+    /// or the pool. Returns the result along with the quote fees that accrue to the position as
+    /// part of the deposit, since pending fees are settled before the new shares are minted.
+    pub fn preview_add_with_accrual(
+        &self,
+        slot: SlotWindow,
+        amm: &Amm,
+        base_amount_desired: u64,
+        quote_amount_desired: u64,
+    ) -> Result<(AddLiquidityResult, u64), PlasmaStateError> {
+        let mut lp_position = *self;
+        let mut amm = *amm;
+        let result = lp_position.add_liquidity(
+            slot,
+            &mut amm,
+            base_amount_desired,
+            quote_amount_desired,
+            None,
+        )?;
+        let accrued_fees = result.quote_fees_accumulated;
+        Ok((result, accrued_fees))
+    }
+
+    /// Simulates `remove_liquidity` at `future_slot` without mutating the position or the pool.
+    /// Pending shares that have vested by `future_slot` count as withdrawable. As with
+    /// `remove_liquidity`, nothing is withdrawn if `lp_shares` exceeds the withdrawable shares.
+    pub fn simulate_remove_at_slot(
+        &self,
+        future_slot: SlotWindow,
+        amm: &Amm,
+        lp_shares: u64,
+    ) -> Result<RemoveLiquidityResult, PlasmaStateError> {
+        let mut lp_position = *self;
+        let mut amm = *amm;
+        lp_position.remove_liquidity(future_slot, &mut amm, lp_shares)
+    }
+
     pub fn remove_liquidity(
         &mut self,
         slot: SlotWindow,
@@ -230,6 +588,16 @@ impl LpPosition {
         self.uncollected_fees = 0;
         Ok(fees)
     }
+
+    /// Returns the quote fees `collect_fees` would pay out at `slot`, without modifying the
+    /// position. If the pool's reward factor is behind the position's snapshot, which
+    /// `collect_fees` rejects, only the already recorded uncollected fees are returned.
+    pub fn pending_fees(&self, amm: &Amm, slot: SlotWindow) -> u64 {
+        let mut preview = *self;
+        preview
+            .collect_fees(slot, amm)
+            .unwrap_or(self.uncollected_fees)
+    }
 }
 
 pub struct AddLiquidityResult {
@@ -240,6 +608,14 @@ pub struct AddLiquidityResult {
     pub quote_fees_accumulated: u64,
 }
 
+impl AddLiquidityResult {
+    /// Net quote received by the LP in this instruction: the fees accrued during the
+    /// preprocess step minus the quote deposited. Positive if the LP net-received quote.
+    pub fn net_quote_flow(&self) -> i128 {
+        self.quote_fees_accumulated as i128 - self.quote_amount_deposited as i128
+    }
+}
+
 pub struct RemoveLiquidityResult {
     pub base_amount_withdrawn: u64,
     pub quote_amount_withdrawn: u64,
@@ -247,3 +623,562 @@ pub struct RemoveLiquidityResult {
     pub lp_shares_vested: u64,
     pub quote_fees_accumulated: u64,
 }
+
+impl RemoveLiquidityResult {
+    /// Total quote attributable to the LP in this instruction, the withdrawn quote plus
+    /// the fees accrued during the preprocess step.
+    pub fn total_quote_out(&self) -> u64 {
+        self.quote_amount_withdrawn
+            .saturating_add(self.quote_fees_accumulated)
+    }
+}
+
+/// Client-facing view of an `LpPosition` with a stable JSON schema that does not depend on the
+/// account layout
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LpPositionJson {
+    /// Total LP shares owned by the position, including shares that are still vesting
+    pub lp_shares: u64,
+    /// LP shares that can be withdrawn now
+    pub withdrawable_lp_shares: u64,
+    /// Quote fees accrued to the position that have not been withdrawn yet
+    pub uncollected_fees: u64,
+    /// Quote fees already withdrawn from the position
+    pub collected_fees: u64,
+    /// The tranche of shares that is still vesting, if any
+    pub pending_vesting: Option<PendingVestingJson>,
+}
+
+/// Client-facing view of the shares an `LpPosition` has vesting
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PendingVestingJson {
+    /// Slot at which the shares were deposited
+    pub deposit_slot: SlotWindow,
+    /// Number of shares vesting
+    pub shares: u64,
+}
+
+#[cfg(feature = "serde")]
+impl From<&LpPosition> for LpPositionJson {
+    fn from(lp_position: &LpPosition) -> Self {
+        let pending = &lp_position.pending_shares_to_vest;
+        Self {
+            lp_shares: lp_position.lp_shares,
+            withdrawable_lp_shares: lp_position.withdrawable_lp_shares,
+            uncollected_fees: lp_position.uncollected_fees,
+            collected_fees: lp_position.collected_fees,
+            pending_vesting: pending.is_vesting().then_some(PendingVestingJson {
+                deposit_slot: pending.deposit_slot,
+                shares: pending.lp_shares_to_vest,
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl LpPosition {
+    /// Serializes the position to JSON using the `LpPositionJson` schema
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&LpPositionJson::from(self))
+            .expect("LpPositionJson is always serializable")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{new_pool, seed_pool};
+
+    fn setup() -> (Amm, LpPosition) {
+        let mut amm = Amm::new(30, 0, 0, 0);
+        let mut lp = LpPosition::new_with_reward_factor_snapshot(amm.reward_factor);
+        lp.add_liquidity(
+            1,
+            &mut amm,
+            1_000_000_000,
+            1_000_000_000,
+            Some(1_000_000_000),
+        )
+        .unwrap();
+        // Generate some fees for the LP
+        amm.buy_exact_in(2, 10_000_000).unwrap();
+        (amm, lp)
+    }
+
+    #[test]
+    fn test_net_quote_flow_with_accrued_fees() {
+        let (mut amm, mut lp) = setup();
+        let result = lp
+            .add_liquidity(3, &mut amm, 1_000_000, 2_000_000, None)
+            .unwrap();
+        assert!(result.quote_fees_accumulated > 0);
+        assert_eq!(
+            result.net_quote_flow(),
+            result.quote_fees_accumulated as i128 - result.quote_amount_deposited as i128
+        );
+        assert!(result.net_quote_flow() < 0);
+    }
+
+    #[test]
+    fn test_value_growth_from_fees() {
+        let (mut amm, mut lp) = setup();
+        let fees = amm.cumulative_quote_lp_fees;
+        assert!(fees > 0);
+        // The position owns every share, so it is owed all of the LP fees (up to rounding)
+        let growth = lp.value_growth_from_fees(&amm);
+        assert!(fees.upcast() - growth <= 1);
+
+        // Collecting fees does not change the growth
+        assert_eq!(lp.collect_fees(3, &amm).unwrap().upcast(), growth);
+        assert_eq!(lp.collected_fees().upcast(), growth);
+        assert_eq!(lp.value_growth_from_fees(&amm), growth);
+
+        // Neither do reserve changes that don't generate fees
+        let mut other = LpPosition::new_with_reward_factor_snapshot(amm.reward_factor);
+        other
+            .add_liquidity(4, &mut amm, 500_000_000, 600_000_000, None)
+            .unwrap();
+        assert_eq!(lp.value_growth_from_fees(&amm), growth);
+
+        // The sum does not overflow when the fee counters are near their limits
+        lp.collected_fees = u64::MAX;
+        lp.uncollected_fees = u64::MAX;
+        assert_eq!(lp.value_growth_from_fees(&amm), 2 * u64::MAX.upcast());
+    }
+
+    #[test]
+    fn test_preview_add_with_accrual() {
+        let (amm, lp) = setup();
+        let pending_fees = lp.value_growth_from_fees(&amm);
+        assert!(pending_fees > 0);
+
+        let (result, accrued_fees) = lp
+            .preview_add_with_accrual(3, &amm, 1_000_000, 2_000_000)
+            .unwrap();
+        assert_eq!(accrued_fees.upcast(), pending_fees);
+        assert_eq!(result.quote_fees_accumulated.upcast(), pending_fees);
+        assert!(result.lp_shares_received > 0);
+
+        // Nothing was mutated
+        assert_eq!(lp.uncollected_fees(), 0);
+        assert_eq!(lp.value_growth_from_fees(&amm), pending_fees);
+
+        // The preview matches the actual deposit
+        let (mut amm, mut lp) = (amm, lp);
+        let actual = lp
+            .add_liquidity(3, &mut amm, 1_000_000, 2_000_000, None)
+            .unwrap();
+        assert_eq!(actual.lp_shares_received, result.lp_shares_received);
+        assert_eq!(actual.quote_amount_deposited, result.quote_amount_deposited);
+        assert_eq!(lp.uncollected_fees(), accrued_fees);
+    }
+
+    #[test]
+    fn test_total_quote_out_with_accrued_fees() {
+        let (mut amm, mut lp) = setup();
+        let result = lp.remove_liquidity(3, &mut amm, 100_000_000).unwrap();
+        assert!(result.quote_fees_accumulated > 0);
+        assert_eq!(
+            result.total_quote_out(),
+            result.quote_amount_withdrawn + result.quote_fees_accumulated
+        );
+    }
+
+    #[test]
+    fn test_fee_share_fraction() {
+        let mut amm = new_pool(30);
+        let mut lp = LpPosition::new_with_reward_factor_snapshot(amm.reward_factor);
+        lp.add_liquidity(1, &mut amm, 1_000_000_000, 1_000_000_000, None)
+            .unwrap();
+        assert_eq!(lp.lp_shares * 2, amm.total_lp_shares);
+
+        assert_eq!(lp.fee_share_fraction(&amm), I80F48::from_fraction(1, 2));
+        assert_eq!(lp.expected_fee_from_swap(&amm, 30_000), 15_000);
+        assert_eq!(lp.expected_fee_from_swap(&amm, 1), 0);
+
+        // The position receives its share of the LP fees of the next swap
+        let reward_factor = amm.reward_factor;
+        let swap_result = amm.buy_exact_in(2, 10_000_000).unwrap();
+        let accrued =
+            ((amm.reward_factor - reward_factor) * I80F48::from_num(lp.lp_shares)).floor();
+        assert!(accrued.abs_diff(lp.expected_fee_from_swap(&amm, swap_result.fee_in_quote)) <= 1);
+
+        let empty_amm = Amm::new(30, 0, 0, 0);
+        assert_eq!(lp.fee_share_fraction(&empty_amm), I80F48::ZERO);
+        assert_eq!(lp.expected_fee_from_swap(&empty_amm, 30_000), 0);
+    }
+
+    #[test]
+    fn test_force_vest() {
+        let mut amm = seed_pool(Amm::new(30, 0, 8, 0));
+        let mut lp = LpPosition::new_with_reward_factor_snapshot(amm.reward_factor);
+        let result = lp
+            .add_liquidity(4, &mut amm, 100_000_000, 100_000_000, None)
+            .unwrap();
+        assert_eq!(lp.withdrawable_lp_shares, 0);
+        assert!(lp.pending_shares_to_vest.is_vesting());
+
+        assert_eq!(lp.force_vest().unwrap(), result.lp_shares_received);
+        assert_eq!(lp.withdrawable_lp_shares, lp.lp_shares);
+        assert!(!lp.pending_shares_to_vest.is_vesting());
+
+        // Nothing is left to vest
+        assert_eq!(lp.force_vest().unwrap(), 0);
+
+        // A position whose shares do not add up is left untouched
+        let mut inconsistent = lp;
+        inconsistent.pending_shares_to_vest.set(8, 10).unwrap();
+        assert_eq!(
+            inconsistent.force_vest(),
+            Err(PlasmaStateError::InconsistentLpPosition {
+                lp_shares: lp.lp_shares,
+                withdrawable_lp_shares: lp.lp_shares,
+                lp_shares_to_vest: 10,
+            })
+        );
+        assert!(inconsistent.pending_shares_to_vest.is_vesting());
+    }
+
+    #[test]
+    fn test_value_at_price() {
+        let mut amm = Amm::new(30, 0, 0, 0);
+        let mut lp = LpPosition::new_with_reward_factor_snapshot(amm.reward_factor);
+        lp.add_liquidity(
+            1,
+            &mut amm,
+            1_000_000_000,
+            1_000_000_000,
+            Some(1_000_000_000),
+        )
+        .unwrap();
+        lp.force_vest().unwrap();
+
+        // At the current price the value matches what can be withdrawn today
+        let (base, quote) = lp.value_at_price(&amm, I80F48::from_num(1));
+        assert_eq!(
+            (base, quote),
+            lp.get_withdrawable_base_and_quote_amounts(&amm).unwrap()
+        );
+
+        // Doubling the price rebalances the position towards quote: base / sqrt(2), quote * sqrt(2)
+        let (base, quote) = lp.value_at_price(&amm, I80F48::from_num(2));
+        assert!(base.abs_diff(707_106_781) <= 1);
+        assert!(quote.abs_diff(1_414_213_562) <= 1);
+        assert!((base as u128 * quote as u128).abs_diff(1_000_000_000_000_000_000) < 2_000_000_000);
+
+        // Halving it rebalances towards base
+        let (base, quote) = lp.value_at_price(&amm, I80F48::from_fraction(1, 2));
+        assert!(base.abs_diff(1_414_213_562) <= 1);
+        assert!(quote.abs_diff(707_106_781) <= 1);
+
+        assert_eq!(lp.value_at_price(&amm, I80F48::ZERO), (0, 0));
+        assert_eq!(lp.value_at_price(&amm, I80F48::from_bits(-1)), (0, 0));
+    }
+
+    #[test]
+    fn test_net_yield() {
+        let (mut amm, lp) = setup();
+        let fees = u64::try_from(lp.value_growth_from_fees(&amm)).unwrap();
+        let spot_price = I80F48::from_fraction(amm.quote_reserves, amm.base_reserves);
+        let position_value = 2 * amm.quote_reserves;
+
+        // Without a price move there is no impermanent loss, so the net yield is the fee yield
+        let net_yield = lp.net_yield(&amm, spot_price, 1_000, 1_000);
+        assert!(net_yield > I80F48::ZERO);
+        assert_eq!(net_yield, I80F48::from_fraction(fees, position_value));
+        // Earning the same fees in a tenth of a year is ten times the yield
+        let annualized = lp.net_yield(&amm, spot_price, 100, 1_000);
+        assert!(
+            (annualized - I80F48::from_fraction(10 * fees, position_value))
+                .to_bits()
+                .abs()
+                < 16
+        );
+
+        // A large price move costs more in impermanent loss than the fees earned
+        amm.buy_exact_in(3, 400_000_000).unwrap();
+        let net_yield = lp.net_yield(&amm, I80F48::from_num(1), 1_000, 1_000);
+        assert!(net_yield < I80F48::ZERO);
+        // The price roughly doubled, so IL is about 1 - 2 * sqrt(2) / 3 = 5.7%
+        let impermanent_loss = I80F48::ZERO - net_yield;
+        assert!(impermanent_loss > I80F48::from_fraction(4, 100));
+        assert!(impermanent_loss < I80F48::from_fraction(6, 100));
+
+        assert_eq!(lp.net_yield(&amm, spot_price, 0, 1_000), I80F48::ZERO);
+    }
+
+    #[test]
+    fn test_transferable_shares() {
+        let (amm, mut lp) = setup();
+        assert!(lp.is_fully_transferable());
+        assert_eq!(lp.transferable_shares(), lp.lp_shares);
+
+        let mut dst = LpPosition::new_with_reward_factor_snapshot(amm.reward_factor);
+        let mut transferred = lp;
+        assert_eq!(
+            transferred.transfer_liquidity(3, &amm, &mut dst).unwrap(),
+            lp.transferable_shares()
+        );
+
+        // A position whose total no longer matches its withdrawable and vesting shares
+        lp.lp_shares += 1;
+        assert!(!lp.is_fully_transferable());
+        assert_eq!(lp.transferable_shares(), 0);
+        let mut dst = LpPosition::new_with_reward_factor_snapshot(amm.reward_factor);
+        assert!(matches!(
+            lp.transfer_liquidity(3, &amm, &mut dst),
+            Err(PlasmaStateError::InvariantViolation(..))
+        ));
+    }
+
+    #[test]
+    fn test_simulate_remove_at_slot() {
+        let mut amm = Amm::new(30, 0, 100, 0);
+        let mut lp = LpPosition::new_with_reward_factor_snapshot(amm.reward_factor);
+        lp.add_liquidity(
+            4,
+            &mut amm,
+            1_000_000_000,
+            1_000_000_000,
+            Some(1_000_000_000),
+        )
+        .unwrap();
+        let shares = lp.lp_shares / 2;
+
+        // Before the shares vest nothing can be removed
+        let result = lp.simulate_remove_at_slot(8, &amm, shares).unwrap();
+        assert_eq!(result.lp_shares_burned, 0);
+        assert_eq!(result.base_amount_withdrawn, 0);
+
+        let result = lp.simulate_remove_at_slot(104, &amm, shares).unwrap();
+        assert_eq!(result.lp_shares_vested, lp.lp_shares);
+        assert_eq!(result.lp_shares_burned, shares);
+        assert_eq!(result.base_amount_withdrawn, 500_000_000);
+        assert_eq!(result.quote_amount_withdrawn, 500_000_000);
+
+        // The position and the pool are untouched
+        assert_eq!(lp.withdrawable_lp_shares, 0);
+        assert_eq!(amm.base_reserves, 1_000_000_000);
+    }
+
+    #[test]
+    fn test_unlock_profile() {
+        let mut pending = PendingSharesToVest::new();
+        assert_eq!(pending.unlock_profile(8), None);
+
+        pending.set(100, 500).unwrap();
+        assert_eq!(pending.unlock_profile(8), Some((108, 500)));
+
+        // Shares only unlock at the cliff
+        let amm = Amm::new(30, 0, 8, 0);
+        assert_eq!(pending.maybe_vest_shares(107, &amm), 0);
+        assert_eq!(pending.maybe_vest_shares(108, &amm), 500);
+        assert_eq!(pending.unlock_profile(8), None);
+    }
+
+    #[test]
+    fn test_fees_earned_between() {
+        let (mut amm, mut lp) = setup();
+        let start_factor = lp.reward_factor_snapshot;
+        let shares_held = lp.lp_shares;
+        let expected =
+            LpPosition::fees_earned_between(start_factor, amm.reward_factor, shares_held);
+        assert!(expected > 0);
+        assert_eq!(lp.collect_fees(3, &amm).unwrap(), expected);
+
+        // Shares added mid-window only earn from the point they were added
+        let mid_factor = amm.reward_factor;
+        lp.add_liquidity(4, &mut amm, 1_000_000_000, 1_000_000_000, None)
+            .unwrap();
+        let added_shares = lp.lp_shares - shares_held;
+        amm.buy_exact_in(5, 10_000_000).unwrap();
+        let end_factor = amm.reward_factor;
+        assert_eq!(
+            lp.collect_fees(6, &amm).unwrap(),
+            LpPosition::fees_earned_between(mid_factor, end_factor, shares_held + added_shares)
+        );
+
+        assert_eq!(
+            LpPosition::fees_earned_between(end_factor, mid_factor, shares_held),
+            0
+        );
+        assert_eq!(
+            LpPosition::fees_earned_between(start_factor, end_factor, 0),
+            0
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_to_json() {
+        let (_, lp) = setup();
+        let json = lp.to_json();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["lp_shares"], lp.lp_shares);
+        assert_eq!(value["withdrawable_lp_shares"], lp.withdrawable_lp_shares);
+        assert_eq!(value["uncollected_fees"], 0);
+        assert_eq!(value["collected_fees"], 0);
+        assert_eq!(value["pending_vesting"]["deposit_slot"], 1);
+        assert_eq!(value["pending_vesting"]["shares"], lp.lp_shares);
+        assert_eq!(
+            serde_json::from_str::<LpPositionJson>(&json).unwrap(),
+            LpPositionJson::from(&lp)
+        );
+
+        // Nothing vesting serializes as null
+        let empty = LpPosition::new_with_reward_factor_snapshot(I80F48::ZERO);
+        let value: serde_json::Value = serde_json::from_str(&empty.to_json()).unwrap();
+        assert!(value["pending_vesting"].is_null());
+    }
+
+    #[test]
+    fn test_max_balanced_withdrawal() {
+        let (mut amm, mut lp) = setup();
+        lp.crank_vesting(3, &amm).unwrap();
+        assert!(lp.withdrawable_lp_shares > 0);
+        let (base, quote) = lp.max_balanced_withdrawal(&amm);
+        assert_eq!(lp.max_removable_shares(&amm), lp.withdrawable_lp_shares);
+        let removed = lp
+            .simulate_remove_at_slot(3, &amm, lp.withdrawable_lp_shares)
+            .unwrap();
+        assert_eq!(base, removed.base_amount_withdrawn);
+        assert_eq!(quote, removed.quote_amount_withdrawn);
+
+        // A pool that is very lopsided towards quote leaves a small position with no base to
+        // withdraw, even though its quote share is well above zero
+        amm.base_reserves = 1_000;
+        let mut small = LpPosition::new_with_reward_factor_snapshot(amm.reward_factor);
+        small.lp_shares = 100_000;
+        small.withdrawable_lp_shares = 100_000;
+        assert!(
+            small
+                .get_withdrawable_base_and_quote_amounts(&amm)
+                .unwrap()
+                .1
+                > 0
+        );
+        assert_eq!(small.max_removable_shares(&amm), 0);
+        assert_eq!(small.max_balanced_withdrawal(&amm), (0, 0));
+        assert!(small.simulate_remove_at_slot(3, &amm, 100_000).is_err());
+
+        // Enough shares for a single atomic base unit lifts the constraint
+        small.lp_shares = 1_000_000;
+        small.withdrawable_lp_shares = 1_000_000;
+        assert_eq!(small.max_removable_shares(&amm), 1_000_000);
+        let (base, quote) = small.max_balanced_withdrawal(&amm);
+        assert_eq!(base, 1);
+        assert!(quote > 0);
+    }
+
+    #[test]
+    fn test_ownership_bps() {
+        let mut amm = Amm::new(30, 0, 0, 0);
+        let mut lp = LpPosition::new_with_reward_factor_snapshot(amm.reward_factor);
+        assert_eq!(lp.ownership_bps(&amm), 0);
+        assert!(lp.ownership_fraction(&amm) == I80F48::ZERO);
+
+        amm.total_lp_shares = 4_000_000;
+        lp.lp_shares = 1_000_000;
+        assert_eq!(lp.ownership_bps(&amm), 2_500);
+        assert!(lp.ownership_fraction(&amm) == I80F48::from_fraction(1, 4));
+
+        // Rounded down
+        lp.lp_shares = 1_399;
+        assert_eq!(lp.ownership_bps(&amm), 3);
+    }
+
+    #[test]
+    fn test_price_for_target_value() {
+        let (amm, mut lp) = setup();
+        lp.crank_vesting(3, &amm).unwrap();
+        let (base, quote) = lp.get_withdrawable_base_and_quote_amounts(&amm).unwrap();
+        let spot_price = I80F48::from_fraction(amm.quote_reserves, amm.base_reserves);
+        let current_value = quote + (I80F48::from_num(base) * spot_price).floor();
+
+        // The current value is reached at about the current price
+        let price = lp.price_for_target_value(&amm, current_value).unwrap();
+        assert!(price.to_bits().abs_diff(spot_price.to_bits()) < 1 << 30);
+
+        // Higher targets need higher prices, growing with the square of the value
+        let higher = lp.price_for_target_value(&amm, current_value * 2).unwrap();
+        assert!(higher > price);
+        assert!(higher.to_bits().abs_diff(price.to_bits() * 4) < 1 << 32);
+        let lower = lp.price_for_target_value(&amm, current_value / 2).unwrap();
+        assert!(lower < price);
+
+        // The position is worth the target at the returned price, up to rounding in the roots
+        let (base_at, quote_at) = lp.value_at_price(&amm, higher);
+        let value_at = quote_at + (I80F48::from_num(base_at) * higher).floor();
+        assert!(value_at.abs_diff(current_value * 2) <= 10);
+
+        let empty = LpPosition::new_with_reward_factor_snapshot(amm.reward_factor);
+        assert_eq!(
+            empty.price_for_target_value(&amm, 1),
+            Err(PlasmaStateError::UnexpectedArgument)
+        );
+        assert_eq!(
+            lp.price_for_target_value(&Amm::new(30, 0, 0, 0), 1),
+            Err(PlasmaStateError::UninitializedPool)
+        );
+    }
+
+    #[test]
+    fn test_withdrawable_amounts_with_large_reserves() {
+        let mut amm = Amm::new(30, 0, 0, 0);
+        amm.base_reserves = u64::MAX / 2;
+        amm.quote_reserves = u64::MAX / 2 - 1;
+        amm.total_lp_shares = 1_000_000_000;
+        let mut lp = LpPosition::new_with_reward_factor_snapshot(amm.reward_factor);
+        lp.lp_shares = 250_000_000;
+        lp.withdrawable_lp_shares = 250_000_000;
+
+        // shares * reserves is far beyond u64::MAX, but a quarter of the reserves is not
+        assert_eq!(
+            lp.get_withdrawable_base_and_quote_amounts(&amm),
+            Ok((u64::MAX / 8, (u64::MAX / 2 - 1) / 4))
+        );
+
+        // A position claiming more shares than the pool cannot be paid out
+        lp.withdrawable_lp_shares = 4_000_000_000;
+        assert_eq!(
+            lp.get_withdrawable_base_and_quote_amounts(&amm),
+            Err(PlasmaStateError::Overflow)
+        );
+
+        // An empty pool backs nothing
+        amm.total_lp_shares = 0;
+        assert_eq!(lp.get_withdrawable_base_and_quote_amounts(&amm), Ok((0, 0)));
+    }
+
+    #[test]
+    fn test_pending_fees() {
+        let mut amm = Amm::new(30, 0, 0, 0);
+        amm.mint(0, 900_000_000, 900_000_000, Some(900_000_000))
+            .unwrap();
+        let mut lp = LpPosition::new_with_reward_factor_snapshot(amm.reward_factor);
+        lp.add_liquidity(1, &mut amm, 100_000_000, 100_000_000, None)
+            .unwrap();
+        assert_eq!(lp.reward_factor_snapshot(), amm.reward_factor);
+        assert_eq!(lp.pending_fees(&amm, 2), 0);
+
+        amm.buy_exact_in(2, 10_000_000).unwrap();
+        let snapshot = lp.reward_factor_snapshot();
+        let pending = lp.pending_fees(&amm, 3);
+        assert!(pending > 0);
+        assert_eq!(
+            pending,
+            LpPosition::fees_earned_between(snapshot, amm.reward_factor, lp.lp_shares)
+        );
+        // Previewing does not touch the position
+        assert_eq!(lp.reward_factor_snapshot(), snapshot);
+        assert_eq!(lp.uncollected_fees(), 0);
+        assert_eq!(lp.collected_fees(), 0);
+
+        assert_eq!(lp.collect_fees(3, &amm).unwrap(), pending);
+        assert_eq!(lp.collected_fees(), pending);
+        assert_eq!(lp.reward_factor_snapshot(), amm.reward_factor);
+        assert_eq!(lp.pending_fees(&amm, 4), 0);
+    }
+}
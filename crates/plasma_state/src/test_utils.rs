@@ -0,0 +1,16 @@
+//! Fixtures shared by the tests of this crate and the crates that depend on it
+
+use crate::amm::Amm;
+
+/// Seeds `amm` with 1e9 base, quote and LP shares at slot 0
+pub fn seed_pool(mut amm: Amm) -> Amm {
+    amm.mint(0, 1_000_000_000, 1_000_000_000, Some(1_000_000_000))
+        .unwrap();
+    amm
+}
+
+/// Returns a pool with no protocol allocation or vesting window, seeded with 1e9 base, quote and
+/// LP shares
+pub fn new_pool(fee_in_bps: u32) -> Amm {
+    seed_pool(Amm::new(fee_in_bps, 0, 0, 0))
+}
@@ -0,0 +1,73 @@
+#[cfg(feature = "borsh")]
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::Upcast;
+
+/// Transfer fees charged by the base and quote mints, in basis points of the amount sent
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "borsh", derive(BorshDeserialize, BorshSerialize))]
+pub struct TransferFeeBps {
+    pub base: u16,
+    pub quote: u16,
+}
+
+impl TransferFeeBps {
+    pub fn is_zero(&self) -> bool {
+        self.base == 0 && self.quote == 0
+    }
+
+    /// Amount that arrives when sending `gross_amount` through a mint charging `fee_bps`. The fee
+    /// is rounded up, matching Token-2022 transfer fees.
+    pub fn amount_after_fee(gross_amount: u64, fee_bps: u16) -> u64 {
+        let fee = (gross_amount.upcast() * fee_bps.upcast()).div_ceil(10_000);
+        gross_amount.saturating_sub(fee as u64)
+    }
+
+    /// Smallest amount to send through a mint charging `fee_bps` so that at least `net_amount`
+    /// arrives. Returns `None` if the fee takes the whole transfer or the amount overflows.
+    pub fn amount_before_fee(net_amount: u64, fee_bps: u16) -> Option<u64> {
+        if fee_bps >= 10_000 {
+            return None;
+        }
+        let gross_amount =
+            u64::try_from((net_amount.upcast() * 10_000).div_ceil(10_000 - fee_bps.upcast()))
+                .ok()?;
+        // Rounding the fee up can leave the estimate one short
+        if Self::amount_after_fee(gross_amount, fee_bps) < net_amount {
+            gross_amount.checked_add(1)
+        } else {
+            Some(gross_amount)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_fee_amounts() {
+        assert_eq!(TransferFeeBps::amount_after_fee(1_000_000, 100), 990_000);
+        assert_eq!(TransferFeeBps::amount_after_fee(1_000_000, 0), 1_000_000);
+        // The fee is rounded up
+        assert_eq!(TransferFeeBps::amount_after_fee(101, 100), 99);
+
+        assert_eq!(TransferFeeBps::amount_before_fee(0, 100), Some(0));
+        assert_eq!(
+            TransferFeeBps::amount_before_fee(990_000, 100),
+            Some(1_000_000)
+        );
+        for net_amount in [1, 99, 101, 12_345, 990_000, u32::MAX as u64] {
+            // The gross amount is the smallest one that delivers the net amount
+            let gross_amount = TransferFeeBps::amount_before_fee(net_amount, 100).unwrap();
+            assert!(TransferFeeBps::amount_after_fee(gross_amount, 100) >= net_amount);
+            assert!(TransferFeeBps::amount_after_fee(gross_amount - 1, 100) < net_amount);
+        }
+        assert_eq!(TransferFeeBps::amount_before_fee(1, 10_000), None);
+        assert_eq!(TransferFeeBps::amount_before_fee(u64::MAX, 100), None);
+
+        assert!(TransferFeeBps::default().is_zero());
+        assert!(!TransferFeeBps { base: 0, quote: 1 }.is_zero());
+    }
+}
@@ -55,13 +55,20 @@ pub struct PoolHeader {
     pub quote_params: TokenParams,
     pub fee_recipients: ProtocolFeeRecipients,
     pub swap_sequence_number: u64,
-    pub padding: [u64; 12],
+    pub authority: Pubkey,
+    pub min_quote_deposit: u64,
+    pub max_quote_tvl: u64,
+    pub creation_slot: u64,
+    pub last_price_update_slot: u64,
+    pub cumulative_price_bits: [u64; 2],
+    pub padding: [u64; 2],
 }
 #[repr(C)]
 #[derive(Debug, Copy, Clone, BorshDeserialize, BorshSerialize)]
 pub struct Amm {
     pub fee_in_bps: u32,
-    pub protocol_allocation_in_pct: u32,
+    pub protocol_allocation_in_pct: u16,
+    pub protocol_allocation_in_bps: u16,
     pub lp_vesting_window: u64,
     pub reward_factor: u128,
     pub total_lp_shares: u64,
@@ -100,6 +107,9 @@ pub struct InitializePoolIxParams {
     protocol_lp_fee_allocation_in_pct: u64,
     fee_recipients_params: [ProtocolFeeRecipientParams; 3],
     num_slots_to_vest_lp_shares: Option<u64>,
+    min_quote_deposit: Option<u64>,
+    max_quote_tvl: Option<u64>,
+    protocol_lp_fee_allocation_in_bps: Option<u64>,
 }
 
 #[repr(C)]
@@ -108,6 +118,14 @@ pub struct AddLiquidityIxParams {
     pub desired_base_amount_in: u64,
     pub desired_quote_amount_in: u64,
     pub initial_lp_shares: Option<u64>,
+    pub expected_transfer_fee_bps: Option<TransferFeeBps>,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, BorshDeserialize, BorshSerialize)]
+pub struct TransferFeeBps {
+    pub base: u16,
+    pub quote: u16,
 }
 
 #[repr(C)]
@@ -121,6 +139,7 @@ pub struct RemoveLiquidityIxParams {
 pub struct SwapIxParams {
     pub side: Side,
     pub swap_type: SwapType,
+    pub deadline_slot: Option<u64>,
 }
 
 #[repr(C)]
@@ -135,6 +154,12 @@ pub struct RenounceLiquidityIxParams {
     pub allow_fee_withdrawal: bool,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, BorshDeserialize, BorshSerialize)]
+pub struct SetLpFeeIxParams {
+    pub lp_fee_in_bps: u64,
+}
+
 /* Accounts */
 
 #[repr(C)]
@@ -177,6 +202,8 @@ pub struct SwapEvent {
     pub snapshot_base_liquidity: u64,
     pub snapshot_quote_liquidity: u64,
     pub swap_result: SwapResult,
+    pub lp_fee_quote: u64,
+    pub protocol_fee_quote: u64,
 }
 
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
@@ -235,6 +262,7 @@ pub struct InitializePoolEvent {
     pub lp_fee_in_bps: u64,
     pub protocol_fee_in_pct: u64,
     pub fee_recipient_params: [ProtocolFeeRecipientParams; 3],
+    pub protocol_fee_in_bps: u64,
 }
 
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
@@ -248,6 +276,42 @@ pub struct WithdrawProtocolFeesEvent {
     pub fees_withdrawn: u64,
 }
 
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct TransferLiquidityEvent {
+    pub src: Pubkey,
+    pub dst: Pubkey,
+    pub lp_shares_transferred: u64,
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct SetLpFeeEvent {
+    pub old_lp_fee_in_bps: u64,
+    pub new_lp_fee_in_bps: u64,
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct BatchInitializeLpPositionsEvent {
+    pub owners: Vec<Pubkey>,
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct CrankVestingEvent {
+    pub owner: Pubkey,
+    pub shares_vested: u64,
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct ReinvestProtocolFeesEvent {
+    pub protocol_fee_recipient: Pubkey,
+    pub fees_reinvested: u64,
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct ReleaseVestingEvent {
+    pub owner: Pubkey,
+    pub shares_released: u64,
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
 pub enum PlasmaEvent {
@@ -283,4 +347,32 @@ pub enum PlasmaEvent {
         header: PlasmaEventHeader,
         event: WithdrawProtocolFeesEvent,
     },
+    // This doesnt exist but need so the discriminators match the instructions
+    Log {
+        header: PlasmaEventHeader,
+    },
+    TransferLiquidity {
+        header: PlasmaEventHeader,
+        event: TransferLiquidityEvent,
+    },
+    SetLpFee {
+        header: PlasmaEventHeader,
+        event: SetLpFeeEvent,
+    },
+    BatchInitializeLpPositions {
+        header: PlasmaEventHeader,
+        event: BatchInitializeLpPositionsEvent,
+    },
+    CrankVesting {
+        header: PlasmaEventHeader,
+        event: CrankVestingEvent,
+    },
+    ReinvestProtocolFees {
+        header: PlasmaEventHeader,
+        event: ReinvestProtocolFeesEvent,
+    },
+    ReleaseVesting {
+        header: PlasmaEventHeader,
+        event: ReleaseVestingEvent,
+    },
 }
@@ -149,6 +149,11 @@ pub fn process_instruction(
             liquidity::process_initialize_lp_position(&pool_context, accounts)
                 .and_then(|event| record_event!(plasma_log_context, pool_context, event))?
         }
+        PlasmaInstruction::BatchInitializeLpPositions => {
+            msg!("BatchInitializeLpPositions");
+            liquidity::process_batch_initialize_lp_positions(&pool_context, accounts)
+                .and_then(|event| record_event!(plasma_log_context, pool_context, event))?
+        }
         PlasmaInstruction::Swap => {
             msg!("Swap");
             swap::process_swap(&pool_context, accounts, data)
@@ -184,6 +189,26 @@ pub fn process_instruction(
             liquidity::process_transfer_liquidity(&pool_context, accounts)
                 .and_then(|event| record_event!(plasma_log_context, pool_context, event))?
         }
+        PlasmaInstruction::CrankVesting => {
+            msg!("CrankVesting");
+            liquidity::process_crank_vesting(&pool_context, accounts)
+                .and_then(|event| record_event!(plasma_log_context, pool_context, event))?
+        }
+        PlasmaInstruction::ReinvestProtocolFees => {
+            msg!("ReinvestProtocolFees");
+            fees::process_reinvest_protocol_fees(&pool_context)
+                .and_then(|event| record_event!(plasma_log_context, pool_context, event))?
+        }
+        PlasmaInstruction::ReleaseVesting => {
+            msg!("ReleaseVesting");
+            liquidity::process_release_vesting(&pool_context, accounts)
+                .and_then(|event| record_event!(plasma_log_context, pool_context, event))?
+        }
+        PlasmaInstruction::SetLpFee => {
+            msg!("SetLpFee");
+            fees::process_set_lp_fee(&pool_context, data)
+                .and_then(|event| record_event!(plasma_log_context, pool_context, event))?
+        }
         PlasmaInstruction::Log => {
             // The log instruction is handled at the beginning of this function
             unreachable!()
@@ -7,7 +7,9 @@ use std::{
 use bytemuck::{Pod, Zeroable};
 use solana_program::{msg, program_error::ProgramError, pubkey::Pubkey};
 
-use plasma_amm_state::{amm::Amm, lp::LpPosition};
+#[cfg(test)]
+use plasma_amm_state::test_utils::{new_pool, seed_pool};
+use plasma_amm_state::{amm::Amm, errors::PlasmaStateError, fixed::I80F48, lp::LpPosition};
 
 use crate::assert_with_msg;
 
@@ -71,7 +73,23 @@ pub struct PoolHeader {
     pub quote_params: TokenParams,
     pub fee_recipients: ProtocolFeeRecipients,
     pub swap_sequence_number: u64,
-    pub padding: [u64; 12],
+    /// The authority that is allowed to update the pool's LP fee
+    pub authority: Pubkey,
+    /// Deposits whose quote amount falls below this threshold are rejected. Zero disables the check
+    pub min_quote_deposit: u64,
+    /// Deposits that would push the pool's quote-denominated TVL above this cap are rejected. Zero disables the check
+    pub max_quote_tvl: u64,
+    /// The slot at which the pool was initialized. Zero for pools created before this was recorded
+    pub creation_slot: u64,
+    /// The slot at which `cumulative_price` was last advanced, i.e. the slot it is current as of.
+    /// Zero until the pool first has liquidity
+    pub last_price_update_slot: u64,
+    /// Raw `I80F48` bits of the running sum of the spot price (quote per base), weighted by the
+    /// number of slots it was held, stored low word first to keep the header 8-byte aligned.
+    /// Read it through `cumulative_price`. Wraps on overflow; see `Amm::twap` for turning two readings
+    /// into an average price
+    pub cumulative_price_bits: [u64; 2],
+    pub padding: [u64; 2],
 }
 
 #[derive(Debug, Copy, Clone, Zeroable, Pod)]
@@ -81,6 +99,18 @@ pub struct PoolAccount {
     pub amm: Amm,
 }
 
+impl PoolHeader {
+    pub fn cumulative_price(&self) -> I80F48 {
+        let [low, high] = self.cumulative_price_bits;
+        I80F48::from_bits(((high as i128) << 64) | low as i128)
+    }
+
+    pub fn set_cumulative_price(&mut self, cumulative_price: I80F48) {
+        let bits = cumulative_price.to_bits();
+        self.cumulative_price_bits = [bits as u64, (bits >> 64) as u64];
+    }
+}
+
 impl PoolAccount {
     pub fn increment_sequence_number(&mut self) {
         self.header.sequence_number += 1;
@@ -91,6 +121,30 @@ impl PoolAccount {
         self.header.swap_sequence_number
     }
 
+    /// Advances the cumulative price by the spot price held since the last update. Called before
+    /// every swap, deposit, withdrawal and donation so that each price is weighted by the slots it
+    /// was in effect.
+    ///
+    /// The clock only runs while the pool has liquidity. New pools, and pools created before the
+    /// accumulator existed, store a zero update slot, and a pool without reserves has no price, so
+    /// in either case the update only restarts the clock rather than crediting a price for the
+    /// slots since then.
+    pub fn update_cumulative_price(&mut self, slot: u64) {
+        if self.header.last_price_update_slot == 0 || !self.amm.has_liquidity() {
+            self.header.last_price_update_slot = slot;
+            return;
+        }
+        let elapsed_slots = slot.saturating_sub(self.header.last_price_update_slot);
+        if elapsed_slots == 0 {
+            return;
+        }
+        let cumulative_price = self
+            .amm
+            .accumulate_price(self.header.cumulative_price(), elapsed_slots);
+        self.header.set_cumulative_price(cumulative_price);
+        self.header.last_price_update_slot = slot;
+    }
+
     pub fn update_protocol_fee_recipients_post_swap(&mut self) -> Result<(), ProgramError> {
         let total_shares = self
             .header
@@ -129,6 +183,65 @@ impl PoolAccount {
         Ok(())
     }
 
+    /// Updates the LP fee of the pool and returns the previous fee. Errors if the signer is not the pool authority,
+    /// or if the new fee is not below 500 bps, the cap `InitializePool` also enforces. Pools created before the
+    /// authority was recorded store the default pubkey, which cannot sign, so their fee is fixed.
+    ///
+    /// Fees that were already charged are accounted for in the reward factor, so only future swaps are affected.
+    pub fn set_lp_fee(
+        &mut self,
+        authority: &Pubkey,
+        lp_fee_in_bps: u64,
+    ) -> Result<u64, ProgramError> {
+        assert_with_msg(
+            self.header.authority == *authority,
+            ProgramError::InvalidArgument,
+            "Signer is not the pool authority",
+        )?;
+        assert_with_msg(
+            lp_fee_in_bps < 500,
+            ProgramError::InvalidArgument,
+            "LP fee is capped at 5%",
+        )?;
+        let old_lp_fee_in_bps = self.amm.fee_in_bps as u64;
+        self.amm.fee_in_bps = lp_fee_in_bps as u32;
+        Ok(old_lp_fee_in_bps)
+    }
+
+    /// Errors if a deposit of `quote_amount_deposited` is below the pool's minimum quote deposit.
+    pub fn check_min_quote_deposit(
+        &self,
+        quote_amount_deposited: u64,
+    ) -> Result<(), PlasmaStateError> {
+        if quote_amount_deposited < self.header.min_quote_deposit {
+            return Err(PlasmaStateError::BelowMinimumDeposit {
+                quote_amount_deposited,
+                min_quote_deposit: self.header.min_quote_deposit,
+            });
+        }
+        Ok(())
+    }
+
+    /// Errors with `UninitializedPool` if the pool has no liquidity to swap against.
+    pub fn check_has_liquidity(&self) -> Result<(), PlasmaStateError> {
+        if !self.amm.has_liquidity() {
+            return Err(PlasmaStateError::UninitializedPool);
+        }
+        Ok(())
+    }
+
+    /// Errors if the pool's quote-denominated TVL exceeds the pool's cap.
+    pub fn check_max_quote_tvl(&self) -> Result<(), PlasmaStateError> {
+        let max_quote_tvl = self.header.max_quote_tvl;
+        if max_quote_tvl != 0 && self.amm.quote_tvl() > max_quote_tvl as u128 {
+            return Err(PlasmaStateError::TvlCapExceeded {
+                quote_tvl: self.amm.quote_tvl(),
+                max_quote_tvl,
+            });
+        }
+        Ok(())
+    }
+
     /// Withdraws protocol fees for a given recipient. Error if the recipient is not one of the protocol fee recipients.
     pub fn withdraw_protocol_fee(&mut self, recipient: &Pubkey) -> Result<u64, ProgramError> {
         let recipient_index = self
@@ -146,6 +259,58 @@ impl PoolAccount {
 
         Ok(withdrawable_amount)
     }
+
+    /// Immediately vests all pending shares of an LP position, making them withdrawable, and
+    /// returns the number of shares released. Errors if the signer is not the pool authority or
+    /// if the position is owned by the pool authority.
+    ///
+    /// This trusts the pool authority not to collude with LPs. The authority's own positions are
+    /// excluded so it cannot add liquidity, release it and remove it within the vesting window.
+    ///
+    /// Fees are accrued up to `slot` first, so the release does not affect the position's fees.
+    pub fn release_vesting(
+        &self,
+        authority: &Pubkey,
+        lp_position_owner: &Pubkey,
+        slot: u64,
+        lp_position: &mut LpPosition,
+    ) -> Result<u64, ProgramError> {
+        assert_with_msg(
+            self.header.authority == *authority,
+            ProgramError::InvalidArgument,
+            "Signer is not the pool authority",
+        )?;
+        assert_with_msg(
+            self.header.authority != *lp_position_owner,
+            ProgramError::InvalidArgument,
+            "Cannot release vesting for a position owned by the pool authority",
+        )?;
+        let release = |lp_position: &mut LpPosition| -> Result<u64, PlasmaStateError> {
+            let shares_vested = lp_position.crank_vesting(slot, &self.amm)?;
+            Ok(shares_vested + lp_position.force_vest()?)
+        };
+        release(lp_position).map_err(|e| {
+            msg!("Error releasing vesting: {:?}", e);
+            ProgramError::InvalidArgument
+        })
+    }
+
+    /// Collects the protocol fees owed to a given recipient and donates them to the pool's quote
+    /// reserves. Error if the recipient is not one of the protocol fee recipients.
+    pub fn reinvest_protocol_fee(
+        &mut self,
+        slot: u64,
+        recipient: &Pubkey,
+    ) -> Result<u64, ProgramError> {
+        let reinvested_amount = self.withdraw_protocol_fee(recipient)?;
+        self.amm
+            .donate_quote(slot, reinvested_amount)
+            .map_err(|e| {
+                msg!("Error reinvesting protocol fees: {:?}", e);
+                ProgramError::InvalidArgument
+            })?;
+        Ok(reinvested_amount)
+    }
 }
 
 impl Deref for PoolAccount {
@@ -235,3 +400,221 @@ impl DerefMut for LpPositionAccount {
         &mut self.lp_position
     }
 }
+
+#[test]
+fn test_set_lp_fee() {
+    let authority = Pubkey::new_unique();
+    let mut pool = PoolAccount::zeroed();
+    pool.header.authority = authority;
+    pool.amm = new_pool(30);
+    pool.amm.buy_exact_in(1, 10_000_000).unwrap();
+    let reward_factor = pool.amm.reward_factor;
+
+    // Only the pool authority can update the fee
+    assert!(pool.set_lp_fee(&Pubkey::new_unique(), 100).is_err());
+    // The fee is capped
+    assert!(pool.set_lp_fee(&authority, 500).is_err());
+
+    assert_eq!(pool.set_lp_fee(&authority, 100).unwrap(), 30);
+    assert_eq!(pool.amm.fee_in_bps, 100);
+    // Previously accrued fees are untouched
+    assert!(pool.amm.reward_factor == reward_factor);
+
+    let swap_result = pool.amm.buy_exact_in(2, 10_000_000).unwrap();
+    assert!(swap_result.realized_fee_bps().unwrap().abs_diff(100) <= 1);
+}
+
+#[test]
+fn test_check_min_quote_deposit() {
+    let mut pool = PoolAccount::zeroed();
+    // The default of zero accepts any deposit
+    assert!(pool.check_min_quote_deposit(0).is_ok());
+
+    pool.header.min_quote_deposit = 1_000;
+    assert_eq!(
+        pool.check_min_quote_deposit(999),
+        Err(PlasmaStateError::BelowMinimumDeposit {
+            quote_amount_deposited: 999,
+            min_quote_deposit: 1_000,
+        })
+    );
+    assert!(pool.check_min_quote_deposit(1_000).is_ok());
+}
+
+#[test]
+fn test_check_has_liquidity() {
+    let mut pool = PoolAccount::zeroed();
+    assert_eq!(
+        pool.check_has_liquidity(),
+        Err(PlasmaStateError::UninitializedPool)
+    );
+
+    pool.amm = new_pool(30);
+    assert!(pool.check_has_liquidity().is_ok());
+}
+
+#[test]
+fn test_check_max_quote_tvl() {
+    let mut pool = PoolAccount::zeroed();
+    pool.amm = Amm::new(30, 0, 0, 0);
+    pool.amm.mint(0, 1_000, 250, Some(500)).unwrap();
+    // The default of zero leaves the pool uncapped
+    assert!(pool.check_max_quote_tvl().is_ok());
+
+    pool.header.max_quote_tvl = 600;
+    assert!(pool.check_max_quote_tvl().is_ok());
+
+    // A deposit that pushes the TVL over the cap is rejected
+    pool.amm.mint(0, 1_000, 250, None).unwrap();
+    assert_eq!(
+        pool.check_max_quote_tvl(),
+        Err(PlasmaStateError::TvlCapExceeded {
+            quote_tvl: 1_000,
+            max_quote_tvl: 600,
+        })
+    );
+}
+
+#[test]
+fn test_reinvest_protocol_fee() {
+    let recipient = Pubkey::new_unique();
+    let mut pool = PoolAccount::zeroed();
+    pool.header.fee_recipients.recipients[0] = ProtocolFeeRecipient {
+        recipient,
+        shares: 1,
+        total_accumulated_quote_fees: 0,
+        collected_quote_fees: 0,
+    };
+    pool.amm = seed_pool(Amm::new(30, 50, 0, 0));
+    pool.amm.buy_exact_in(1, 10_000_000).unwrap();
+    pool.update_protocol_fee_recipients_post_swap().unwrap();
+
+    let owed = pool.header.fee_recipients.recipients[0].total_accumulated_quote_fees;
+    assert!(owed > 0);
+    let quote_reserves = pool.amm.quote_reserves;
+    let total_lp_shares = pool.amm.total_lp_shares;
+
+    assert!(pool
+        .reinvest_protocol_fee(2, &Pubkey::new_unique())
+        .is_err());
+    assert_eq!(pool.reinvest_protocol_fee(2, &recipient).unwrap(), owed);
+    assert_eq!(pool.amm.quote_reserves, quote_reserves + owed);
+    assert_eq!(pool.amm.total_lp_shares, total_lp_shares);
+    let fee_recipient = pool.header.fee_recipients.recipients[0];
+    assert_eq!(
+        fee_recipient.total_accumulated_quote_fees - fee_recipient.collected_quote_fees,
+        0
+    );
+
+    // Nothing is left to reinvest
+    assert_eq!(pool.reinvest_protocol_fee(2, &recipient).unwrap(), 0);
+    assert_eq!(pool.amm.quote_reserves, quote_reserves + owed);
+}
+
+#[test]
+fn test_release_vesting() {
+    let authority = Pubkey::new_unique();
+    let mut pool = PoolAccount::zeroed();
+    pool.header.authority = authority;
+    pool.amm = seed_pool(Amm::new(30, 0, 100, 0));
+
+    let mut lp_position = LpPosition::new_with_reward_factor_snapshot(pool.amm.reward_factor);
+    lp_position
+        .add_liquidity(4, &mut pool.amm, 1_000_000, 1_000_000, None)
+        .unwrap();
+    let lp_shares = lp_position.lp_shares;
+    assert!(lp_shares > 0);
+    assert_eq!(lp_position.withdrawable_lp_shares, 0);
+    assert_eq!(
+        lp_position.pending_shares_to_vest.lp_shares_to_vest,
+        lp_shares
+    );
+
+    let owner = Pubkey::new_unique();
+
+    // Only the pool authority can release vesting
+    assert!(pool
+        .release_vesting(&Pubkey::new_unique(), &owner, 8, &mut lp_position)
+        .is_err());
+    assert_eq!(lp_position.withdrawable_lp_shares, 0);
+
+    // The pool authority cannot release vesting on its own position
+    assert!(pool
+        .release_vesting(&authority, &authority, 8, &mut lp_position)
+        .is_err());
+    assert_eq!(lp_position.withdrawable_lp_shares, 0);
+
+    // The vesting window has not elapsed, but the shares are released anyway
+    assert_eq!(
+        pool.release_vesting(&authority, &owner, 8, &mut lp_position)
+            .unwrap(),
+        lp_shares
+    );
+    assert_eq!(lp_position.withdrawable_lp_shares, lp_shares);
+    assert!(!lp_position.pending_shares_to_vest.is_vesting());
+
+    // Nothing is left to release
+    assert_eq!(
+        pool.release_vesting(&authority, &owner, 8, &mut lp_position)
+            .unwrap(),
+        0
+    );
+}
+
+#[test]
+fn test_cumulative_price_across_swaps() {
+    let mut pool = PoolAccount::zeroed();
+    pool.header.last_price_update_slot = 100;
+    pool.amm = Amm::new(0, 0, 0, 100);
+    pool.amm
+        .mint(100, 1_000_000_000, 1_000_000_000, Some(1_000_000_000))
+        .unwrap();
+
+    // The price of 1 is held for 10 slots before the first swap
+    pool.update_cumulative_price(110);
+    pool.amm.buy_exact_in(108, 1_000_000_000).unwrap();
+    assert!(pool.header.cumulative_price() == I80F48::from_num(10));
+    assert_eq!(pool.header.last_price_update_slot, 110);
+
+    // A second swap in the same slot does not advance the accumulator
+    pool.update_cumulative_price(110);
+    assert!(pool.header.cumulative_price() == I80F48::from_num(10));
+
+    // The post-swap price is then held for 20 slots
+    let post_swap_price = I80F48::from_fraction(pool.amm.quote_reserves, pool.amm.base_reserves);
+    pool.update_cumulative_price(130);
+    pool.amm.sell_exact_in(128, 1_000_000).unwrap();
+    let expected = I80F48::from_num(10) + post_swap_price * I80F48::from_num(20);
+    assert!(pool.header.cumulative_price() == expected);
+    assert_eq!(pool.header.last_price_update_slot, 130);
+
+    let twap = Amm::twap(I80F48::ZERO, 100, pool.header.cumulative_price(), 130);
+    assert!(twap > I80F48::from_num(1) && twap < post_swap_price);
+
+    // The stored words round-trip values that have wrapped negative
+    let mut header = pool.header;
+    header.set_cumulative_price(I80F48::from_bits(-(1 << 70) - 3));
+    assert!(header.cumulative_price() == I80F48::from_bits(-(1 << 70) - 3));
+
+    // A pool from before the accumulator existed starts it on its first update
+    let mut legacy = PoolAccount::zeroed();
+    legacy.amm = pool.amm;
+    legacy.update_cumulative_price(1_000);
+    assert!(legacy.header.cumulative_price() == I80F48::ZERO);
+    assert_eq!(legacy.header.last_price_update_slot, 1_000);
+
+    // An empty pool has no price, so its clock restarts until the first deposit lands
+    let mut empty = PoolAccount::zeroed();
+    empty.amm = Amm::new(0, 0, 0, 0);
+    empty.update_cumulative_price(2_000);
+    empty.update_cumulative_price(2_100);
+    assert!(empty.header.cumulative_price() == I80F48::ZERO);
+    assert_eq!(empty.header.last_price_update_slot, 2_100);
+    empty
+        .amm
+        .mint(2_100, 1_000_000_000, 2_000_000_000, Some(1_414_213_562))
+        .unwrap();
+    // Only the slots since the deposit count
+    empty.update_cumulative_price(2_110);
+    assert!(empty.header.cumulative_price() == I80F48::from_num(20));
+}
@@ -59,6 +59,26 @@ pub enum PlasmaEvent {
         header: PlasmaEventHeader,
         event: TransferLiquidityEvent,
     },
+    SetLpFee {
+        header: PlasmaEventHeader,
+        event: SetLpFeeEvent,
+    },
+    BatchInitializeLpPositions {
+        header: PlasmaEventHeader,
+        event: BatchInitializeLpPositionsEvent,
+    },
+    CrankVesting {
+        header: PlasmaEventHeader,
+        event: CrankVestingEvent,
+    },
+    ReinvestProtocolFees {
+        header: PlasmaEventHeader,
+        event: ReinvestProtocolFeesEvent,
+    },
+    ReleaseVesting {
+        header: PlasmaEventHeader,
+        event: ReleaseVestingEvent,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,6 +91,10 @@ pub struct SwapEvent {
     pub snapshot_base_liquidity: u64,
     pub snapshot_quote_liquidity: u64,
     pub swap_result: SwapResult,
+    /// Part of `swap_result.fee_in_quote` that accrued to LPs
+    pub lp_fee_quote: u64,
+    /// Part of `swap_result.fee_in_quote` allocated to the protocol fee recipients
+    pub protocol_fee_quote: u64,
 }
 impl From<(PlasmaEventHeader, SwapEvent)> for PlasmaEvent {
     fn from(value: (PlasmaEventHeader, SwapEvent)) -> Self {
@@ -173,8 +197,11 @@ impl From<(PlasmaEventHeader, WithdrawLpFeesEvent)> for PlasmaEvent {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InitializePoolEvent {
     pub lp_fee_in_bps: u64,
+    /// The protocol's share of the LP fee rounded down to a whole percentage, see
+    /// `protocol_fee_in_bps` for the exact value
     pub protocol_fee_in_pct: u64,
     pub fee_recipient_params: [ProtocolFeeRecipientParams; 3],
+    pub protocol_fee_in_bps: u64,
 }
 impl From<(PlasmaEventHeader, InitializePoolEvent)> for PlasmaEvent {
     fn from(value: (PlasmaEventHeader, InitializePoolEvent)) -> Self {
@@ -213,3 +240,72 @@ impl From<(PlasmaEventHeader, TransferLiquidityEvent)> for PlasmaEvent {
         }
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetLpFeeEvent {
+    pub old_lp_fee_in_bps: u64,
+    pub new_lp_fee_in_bps: u64,
+}
+impl From<(PlasmaEventHeader, SetLpFeeEvent)> for PlasmaEvent {
+    fn from(value: (PlasmaEventHeader, SetLpFeeEvent)) -> Self {
+        PlasmaEvent::SetLpFee {
+            header: value.0,
+            event: value.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchInitializeLpPositionsEvent {
+    pub owners: Vec<Pubkey>,
+}
+impl From<(PlasmaEventHeader, BatchInitializeLpPositionsEvent)> for PlasmaEvent {
+    fn from(value: (PlasmaEventHeader, BatchInitializeLpPositionsEvent)) -> Self {
+        PlasmaEvent::BatchInitializeLpPositions {
+            header: value.0,
+            event: value.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrankVestingEvent {
+    pub owner: Pubkey,
+    pub shares_vested: u64,
+}
+impl From<(PlasmaEventHeader, CrankVestingEvent)> for PlasmaEvent {
+    fn from(value: (PlasmaEventHeader, CrankVestingEvent)) -> Self {
+        PlasmaEvent::CrankVesting {
+            header: value.0,
+            event: value.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReinvestProtocolFeesEvent {
+    pub protocol_fee_recipient: Pubkey,
+    pub fees_reinvested: u64,
+}
+impl From<(PlasmaEventHeader, ReinvestProtocolFeesEvent)> for PlasmaEvent {
+    fn from(value: (PlasmaEventHeader, ReinvestProtocolFeesEvent)) -> Self {
+        PlasmaEvent::ReinvestProtocolFees {
+            header: value.0,
+            event: value.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseVestingEvent {
+    pub owner: Pubkey,
+    pub shares_released: u64,
+}
+impl From<(PlasmaEventHeader, ReleaseVestingEvent)> for PlasmaEvent {
+    fn from(value: (PlasmaEventHeader, ReleaseVestingEvent)) -> Self {
+        PlasmaEvent::ReleaseVesting {
+            header: value.0,
+            event: value.1,
+        }
+    }
+}
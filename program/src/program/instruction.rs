@@ -6,7 +6,7 @@ use shank::ShankInstruction;
 #[rustfmt::skip]
 pub enum PlasmaInstruction {
     // Pool instructions
-    /// Send a swap order
+    /// Send a swap order. Fails with custom error `UNINITIALIZED_POOL_ERROR_CODE` if the pool has no liquidity
     #[account(0, name = "plasma_program", desc = "Plasma program")]
     #[account(1, name = "log_authority", desc = "Plasma log authority")]
     #[account(2, writable, name = "pool", desc = "This account holds the pool state")]
@@ -107,6 +107,51 @@ pub enum PlasmaInstruction {
     #[account(4, writable, name = "src_lp_position")]
     #[account(5, writable, name = "dst_lp_position")]
     TransferLiquidity = 9,
+
+    /// Update the LP fee of the pool. The new fee must be below 500 bps (5%), the same cap as at
+    /// pool initialization. Pools created before the authority was recorded have the default
+    /// pubkey as their authority, so their fee cannot be changed
+    #[account(0, name = "plasma_program", desc = "Plasma program")]
+    #[account(1, name = "log_authority", desc = "Plasma log authority")]
+    #[account(2, writable, name = "pool", desc = "This account holds the pool state")]
+    #[account(3, signer, name = "pool_authority", desc = "The authority of the pool")]
+    SetLpFee = 10,
+
+    /// Initialize up to 8 LP positions at once, with the payer funding all of them.
+    /// Each LP position is passed after the system program, preceded by its owner
+    #[account(0, name = "plasma_program", desc = "Plasma program")]
+    #[account(1, name = "log_authority", desc = "Plasma log authority")]
+    #[account(2, writable, name = "pool", desc = "This account holds the pool state")]
+    #[account(3, writable, signer, name = "payer")]
+    #[account(4, name = "system_program", desc = "System program")]
+    BatchInitializeLpPositions = 11,
+
+    /// Vest the pending shares of an LP position. This instruction is permissionless
+    #[account(0, name = "plasma_program", desc = "Plasma program")]
+    #[account(1, name = "log_authority", desc = "Plasma log authority")]
+    #[account(2, writable, name = "pool", desc = "This account holds the pool state")]
+    #[account(3, signer, name = "cranker")]
+    #[account(4, name = "lp_position_owner")]
+    #[account(5, writable, name = "lp_position")]
+    CrankVesting = 12,
+
+    /// Donate the signer's accrued protocol fees to the pool's quote reserves without minting LP shares
+    #[account(0, name = "plasma_program", desc = "Plasma program")]
+    #[account(1, name = "log_authority", desc = "Plasma log authority")]
+    #[account(2, writable, name = "pool", desc = "This account holds the pool state")]
+    #[account(3, signer, name = "protocol_fee_recipient", desc = "Recipient of protocol fees")]
+    ReinvestProtocolFees = 13,
+
+    /// Immediately vest the pending shares of an LP position, e.g. when migrating positions.
+    /// The pool authority is trusted not to release positions it colludes with, and cannot
+    /// release its own positions
+    #[account(0, name = "plasma_program", desc = "Plasma program")]
+    #[account(1, name = "log_authority", desc = "Plasma log authority")]
+    #[account(2, writable, name = "pool", desc = "This account holds the pool state")]
+    #[account(3, signer, name = "pool_authority", desc = "The authority of the pool")]
+    #[account(4, name = "lp_position_owner")]
+    #[account(5, writable, name = "lp_position")]
+    ReleaseVesting = 14,
 }
 
 impl PlasmaInstruction {
@@ -122,7 +167,7 @@ fn test_instruction_serialization() {
             Ok(j) => j,
             Err(_) => {
                 // This needs to be changed if new instructions are added
-                assert!(i > 7);
+                assert!(i > 14);
                 continue;
             }
         };
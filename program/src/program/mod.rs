@@ -3,5 +3,7 @@ pub mod events;
 pub mod instruction;
 pub mod processor;
 pub mod system_utils;
+#[cfg(test)]
+pub(crate) mod test_utils;
 pub mod token_utils;
 pub mod validation;
@@ -1,13 +1,22 @@
+use borsh::{BorshDeserialize as Deserialize, BorshSerialize as Serialize};
 use bytemuck::try_from_bytes_mut;
 use solana_program::{
     account_info::AccountInfo, clock::Clock, msg, program_error::ProgramError, sysvar::Sysvar,
 };
 
-use crate::program::{
-    accounts::{LpPositionAccount, LpPositionStatus, PoolAccount},
-    events::{WithdrawLpFeesEvent, WithdrawProtocolFeesEvent},
-    token_utils::{maybe_invoke_withdraw, MaybeInvokeWithdrawParams},
-    validation::loaders::{PlasmaPoolContext, WithdrawLpFeesContext, WithdrawProtocolFeesContext},
+use crate::{
+    program::{
+        accounts::{LpPositionAccount, LpPositionStatus, PoolAccount},
+        events::{
+            ReinvestProtocolFeesEvent, SetLpFeeEvent, WithdrawLpFeesEvent,
+            WithdrawProtocolFeesEvent,
+        },
+        token_utils::{maybe_invoke_withdraw, MaybeInvokeWithdrawParams},
+        validation::loaders::{
+            PlasmaPoolContext, WithdrawLpFeesContext, WithdrawProtocolFeesContext,
+        },
+    },
+    LEADER_SLOT_WINDOW,
 };
 
 pub(crate) fn process_withdraw_lp_fees<'a, 'info>(
@@ -101,3 +110,62 @@ pub(crate) fn process_withdraw_protocol_fees<'a, 'info>(
         protocol_fee_recipient: *recipient,
     })
 }
+
+pub(crate) fn process_reinvest_protocol_fees<'a, 'info>(
+    pool_context: &PlasmaPoolContext<'a, 'info>,
+) -> Result<ReinvestProtocolFeesEvent, ProgramError> {
+    // Get the active leader slot
+    let clock_slot = Clock::get()?.slot;
+    let slot = (clock_slot / LEADER_SLOT_WINDOW) * LEADER_SLOT_WINDOW;
+
+    let mut pool_bytes = pool_context.pool_info.try_borrow_mut_data()?;
+    let pool = try_from_bytes_mut::<PoolAccount>(&mut pool_bytes)
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    // Weight the price before the donation moves it by the slots it was in effect
+    pool.update_cumulative_price(clock_slot);
+
+    let recipient = pool_context.signer.key;
+    let fees_reinvested = pool.reinvest_protocol_fee(slot, recipient)?;
+
+    msg!(
+        "Reinvesting {} protocol fees for {}",
+        fees_reinvested,
+        recipient
+    );
+
+    Ok(ReinvestProtocolFeesEvent {
+        protocol_fee_recipient: *recipient,
+        fees_reinvested,
+    })
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct SetLpFeeParams {
+    pub lp_fee_in_bps: u64,
+}
+
+pub(crate) fn process_set_lp_fee<'a, 'info>(
+    pool_context: &PlasmaPoolContext<'a, 'info>,
+    data: &[u8],
+) -> Result<SetLpFeeEvent, ProgramError> {
+    let SetLpFeeParams { lp_fee_in_bps } = SetLpFeeParams::try_from_slice(data)?;
+
+    let mut pool_bytes = pool_context.pool_info.try_borrow_mut_data()?;
+    let pool = try_from_bytes_mut::<PoolAccount>(&mut pool_bytes)
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    let old_lp_fee_in_bps = pool.set_lp_fee(pool_context.signer.key, lp_fee_in_bps)?;
+
+    msg!(
+        "Updated LP fee from {} bps to {} bps",
+        old_lp_fee_in_bps,
+        lp_fee_in_bps
+    );
+
+    Ok(SetLpFeeEvent {
+        old_lp_fee_in_bps,
+        new_lp_fee_in_bps: lp_fee_in_bps,
+    })
+}
@@ -31,11 +31,55 @@ pub struct ProtocolFeeRecipientParams {
 #[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
 pub struct InitializePoolParams {
     lp_fee_in_bps: u64,
+    /// Whole percentage of the LP fee allocated to the protocol. Ignored if
+    /// `protocol_lp_fee_allocation_in_bps` is set
     protocol_lp_fee_allocation_in_pct: u64,
     fee_recipients_params: [ProtocolFeeRecipientParams; 3],
     /// This is the number of slots that the LP shares will be vested over
     /// If this value is not a multiple of the leader slot window, it will be rounded down
     num_slots_to_vest_lp_shares: Option<u64>,
+    /// Deposits with a quote amount below this value are rejected. Defaults to zero (no minimum)
+    min_quote_deposit: Option<u64>,
+    /// Deposits that would push the pool's quote-denominated TVL above this cap are rejected. Defaults to zero (no cap)
+    max_quote_tvl: Option<u64>,
+    /// Share of the LP fee allocated to the protocol in basis points, for allocations that are not
+    /// a whole percentage. Takes precedence over `protocol_lp_fee_allocation_in_pct`
+    protocol_lp_fee_allocation_in_bps: Option<u64>,
+}
+
+impl InitializePoolParams {
+    /// Deserializes the params. The fields after `num_slots_to_vest_lp_shares` may be omitted
+    /// entirely so that clients built before they were added keep working.
+    pub fn unpack(mut data: &[u8]) -> Result<Self, ProgramError> {
+        let lp_fee_in_bps = u64::deserialize(&mut data)?;
+        let protocol_lp_fee_allocation_in_pct = u64::deserialize(&mut data)?;
+        let fee_recipients_params = <[ProtocolFeeRecipientParams; 3]>::deserialize(&mut data)?;
+        let num_slots_to_vest_lp_shares = Option::<u64>::deserialize(&mut data)?;
+        let min_quote_deposit = if data.is_empty() {
+            None
+        } else {
+            Option::<u64>::deserialize(&mut data)?
+        };
+        let max_quote_tvl = if data.is_empty() {
+            None
+        } else {
+            Option::<u64>::deserialize(&mut data)?
+        };
+        let protocol_lp_fee_allocation_in_bps = if data.is_empty() {
+            None
+        } else {
+            Option::<u64>::try_from_slice(data)?
+        };
+        Ok(Self {
+            lp_fee_in_bps,
+            protocol_lp_fee_allocation_in_pct,
+            fee_recipients_params,
+            num_slots_to_vest_lp_shares,
+            min_quote_deposit,
+            max_quote_tvl,
+            protocol_lp_fee_allocation_in_bps,
+        })
+    }
 }
 
 pub(crate) fn process_initialize_pool<'a, 'info>(
@@ -58,10 +102,13 @@ pub(crate) fn process_initialize_pool<'a, 'info>(
 
     let InitializePoolParams {
         lp_fee_in_bps,
-        protocol_lp_fee_allocation_in_pct: protocol_fee_allocation_in_pct,
+        protocol_lp_fee_allocation_in_pct,
         fee_recipients_params,
         num_slots_to_vest_lp_shares: vesting_slot_window,
-    } = InitializePoolParams::try_from_slice(data)?;
+        min_quote_deposit,
+        max_quote_tvl,
+        protocol_lp_fee_allocation_in_bps,
+    } = InitializePoolParams::unpack(data)?;
 
     assert_with_msg(
         lp_fee_in_bps < 500,
@@ -70,11 +117,19 @@ pub(crate) fn process_initialize_pool<'a, 'info>(
     )?;
 
     assert_with_msg(
-        protocol_fee_allocation_in_pct <= 100,
+        protocol_lp_fee_allocation_in_pct <= 100,
         ProgramError::InvalidArgument,
         "The protocol fee allocation must be less than or equal to 100%",
     )?;
 
+    let protocol_fee_allocation_in_bps =
+        protocol_lp_fee_allocation_in_bps.unwrap_or(protocol_lp_fee_allocation_in_pct * 100);
+    assert_with_msg(
+        protocol_fee_allocation_in_bps <= 10000,
+        ProgramError::InvalidArgument,
+        "The protocol fee allocation must be less than or equal to 10000 bps",
+    )?;
+
     assert_with_msg(
         fee_recipients_params
             .iter()
@@ -170,6 +225,8 @@ pub(crate) fn process_initialize_pool<'a, 'info>(
         ProtocolFeeRecipients::new(fee_recipients)
     };
 
+    let clock_slot = Clock::get()?.slot;
+
     // Populate the header data
     pool.header = PoolHeader {
         discriminator: POOL_ACCOUNT_DISCRIMINATOR,
@@ -188,13 +245,20 @@ pub(crate) fn process_initialize_pool<'a, 'info>(
         },
         fee_recipients,
         swap_sequence_number: 0,
-        padding: [0; 12],
+        authority: *pool_creator.key,
+        min_quote_deposit: min_quote_deposit.unwrap_or(0),
+        max_quote_tvl: max_quote_tvl.unwrap_or(0),
+        creation_slot: clock_slot,
+        // The clock starts with the first deposit, since the pool has no price until then
+        last_price_update_slot: 0,
+        cumulative_price_bits: [0; 2],
+        padding: [0; 2],
     };
 
-    let slot = (Clock::get()?.slot / LEADER_SLOT_WINDOW) * LEADER_SLOT_WINDOW;
-    pool.amm = Amm::new(
+    let slot = (clock_slot / LEADER_SLOT_WINDOW) * LEADER_SLOT_WINDOW;
+    pool.amm = Amm::new_with_protocol_allocation_bps(
         lp_fee_in_bps as u32,
-        protocol_fee_allocation_in_pct as u32,
+        protocol_fee_allocation_in_bps as u32,
         vesting_slot_window
             .map(|v| (v / LEADER_SLOT_WINDOW) * LEADER_SLOT_WINDOW)
             .unwrap_or(2 * LEADER_SLOT_WINDOW),
@@ -203,7 +267,50 @@ pub(crate) fn process_initialize_pool<'a, 'info>(
 
     Ok(InitializePoolEvent {
         lp_fee_in_bps,
-        protocol_fee_in_pct: protocol_fee_allocation_in_pct,
+        protocol_fee_in_pct: protocol_fee_allocation_in_bps / 100,
         fee_recipient_params: fee_recipients_params,
+        protocol_fee_in_bps: protocol_fee_allocation_in_bps,
     })
 }
+
+#[test]
+fn test_unpack_initialize_pool_params() {
+    let params = InitializePoolParams {
+        lp_fee_in_bps: 30,
+        protocol_lp_fee_allocation_in_pct: 20,
+        num_slots_to_vest_lp_shares: Some(100),
+        min_quote_deposit: Some(1_000),
+        protocol_lp_fee_allocation_in_bps: Some(2_050),
+        ..Default::default()
+    };
+    let data = params.try_to_vec().unwrap();
+    let unpacked = InitializePoolParams::unpack(&data).unwrap();
+    assert_eq!(unpacked.min_quote_deposit, Some(1_000));
+    assert_eq!(unpacked.num_slots_to_vest_lp_shares, Some(100));
+    assert_eq!(unpacked.protocol_lp_fee_allocation_in_bps, Some(2_050));
+
+    // Params serialized before the allocation could be given in basis points only have the
+    // percentage
+    let data = &data[..data.len() - 9];
+    let unpacked = InitializePoolParams::unpack(data).unwrap();
+    assert_eq!(unpacked.protocol_lp_fee_allocation_in_pct, 20);
+    assert_eq!(unpacked.protocol_lp_fee_allocation_in_bps, None);
+
+    // Params serialized before the minimum deposit was added have no minimum
+    let legacy = &data[..data.len() - 10];
+    let unpacked = InitializePoolParams::unpack(legacy).unwrap();
+    assert_eq!(unpacked.lp_fee_in_bps, 30);
+    assert_eq!(unpacked.num_slots_to_vest_lp_shares, Some(100));
+    assert_eq!(unpacked.min_quote_deposit, None);
+    assert_eq!(unpacked.max_quote_tvl, None);
+
+    // Params serialized before the TVL cap was added have no cap
+    let legacy = &data[..data.len() - 1];
+    let unpacked = InitializePoolParams::unpack(legacy).unwrap();
+    assert_eq!(unpacked.min_quote_deposit, Some(1_000));
+    assert_eq!(unpacked.max_quote_tvl, None);
+
+    let mut trailing = params.try_to_vec().unwrap();
+    trailing.push(0);
+    assert!(InitializePoolParams::unpack(&trailing).is_err());
+}
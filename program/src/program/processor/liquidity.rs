@@ -2,12 +2,18 @@ use std::mem::size_of;
 
 use borsh::{BorshDeserialize as Deserialize, BorshSerialize as Serialize};
 use bytemuck::{try_from_bytes, try_from_bytes_mut};
-use plasma_amm_state::lp::{AddLiquidityResult, LpPosition, RemoveLiquidityResult};
+use plasma_amm_state::{
+    fixed::I80F48,
+    lp::{AddLiquidityResult, LpPosition, RemoveLiquidityResult},
+    transfer_fee::TransferFeeBps,
+};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     clock::Clock,
+    entrypoint::ProgramResult,
     msg,
     program_error::ProgramError,
+    pubkey::Pubkey,
     rent::Rent,
     sysvar::Sysvar,
 };
@@ -19,7 +25,8 @@ use crate::{
             LpPositionAccount, LpPositionStatus, PoolAccount, LP_POSITION_ACCOUNT_DISCRIMINATOR,
         },
         events::{
-            AddLiquidityEvent, InitializeLpPositionEvent, RemoveLiquidityEvent,
+            AddLiquidityEvent, BatchInitializeLpPositionsEvent, CrankVestingEvent,
+            InitializeLpPositionEvent, ReleaseVestingEvent, RemoveLiquidityEvent,
             RenounceLiquidityEvent, TransferLiquidityEvent,
         },
         system_utils::create_account,
@@ -27,27 +34,25 @@ use crate::{
         validation::{
             checkers::plasma_checkers::LpPositionAccountInfo,
             loaders::{
-                get_lp_position_address, InitializeLpPositionContext, LiquidityActionContext,
-                PlasmaPoolContext, PlasmaVaultContext,
+                get_lp_position_address, BatchInitializeLpPositionsContext, CrankVestingContext,
+                InitializeLpPositionContext, LiquidityActionContext, PlasmaPoolContext,
+                PlasmaVaultContext,
             },
         },
     },
     LEADER_SLOT_WINDOW,
 };
 
-pub(crate) fn process_initialize_lp_position<'a, 'info>(
-    pool_context: &PlasmaPoolContext<'a, 'info>,
-    accounts: &[AccountInfo<'info>],
-) -> Result<InitializeLpPositionEvent, ProgramError> {
-    let pool_key = pool_context.pool_info.key;
+/// The maximum number of LP positions that can be created in a single `BatchInitializeLpPositions` instruction
+pub const MAX_LP_POSITIONS_PER_BATCH: usize = 8;
 
-    let InitializeLpPositionContext {
-        lp_position_owner,
-        lp_position,
-        system_program,
-    } = InitializeLpPositionContext::load(accounts)?;
-
-    let (lp_position_address, bump) = get_lp_position_address(pool_key, lp_position_owner.key);
+/// Checks that `lp_position` is the uninitialized LP position PDA of `lp_position_owner` and returns its bump
+fn validate_lp_position_address(
+    pool_key: &Pubkey,
+    lp_position_owner: &Pubkey,
+    lp_position: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (lp_position_address, bump) = get_lp_position_address(pool_key, lp_position_owner);
 
     assert_with_msg(
         &lp_position_address == lp_position.key,
@@ -59,21 +64,54 @@ pub(crate) fn process_initialize_lp_position<'a, 'info>(
         ProgramError::InvalidAccountData,
         "LpPosition account is already initialized",
     )?;
+    Ok(bump)
+}
+
+/// Writes a new, empty LP position into an allocated `lp_position` account
+fn write_lp_position(
+    pool_key: &Pubkey,
+    lp_position_owner: &Pubkey,
+    lp_position: &AccountInfo,
+    reward_factor: I80F48,
+) -> ProgramResult {
+    let mut lp_position_bytes = lp_position.try_borrow_mut_data()?;
+    *try_from_bytes_mut::<LpPositionAccount>(&mut lp_position_bytes)
+        .map_err(|_| ProgramError::InvalidAccountData)? = LpPositionAccount {
+        discriminator: LP_POSITION_ACCOUNT_DISCRIMINATOR,
+        authority: *lp_position_owner,
+        pool: *pool_key,
+        status: LpPositionStatus::Active as u64,
+        lp_position: LpPosition::new_with_reward_factor_snapshot(reward_factor),
+    };
+    Ok(())
+}
+
+/// Allocates the LP position PDA of `lp_position_owner` (funded by the signer of the pool context)
+/// and initializes it at the pool's current reward factor
+fn create_lp_position<'a, 'info>(
+    pool_context: &PlasmaPoolContext<'a, 'info>,
+    lp_position_owner: &Pubkey,
+    lp_position: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+    rent: &Rent,
+) -> ProgramResult {
+    let pool_key = pool_context.pool_info.key;
+    let bump = validate_lp_position_address(pool_key, lp_position_owner, lp_position)?;
 
     let space = size_of::<LpPositionAccount>();
     let seeds = vec![
         b"lp_position".to_vec(),
         pool_key.as_ref().to_vec(),
-        lp_position_owner.key.as_ref().to_vec(),
+        lp_position_owner.as_ref().to_vec(),
         vec![bump],
     ];
 
     create_account(
         &pool_context.signer,
-        lp_position.as_ref(),
-        system_program.as_ref(),
+        lp_position,
+        system_program,
         &crate::id(),
-        &Rent::get()?,
+        rent,
         space as u64,
         seeds,
     )?;
@@ -84,27 +122,94 @@ pub(crate) fn process_initialize_lp_position<'a, 'info>(
         ProgramError::InvalidAccountData
     })?;
 
-    let mut lp_position_bytes = lp_position.try_borrow_mut_data()?;
-    *try_from_bytes_mut::<LpPositionAccount>(&mut lp_position_bytes)
-        .map_err(|_| ProgramError::InvalidAccountData)? = LpPositionAccount {
-        discriminator: LP_POSITION_ACCOUNT_DISCRIMINATOR,
-        authority: *lp_position_owner.key,
-        pool: *pool_key,
-        status: LpPositionStatus::Active as u64,
-        lp_position: LpPosition::new_with_reward_factor_snapshot(pool.reward_factor),
-    };
+    write_lp_position(pool_key, lp_position_owner, lp_position, pool.reward_factor)
+}
+
+pub(crate) fn process_initialize_lp_position<'a, 'info>(
+    pool_context: &PlasmaPoolContext<'a, 'info>,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<InitializeLpPositionEvent, ProgramError> {
+    let InitializeLpPositionContext {
+        lp_position_owner,
+        lp_position,
+        system_program,
+    } = InitializeLpPositionContext::load(accounts)?;
+
+    create_lp_position(
+        pool_context,
+        lp_position_owner.key,
+        lp_position.as_ref(),
+        system_program.as_ref(),
+        &Rent::get()?,
+    )?;
 
     Ok(InitializeLpPositionEvent {
         owner: *lp_position_owner.key,
     })
 }
 
+pub(crate) fn process_batch_initialize_lp_positions<'a, 'info>(
+    pool_context: &PlasmaPoolContext<'a, 'info>,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<BatchInitializeLpPositionsEvent, ProgramError> {
+    let BatchInitializeLpPositionsContext {
+        system_program,
+        lp_positions,
+    } = BatchInitializeLpPositionsContext::load(accounts)?;
+
+    assert_with_msg(
+        !lp_positions.is_empty() && lp_positions.len() <= MAX_LP_POSITIONS_PER_BATCH,
+        ProgramError::InvalidArgument,
+        "Must initialize between 1 and 8 LP positions",
+    )?;
+
+    let rent = Rent::get()?;
+    let mut owners = Vec::with_capacity(lp_positions.len());
+    for (lp_position_owner, lp_position) in lp_positions.iter() {
+        // Duplicate owners are rejected because their position is no longer empty
+        create_lp_position(
+            pool_context,
+            lp_position_owner.key,
+            lp_position.as_ref(),
+            system_program.as_ref(),
+            &rent,
+        )?;
+        owners.push(*lp_position_owner.key);
+    }
+
+    Ok(BatchInitializeLpPositionsEvent { owners })
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct AddLiquidityParams {
     pub desired_base_amount_in: u64,
     pub desired_quote_amount_in: u64,
     pub initial_lp_shares: Option<u64>,
+    /// Transfer fees the depositor expects the base and quote mints to charge. Pools only hold
+    /// SPL Token mints, which charge no transfer fees, so any non-zero fee is rejected.
+    pub expected_transfer_fee_bps: Option<TransferFeeBps>,
+}
+
+impl AddLiquidityParams {
+    /// Deserializes the params. `expected_transfer_fee_bps` may be omitted entirely so that
+    /// clients built before it was added keep working.
+    pub fn unpack(mut data: &[u8]) -> Result<Self, ProgramError> {
+        let desired_base_amount_in = u64::deserialize(&mut data)?;
+        let desired_quote_amount_in = u64::deserialize(&mut data)?;
+        let initial_lp_shares = Option::<u64>::deserialize(&mut data)?;
+        let expected_transfer_fee_bps = if data.is_empty() {
+            None
+        } else {
+            Option::<TransferFeeBps>::try_from_slice(data)?
+        };
+        Ok(Self {
+            desired_base_amount_in,
+            desired_quote_amount_in,
+            initial_lp_shares,
+            expected_transfer_fee_bps,
+        })
+    }
 }
 
 pub(crate) fn process_add_liquidity<'a, 'info>(
@@ -129,15 +234,25 @@ pub(crate) fn process_add_liquidity<'a, 'info>(
         desired_base_amount_in,
         desired_quote_amount_in,
         initial_lp_shares,
-    } = AddLiquidityParams::try_from_slice(data)?;
+        expected_transfer_fee_bps,
+    } = AddLiquidityParams::unpack(data)?;
+    assert_with_msg(
+        expected_transfer_fee_bps.unwrap_or_default().is_zero(),
+        ProgramError::InvalidArgument,
+        "Transfer fees are not supported, pools only hold SPL Token mints",
+    )?;
 
     // Get the active leader slot
-    let slot = (Clock::get()?.slot / LEADER_SLOT_WINDOW) * LEADER_SLOT_WINDOW;
+    let clock_slot = Clock::get()?.slot;
+    let slot = (clock_slot / LEADER_SLOT_WINDOW) * LEADER_SLOT_WINDOW;
 
     let mut pool_bytes = pool_context.pool_info.try_borrow_mut_data()?;
     let pool = try_from_bytes_mut::<PoolAccount>(&mut *pool_bytes)
         .map_err(|_| ProgramError::InvalidAccountData)?;
 
+    // Weight the pre-deposit price by the slots it was in effect
+    pool.update_cumulative_price(clock_slot);
+
     if pool.total_lp_shares == 0 {
         assert_with_msg(
             initial_lp_shares.is_some(),
@@ -185,8 +300,23 @@ pub(crate) fn process_add_liquidity<'a, 'info>(
             ProgramError::InvalidArgument
         })?;
 
-    let (user_total_withdrawable_base, user_total_withdrawable_quote) =
-        lp_position.get_withdrawable_base_and_quote_amounts(pool);
+    pool.check_min_quote_deposit(quote_amount_deposited)
+        .map_err(|e| {
+            msg!("Error adding liquidity: {}", e);
+            ProgramError::InvalidArgument
+        })?;
+
+    pool.check_max_quote_tvl().map_err(|e| {
+        msg!("Error adding liquidity: {}", e);
+        ProgramError::InvalidArgument
+    })?;
+
+    let (user_total_withdrawable_base, user_total_withdrawable_quote) = lp_position
+        .get_withdrawable_base_and_quote_amounts(pool)
+        .map_err(|e| {
+            msg!("Error computing withdrawable amounts: {}", e);
+            ProgramError::InvalidArgument
+        })?;
 
     try_deposit(TryDepositParams {
         token_program: &token_program,
@@ -242,12 +372,16 @@ pub(crate) fn process_remove_liqidity<'a, 'info>(
     let lp_shares = u64::try_from_slice(data)?;
 
     // Get the active leader slot
-    let slot = (Clock::get()?.slot / LEADER_SLOT_WINDOW) * LEADER_SLOT_WINDOW;
+    let clock_slot = Clock::get()?.slot;
+    let slot = (clock_slot / LEADER_SLOT_WINDOW) * LEADER_SLOT_WINDOW;
 
     let mut pool_bytes = pool_context.pool_info.try_borrow_mut_data()?;
     let pool = try_from_bytes_mut::<PoolAccount>(&mut *pool_bytes)
         .map_err(|_| ProgramError::InvalidAccountData)?;
 
+    // Weight the pre-withdrawal price by the slots it was in effect
+    pool.update_cumulative_price(clock_slot);
+
     let mut lp_position_bytes = lp_position_account.info.try_borrow_mut_data()?;
     let lp_position = try_from_bytes_mut::<LpPositionAccount>(&mut *lp_position_bytes)
         .map_err(|_| ProgramError::InvalidAccountData)?;
@@ -273,8 +407,12 @@ pub(crate) fn process_remove_liqidity<'a, 'info>(
             ProgramError::InvalidArgument
         })?;
 
-    let (user_total_withdrawable_base, user_total_withdrawable_quote) =
-        lp_position.get_withdrawable_base_and_quote_amounts(pool);
+    let (user_total_withdrawable_base, user_total_withdrawable_quote) = lp_position
+        .get_withdrawable_base_and_quote_amounts(pool)
+        .map_err(|e| {
+            msg!("Error computing withdrawable amounts: {}", e);
+            ProgramError::InvalidArgument
+        })?;
 
     try_withdraw(TryWithdrawParams {
         pool_key: &pool_context.pool_info.key,
@@ -411,3 +549,555 @@ pub(crate) fn process_transfer_liquidity<'a, 'info>(
         lp_shares_transferred,
     })
 }
+
+pub(crate) fn process_crank_vesting<'a, 'info>(
+    pool_context: &PlasmaPoolContext<'a, 'info>,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<CrankVestingEvent, ProgramError> {
+    let CrankVestingContext {
+        lp_position_owner,
+        lp_position: lp_position_account,
+    } = CrankVestingContext::load(pool_context, accounts)?;
+
+    // Get the active leader slot
+    let slot = (Clock::get()?.slot / LEADER_SLOT_WINDOW) * LEADER_SLOT_WINDOW;
+
+    let pool_bytes = pool_context.pool_info.try_borrow_data()?;
+    let pool = try_from_bytes::<PoolAccount>(&pool_bytes).map_err(|_| {
+        msg!("Failed to deserialize pool account");
+        ProgramError::InvalidAccountData
+    })?;
+
+    let mut lp_position_bytes = lp_position_account.info.try_borrow_mut_data()?;
+    let lp_position = try_from_bytes_mut::<LpPositionAccount>(&mut lp_position_bytes)
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    let shares_vested = lp_position.crank_vesting(slot, pool).map_err(|e| {
+        msg!("Error vesting shares: {:?}", e);
+        ProgramError::InvalidArgument
+    })?;
+
+    Ok(CrankVestingEvent {
+        owner: *lp_position_owner.key,
+        shares_vested,
+    })
+}
+
+pub(crate) fn process_release_vesting<'a, 'info>(
+    pool_context: &PlasmaPoolContext<'a, 'info>,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<ReleaseVestingEvent, ProgramError> {
+    // The position accounts are laid out the same as for cranking
+    let CrankVestingContext {
+        lp_position_owner,
+        lp_position: lp_position_account,
+    } = CrankVestingContext::load(pool_context, accounts)?;
+
+    // Get the active leader slot
+    let slot = (Clock::get()?.slot / LEADER_SLOT_WINDOW) * LEADER_SLOT_WINDOW;
+
+    let pool_bytes = pool_context.pool_info.try_borrow_data()?;
+    let pool = try_from_bytes::<PoolAccount>(&pool_bytes).map_err(|_| {
+        msg!("Failed to deserialize pool account");
+        ProgramError::InvalidAccountData
+    })?;
+
+    let mut lp_position_bytes = lp_position_account.info.try_borrow_mut_data()?;
+    let lp_position = try_from_bytes_mut::<LpPositionAccount>(&mut lp_position_bytes)
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    let shares_released = pool.release_vesting(
+        pool_context.signer.key,
+        lp_position_owner.key,
+        slot,
+        lp_position,
+    )?;
+
+    msg!(
+        "Released {} vesting shares for {}",
+        shares_released,
+        lp_position_owner.key
+    );
+
+    Ok(ReleaseVestingEvent {
+        owner: *lp_position_owner.key,
+        shares_released,
+    })
+}
+
+#[test]
+fn test_batch_initialize_lp_positions() {
+    let pool_key = Pubkey::new_unique();
+    let owners = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let reward_factor = I80F48::from_num(3);
+
+    for owner in owners.iter() {
+        let lp_position_key = get_lp_position_address(&pool_key, owner).0;
+        let system_program_id = solana_program::system_program::id();
+
+        // The position PDA is derived from the pool and owner and must not exist yet
+        let (mut lamports, mut data) = (0, vec![]);
+        let empty_lp_position = AccountInfo::new(
+            &lp_position_key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &system_program_id,
+            false,
+            0,
+        );
+        validate_lp_position_address(&pool_key, owner, &empty_lp_position).unwrap();
+        assert!(
+            validate_lp_position_address(&pool_key, &Pubkey::new_unique(), &empty_lp_position)
+                .is_err()
+        );
+
+        // Once allocated, the position is written for its owner
+        let (mut lamports, mut data) = (1, vec![0; size_of::<LpPositionAccount>()]);
+        let program_id = crate::id();
+        let lp_position = AccountInfo::new(
+            &lp_position_key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &program_id,
+            false,
+            0,
+        );
+        write_lp_position(&pool_key, owner, &lp_position, reward_factor).unwrap();
+        {
+            let lp_position_bytes = lp_position.try_borrow_data().unwrap();
+            let account = try_from_bytes::<LpPositionAccount>(&lp_position_bytes).unwrap();
+            assert_eq!(account.discriminator, LP_POSITION_ACCOUNT_DISCRIMINATOR);
+            assert_eq!(account.authority, *owner);
+            assert_eq!(account.pool, pool_key);
+            assert_eq!(account.status, LpPositionStatus::Active as u64);
+            assert_eq!(account.lp_shares, 0);
+        }
+
+        // The same position cannot be initialized twice
+        assert!(validate_lp_position_address(&pool_key, owner, &lp_position).is_err());
+    }
+}
+
+#[test]
+fn test_process_batch_initialize_lp_positions() {
+    use crate::program::{
+        accounts::POOL_ACCOUNT_DISCRIMINATOR,
+        test_utils::{install_syscall_stubs, new_account_info},
+        validation::checkers::{plasma_checkers::PoolAccountInfo, Signer},
+    };
+    use bytemuck::{bytes_of, Zeroable};
+    use solana_program::system_program;
+
+    install_syscall_stubs();
+    let mut pool = PoolAccount::zeroed();
+    pool.header.discriminator = POOL_ACCOUNT_DISCRIMINATOR;
+    pool.amm.reward_factor = I80F48::from_num(3);
+    let pool_info = new_account_info(
+        Pubkey::new_unique(),
+        crate::id(),
+        false,
+        1,
+        bytes_of(&pool).to_vec(),
+    );
+    let payer = new_account_info(
+        Pubkey::new_unique(),
+        system_program::id(),
+        true,
+        1_000_000_000,
+        vec![],
+    );
+    let pool_context = PlasmaPoolContext {
+        pool_info: PoolAccountInfo::new(&pool_info).unwrap(),
+        signer: Signer::new(&payer).unwrap(),
+    };
+
+    let owners = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let mut accounts = vec![new_account_info(
+        system_program::id(),
+        Pubkey::default(),
+        false,
+        1,
+        vec![],
+    )];
+    for owner in owners.iter() {
+        let lp_position_key = get_lp_position_address(pool_info.key, owner).0;
+        accounts.push(new_account_info(
+            *owner,
+            system_program::id(),
+            false,
+            0,
+            vec![],
+        ));
+        accounts.push(new_account_info(
+            lp_position_key,
+            system_program::id(),
+            false,
+            0,
+            vec![],
+        ));
+    }
+
+    // An owner without its LP position is rejected
+    assert!(process_batch_initialize_lp_positions(&pool_context, &accounts[..6]).is_err());
+    // So is an empty batch
+    assert!(process_batch_initialize_lp_positions(&pool_context, &accounts[..1]).is_err());
+
+    let event = process_batch_initialize_lp_positions(&pool_context, &accounts).unwrap();
+    assert_eq!(event.owners, owners.to_vec());
+
+    // Each position PDA now exists, is rent exempt and belongs to its owner
+    let rent = Rent::default();
+    for (owner, lp_position) in owners.iter().zip(accounts[1..].chunks_exact(2)) {
+        let lp_position = &lp_position[1];
+        assert_eq!(lp_position.data_len(), size_of::<LpPositionAccount>());
+        assert_eq!(
+            lp_position.lamports(),
+            rent.minimum_balance(size_of::<LpPositionAccount>())
+        );
+        let lp_position_bytes = lp_position.try_borrow_data().unwrap();
+        let account = try_from_bytes::<LpPositionAccount>(&lp_position_bytes).unwrap();
+        assert_eq!(account.discriminator, LP_POSITION_ACCOUNT_DISCRIMINATOR);
+        assert_eq!(account.authority, *owner);
+        assert_eq!(account.pool, *pool_info.key);
+        assert_eq!(account.status, LpPositionStatus::Active as u64);
+        assert!(account.reward_factor_snapshot() == I80F48::from_num(3));
+    }
+
+    // Running the batch again fails because the positions already exist
+    assert!(process_batch_initialize_lp_positions(&pool_context, &accounts).is_err());
+}
+
+#[test]
+fn test_transfer_then_renounce_liquidity() {
+    use crate::program::{
+        accounts::POOL_ACCOUNT_DISCRIMINATOR,
+        test_utils::{install_syscall_stubs, new_account_info},
+        validation::checkers::{plasma_checkers::PoolAccountInfo, Signer},
+    };
+    use bytemuck::{bytes_of, Zeroable};
+    use plasma_amm_state::amm::Amm;
+    use solana_program::system_program;
+
+    install_syscall_stubs();
+    let pool_key = Pubkey::new_unique();
+    let src_owner = Pubkey::new_unique();
+    let dst_owner = Pubkey::new_unique();
+
+    let mut pool = PoolAccount::zeroed();
+    pool.header.discriminator = POOL_ACCOUNT_DISCRIMINATOR;
+    pool.amm = Amm::new(30, 0, 0, 0);
+    let mut src_lp_position = LpPositionAccount {
+        discriminator: LP_POSITION_ACCOUNT_DISCRIMINATOR,
+        authority: src_owner,
+        pool: pool_key,
+        status: LpPositionStatus::Active as u64,
+        lp_position: LpPosition::new_with_reward_factor_snapshot(pool.amm.reward_factor),
+    };
+    src_lp_position
+        .add_liquidity(0, &mut pool.amm, 1_000_000, 1_000_000, Some(1_000_000))
+        .unwrap();
+    let dst_lp_position = LpPositionAccount {
+        authority: dst_owner,
+        lp_position: LpPosition::new_with_reward_factor_snapshot(pool.amm.reward_factor),
+        ..src_lp_position
+    };
+
+    let pool_info = new_account_info(pool_key, crate::id(), false, 1, bytes_of(&pool).to_vec());
+    let src_info = new_account_info(src_owner, system_program::id(), true, 1, vec![]);
+    let dst_info = new_account_info(dst_owner, system_program::id(), true, 1, vec![]);
+    let src_lp_position_info = new_account_info(
+        get_lp_position_address(&pool_key, &src_owner).0,
+        crate::id(),
+        false,
+        1,
+        bytes_of(&src_lp_position).to_vec(),
+    );
+    let dst_lp_position_info = new_account_info(
+        get_lp_position_address(&pool_key, &dst_owner).0,
+        crate::id(),
+        false,
+        1,
+        bytes_of(&dst_lp_position).to_vec(),
+    );
+    let src_context = PlasmaPoolContext {
+        pool_info: PoolAccountInfo::new(&pool_info).unwrap(),
+        signer: Signer::new(&src_info).unwrap(),
+    };
+    let dst_context = PlasmaPoolContext {
+        pool_info: PoolAccountInfo::new(&pool_info).unwrap(),
+        signer: Signer::new(&dst_info).unwrap(),
+    };
+
+    // The transfer signed by the source owner goes through while both positions are active
+    let event = process_transfer_liquidity(
+        &src_context,
+        &[src_lp_position_info.clone(), dst_lp_position_info.clone()],
+    )
+    .unwrap();
+    assert_eq!(event.lp_shares_transferred, 1_000_000);
+
+    // The destination owner can then renounce the position now holding the shares
+    let event = process_renounce_liqidity(
+        &dst_context,
+        std::slice::from_ref(&dst_lp_position_info),
+        &true.try_to_vec().unwrap(),
+    )
+    .unwrap();
+    assert!(event.allow_fee_withdrawal);
+    {
+        let dst_lp_position_bytes = dst_lp_position_info.try_borrow_data().unwrap();
+        let dst_lp_position = try_from_bytes::<LpPositionAccount>(&dst_lp_position_bytes).unwrap();
+        assert_eq!(
+            dst_lp_position.status,
+            LpPositionStatus::RenouncedWithFeeWithdawal as u64
+        );
+        assert_eq!(dst_lp_position.lp_shares, 1_000_000);
+    }
+
+    // Renouncing first would have made the transfer fail, in either direction
+    assert!(process_transfer_liquidity(
+        &dst_context,
+        &[dst_lp_position_info.clone(), src_lp_position_info.clone()],
+    )
+    .is_err());
+    assert!(process_transfer_liquidity(
+        &src_context,
+        &[src_lp_position_info, dst_lp_position_info],
+    )
+    .is_err());
+}
+
+#[test]
+fn test_process_release_vesting() {
+    use crate::program::{
+        accounts::POOL_ACCOUNT_DISCRIMINATOR,
+        test_utils::{install_syscall_stubs, new_account_info, TEST_SLOT},
+        validation::checkers::{plasma_checkers::PoolAccountInfo, Signer},
+    };
+    use bytemuck::{bytes_of, Zeroable};
+    use plasma_amm_state::{amm::Amm, test_utils::seed_pool};
+    use solana_program::system_program;
+
+    install_syscall_stubs();
+    let pool_key = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+
+    // The shares are minted one leader slot window before the clock, so they are still vesting
+    let mut pool = PoolAccount::zeroed();
+    pool.header.discriminator = POOL_ACCOUNT_DISCRIMINATOR;
+    pool.header.authority = authority;
+    pool.amm = seed_pool(Amm::new(30, 0, 100, 0));
+    let new_lp_position_info = |pool: &mut PoolAccount, owner: &Pubkey| {
+        let mut lp_position = LpPositionAccount {
+            discriminator: LP_POSITION_ACCOUNT_DISCRIMINATOR,
+            authority: *owner,
+            pool: pool_key,
+            status: LpPositionStatus::Active as u64,
+            lp_position: LpPosition::new_with_reward_factor_snapshot(pool.amm.reward_factor),
+        };
+        lp_position
+            .add_liquidity(
+                TEST_SLOT - LEADER_SLOT_WINDOW,
+                &mut pool.amm,
+                1_000_000,
+                1_000_000,
+                None,
+            )
+            .unwrap();
+        new_account_info(
+            get_lp_position_address(&pool_key, owner).0,
+            crate::id(),
+            false,
+            1,
+            bytes_of(&lp_position).to_vec(),
+        )
+    };
+    let lp_position_info = new_lp_position_info(&mut pool, &owner);
+    let authority_lp_position_info = new_lp_position_info(&mut pool, &authority);
+
+    let pool_info = new_account_info(pool_key, crate::id(), false, 1, bytes_of(&pool).to_vec());
+    let authority_info = new_account_info(authority, system_program::id(), true, 1, vec![]);
+    let owner_info = new_account_info(owner, system_program::id(), true, 1, vec![]);
+    let accounts = [owner_info.clone(), lp_position_info.clone()];
+
+    let read_lp_position = |info: &AccountInfo| {
+        *try_from_bytes::<LpPositionAccount>(&info.try_borrow_data().unwrap()).unwrap()
+    };
+    let lp_shares = read_lp_position(&lp_position_info).lp_shares;
+    assert!(lp_shares > 0);
+    assert_eq!(
+        read_lp_position(&lp_position_info).withdrawable_lp_shares,
+        0
+    );
+
+    // Only the pool authority can release vesting
+    let owner_context = PlasmaPoolContext {
+        pool_info: PoolAccountInfo::new(&pool_info).unwrap(),
+        signer: Signer::new(&owner_info).unwrap(),
+    };
+    assert!(process_release_vesting(&owner_context, &accounts).is_err());
+    assert_eq!(
+        read_lp_position(&lp_position_info).withdrawable_lp_shares,
+        0
+    );
+
+    // The pool authority cannot release vesting on its own position
+    let authority_context = PlasmaPoolContext {
+        pool_info: PoolAccountInfo::new(&pool_info).unwrap(),
+        signer: Signer::new(&authority_info).unwrap(),
+    };
+    let authority_accounts = [authority_info.clone(), authority_lp_position_info.clone()];
+    assert!(process_release_vesting(&authority_context, &authority_accounts).is_err());
+    assert_eq!(
+        read_lp_position(&authority_lp_position_info).withdrawable_lp_shares,
+        0
+    );
+
+    // The vesting window has not elapsed, but the shares are released anyway
+    let event = process_release_vesting(&authority_context, &accounts).unwrap();
+    assert_eq!(event.owner, owner);
+    assert_eq!(event.shares_released, lp_shares);
+    let lp_position = read_lp_position(&lp_position_info);
+    assert_eq!(lp_position.withdrawable_lp_shares, lp_shares);
+    assert!(!lp_position.pending_shares_to_vest.is_vesting());
+}
+
+#[test]
+fn test_process_add_liquidity_rejects_transfer_fees() {
+    use crate::program::{
+        accounts::POOL_ACCOUNT_DISCRIMINATOR,
+        test_utils::{install_syscall_stubs, new_account_info, new_token_account_info},
+        validation::checkers::{plasma_checkers::PoolAccountInfo, Signer},
+    };
+    use bytemuck::{bytes_of, Zeroable};
+    use plasma_amm_state::{amm::Amm, test_utils::seed_pool};
+    use solana_program::system_program;
+
+    install_syscall_stubs();
+    let pool_key = Pubkey::new_unique();
+    let trader = Pubkey::new_unique();
+
+    let mut pool = PoolAccount::zeroed();
+    pool.header.discriminator = POOL_ACCOUNT_DISCRIMINATOR;
+    for params in [&mut pool.header.base_params, &mut pool.header.quote_params] {
+        params.mint_key = Pubkey::new_unique();
+        params.vault_key = Pubkey::new_unique();
+    }
+    pool.amm = seed_pool(Amm::new(30, 0, 0, 0));
+    let lp_position = LpPositionAccount {
+        discriminator: LP_POSITION_ACCOUNT_DISCRIMINATOR,
+        authority: trader,
+        pool: pool_key,
+        status: LpPositionStatus::Active as u64,
+        lp_position: LpPosition::new_with_reward_factor_snapshot(pool.amm.reward_factor),
+    };
+
+    let (base_params, quote_params) = (pool.header.base_params, pool.header.quote_params);
+    let pool_info = new_account_info(pool_key, crate::id(), false, 1, bytes_of(&pool).to_vec());
+    let trader_info = new_account_info(trader, system_program::id(), true, 1, vec![]);
+    let accounts = [
+        new_account_info(
+            get_lp_position_address(&pool_key, &trader).0,
+            crate::id(),
+            false,
+            1,
+            bytes_of(&lp_position).to_vec(),
+        ),
+        new_token_account_info(
+            Pubkey::new_unique(),
+            &base_params.mint_key,
+            &trader,
+            10_000_000,
+        ),
+        new_token_account_info(
+            Pubkey::new_unique(),
+            &quote_params.mint_key,
+            &trader,
+            10_000_000,
+        ),
+        new_token_account_info(
+            base_params.vault_key,
+            &base_params.mint_key,
+            &base_params.vault_key,
+            0,
+        ),
+        new_token_account_info(
+            quote_params.vault_key,
+            &quote_params.mint_key,
+            &quote_params.vault_key,
+            0,
+        ),
+        new_account_info(spl_token::id(), Pubkey::default(), false, 1, vec![]),
+    ];
+    let pool_context = PlasmaPoolContext {
+        pool_info: PoolAccountInfo::new(&pool_info).unwrap(),
+        signer: Signer::new(&trader_info).unwrap(),
+    };
+    let token_amount =
+        |info: &AccountInfo| u64::from_le_bytes(info.data.borrow()[64..72].try_into().unwrap());
+    let add_liquidity_data = |expected_transfer_fee_bps| {
+        AddLiquidityParams {
+            desired_base_amount_in: 1_000_000,
+            desired_quote_amount_in: 1_000_000,
+            initial_lp_shares: None,
+            expected_transfer_fee_bps,
+        }
+        .try_to_vec()
+        .unwrap()
+    };
+
+    // The vaults only hold SPL Token mints, so a deposit expecting transfer fees is rejected
+    let fee_data = add_liquidity_data(Some(TransferFeeBps {
+        base: 100,
+        quote: 0,
+    }));
+    assert_eq!(
+        process_add_liquidity(&pool_context, &accounts, &fee_data).unwrap_err(),
+        ProgramError::InvalidArgument
+    );
+    assert_eq!(token_amount(&accounts[3]), 0);
+    assert_eq!(token_amount(&accounts[4]), 0);
+
+    // Zero fees are the same as omitting them, and the vaults receive the recorded deposit
+    let event = process_add_liquidity(
+        &pool_context,
+        &accounts,
+        &add_liquidity_data(Some(Default::default())),
+    )
+    .unwrap();
+    assert!(event.user_lp_shares_received > 0);
+    assert_eq!(token_amount(&accounts[3]), event.user_base_deposited);
+    assert_eq!(token_amount(&accounts[4]), event.user_quote_deposited);
+    assert_eq!(
+        token_amount(&accounts[1]),
+        10_000_000 - event.user_base_deposited
+    );
+    assert_eq!(
+        token_amount(&accounts[2]),
+        10_000_000 - event.user_quote_deposited
+    );
+
+    // Params encoded before the transfer fee field was added decode without it
+    let legacy = [
+        1_000_u64.to_le_bytes().as_slice(),
+        2_000_u64.to_le_bytes().as_slice(),
+        &[0],
+    ]
+    .concat();
+    let params = AddLiquidityParams::unpack(&legacy).unwrap();
+    assert_eq!(params.desired_quote_amount_in, 2_000);
+    assert_eq!(params.expected_transfer_fee_bps, None);
+}
@@ -1,6 +1,9 @@
 use borsh::{BorshDeserialize as Deserialize, BorshSerialize as Serialize};
 use bytemuck::try_from_bytes_mut;
-use plasma_amm_state::amm::Side;
+use plasma_amm_state::{
+    amm::Side,
+    errors::{PlasmaStateError, UNINITIALIZED_POOL_ERROR_CODE},
+};
 use solana_program::{
     account_info::AccountInfo, clock::Clock, msg, program::set_return_data,
     program_error::ProgramError, sysvar::Sysvar,
@@ -25,6 +28,40 @@ use crate::{
 pub struct SwapParams {
     pub side: Side,
     pub swap_type: SwapType,
+    /// The swap is rejected if it lands after this slot
+    pub deadline_slot: Option<u64>,
+}
+
+impl SwapParams {
+    /// Deserializes the params. `deadline_slot` may be omitted entirely so that clients built
+    /// before it was added keep working.
+    pub fn unpack(mut data: &[u8]) -> Result<Self, ProgramError> {
+        let side = Side::deserialize(&mut data)?;
+        let swap_type = SwapType::deserialize(&mut data)?;
+        let deadline_slot = if data.is_empty() {
+            None
+        } else {
+            Option::<u64>::try_from_slice(data)?
+        };
+        Ok(Self {
+            side,
+            swap_type,
+            deadline_slot,
+        })
+    }
+
+    /// Errors if `current_slot` is past the deadline slot
+    pub fn check_deadline(&self, current_slot: u64) -> Result<(), PlasmaStateError> {
+        match self.deadline_slot {
+            Some(deadline_slot) if current_slot > deadline_slot => {
+                Err(PlasmaStateError::DeadlineExceeded {
+                    deadline_slot,
+                    current_slot,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Serialize)]
@@ -55,16 +92,32 @@ pub(crate) fn process_swap<'a, 'info>(
         &pool_context.signer.key,
     )?;
 
-    let SwapParams { side, swap_type } = SwapParams::try_from_slice(data)?;
+    let params = SwapParams::unpack(data)?;
+    let SwapParams {
+        side, swap_type, ..
+    } = params;
     msg!("{:?} {:?}", side, swap_type);
     // Get the active leader slot
     let slot = Clock::get()?.slot;
+    params.check_deadline(slot).map_err(|e| {
+        msg!("Swap failed: {}", e);
+        ProgramError::InvalidArgument
+    })?;
     let snapshot_slot = (slot / LEADER_SLOT_WINDOW) * LEADER_SLOT_WINDOW;
 
     let mut pool_bytes = pool_context.pool_info.try_borrow_mut_data()?;
     let pool = try_from_bytes_mut::<PoolAccount>(&mut *pool_bytes)
         .map_err(|_| ProgramError::InvalidAccountData)?;
 
+    // Clients can tell an empty pool apart from a bad account by its custom error code
+    pool.check_has_liquidity().map_err(|e| {
+        msg!("Swap failed: {}", e);
+        ProgramError::Custom(UNINITIALIZED_POOL_ERROR_CODE)
+    })?;
+
+    // Weight the pre-swap price by the slots it was in effect
+    pool.update_cumulative_price(slot);
+
     let pre_base_liquidity = pool.base_reserves;
     let pre_quote_liquidity = pool.quote_reserves;
     let pre_protocol_fees = pool.amm.cumulative_quote_protocol_fees;
@@ -244,5 +297,86 @@ pub(crate) fn process_swap<'a, 'info>(
         snapshot_base_liquidity: pool.base_reserves_snapshot,
         snapshot_quote_liquidity: pool.quote_reserves_snapshot,
         swap_result,
+        // Both deltas were checked to be non-negative above and sum to `fee_in_quote`
+        lp_fee_quote: pool.amm.cumulative_quote_lp_fees - pre_lp_fees,
+        protocol_fee_quote: pool.amm.cumulative_quote_protocol_fees - pre_protocol_fees,
     })
 }
+
+#[test]
+fn test_swap_deadline() {
+    let params = SwapParams {
+        side: Side::Buy,
+        swap_type: SwapType::ExactIn {
+            amount_in: 1_000,
+            min_amount_out: 0,
+        },
+        deadline_slot: Some(100),
+    };
+    // A future deadline, or one that is the current slot, lets the swap through
+    assert!(params.check_deadline(99).is_ok());
+    assert!(params.check_deadline(100).is_ok());
+    // A past deadline rejects it
+    assert_eq!(
+        params.check_deadline(101),
+        Err(PlasmaStateError::DeadlineExceeded {
+            deadline_slot: 100,
+            current_slot: 101,
+        })
+    );
+
+    let params = SwapParams {
+        deadline_slot: None,
+        ..params
+    };
+    assert!(params.check_deadline(u64::MAX).is_ok());
+}
+
+#[test]
+fn test_unpack_swap_params() {
+    let swap_type = SwapType::ExactOut {
+        amount_out: 1_000,
+        max_amount_in: 2_000,
+    };
+    let params = SwapParams {
+        side: Side::Sell,
+        swap_type,
+        deadline_slot: Some(42),
+    };
+    let unpacked = SwapParams::unpack(&params.try_to_vec().unwrap()).unwrap();
+    assert_eq!(unpacked.deadline_slot, Some(42));
+    assert_eq!(unpacked.side, Side::Sell);
+
+    // Params serialized before the deadline was added have no deadline
+    let legacy = [
+        Side::Sell.try_to_vec().unwrap(),
+        swap_type.try_to_vec().unwrap(),
+    ]
+    .concat();
+    assert_eq!(SwapParams::unpack(&legacy).unwrap().deadline_slot, None);
+
+    let mut trailing = params.try_to_vec().unwrap();
+    trailing.push(0);
+    assert!(SwapParams::unpack(&trailing).is_err());
+}
+
+#[test]
+fn test_swap_event_fee_split() {
+    use bytemuck::Zeroable;
+    use plasma_amm_state::{amm::Amm, test_utils::seed_pool};
+
+    // 20% of the fee is allocated to the protocol
+    let mut pool = PoolAccount::zeroed();
+    pool.amm = seed_pool(Amm::new(30, 20, 0, 0));
+
+    // The handler reports the change in the cumulative fees across the swap
+    let pre_lp_fees = pool.amm.cumulative_quote_lp_fees;
+    let pre_protocol_fees = pool.amm.cumulative_quote_protocol_fees;
+    let swap_result = pool.amm.buy_exact_in(4, 100_000_000).unwrap();
+    let lp_fee_quote = pool.amm.cumulative_quote_lp_fees - pre_lp_fees;
+    let protocol_fee_quote = pool.amm.cumulative_quote_protocol_fees - pre_protocol_fees;
+
+    assert!(lp_fee_quote > 0);
+    assert!(protocol_fee_quote > 0);
+    assert_eq!(lp_fee_quote + protocol_fee_quote, swap_result.fee_in_quote);
+}
@@ -0,0 +1,121 @@
+//! Syscall stubs and account builders that let the instruction handlers run natively in unit tests
+
+use std::sync::Once;
+
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::{ProgramResult, SUCCESS},
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_pack::Pack,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program,
+};
+
+/// The slot reported by the `Clock` sysvar while the test stubs are installed
+pub(crate) const TEST_SLOT: u64 = 100;
+
+struct TestSyscallStubs;
+
+impl SyscallStubs for TestSyscallStubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe {
+            *(var_addr as *mut Clock) = Clock {
+                slot: TEST_SLOT,
+                ..Clock::default()
+            };
+        }
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe {
+            *(var_addr as *mut Rent) = Rent::default();
+        }
+        SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let find_account = |index: usize| {
+            account_infos
+                .iter()
+                .find(|info| info.key == &instruction.accounts[index].pubkey)
+                .unwrap()
+        };
+        // Token transfers move the amount stored at offset 64 of the token accounts
+        if instruction.program_id == spl_token::id() && instruction.data[0] == 3 {
+            let amount = u64::from_le_bytes(instruction.data[1..9].try_into().unwrap());
+            let balance = |data: &[u8]| u64::from_le_bytes(data[64..72].try_into().unwrap());
+            let mut source = find_account(0).try_borrow_mut_data()?;
+            let source_balance = balance(&source)
+                .checked_sub(amount)
+                .ok_or(ProgramError::InsufficientFunds)?;
+            source[64..72].copy_from_slice(&source_balance.to_le_bytes());
+            let mut destination = find_account(1).try_borrow_mut_data()?;
+            let destination_balance = balance(&destination) + amount;
+            destination[64..72].copy_from_slice(&destination_balance.to_le_bytes());
+            return Ok(());
+        }
+        // Other than that, only the system program's CreateAccount is emulated and every other
+        // CPI is a no-op
+        if instruction.program_id != system_program::id() || instruction.data[..4] != [0; 4] {
+            return Ok(());
+        }
+        let lamports = u64::from_le_bytes(instruction.data[4..12].try_into().unwrap());
+        let space = u64::from_le_bytes(instruction.data[12..20].try_into().unwrap());
+        let new_account = find_account(1);
+        **new_account.try_borrow_mut_lamports()? += lamports;
+        *new_account.try_borrow_mut_data()? = Box::leak(vec![0; space as usize].into_boxed_slice());
+        Ok(())
+    }
+}
+
+/// Installs the test syscall stubs. Safe to call from every test.
+pub(crate) fn install_syscall_stubs() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        set_syscall_stubs(Box::new(TestSyscallStubs));
+    });
+}
+
+/// Creates an account that lives for the rest of the test
+pub(crate) fn new_account_info(
+    key: Pubkey,
+    owner: Pubkey,
+    is_signer: bool,
+    lamports: u64,
+    data: Vec<u8>,
+) -> AccountInfo<'static> {
+    AccountInfo::new(
+        Box::leak(Box::new(key)),
+        is_signer,
+        true,
+        Box::leak(Box::new(lamports)),
+        Box::leak(data.into_boxed_slice()),
+        Box::leak(Box::new(owner)),
+        false,
+        0,
+    )
+}
+
+/// Creates an SPL token account holding `amount` of `mint`
+pub(crate) fn new_token_account_info(
+    key: Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> AccountInfo<'static> {
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    data[..32].copy_from_slice(mint.as_ref());
+    data[32..64].copy_from_slice(owner.as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    new_account_info(key, spl_token::id(), false, 1, data)
+}
@@ -271,6 +271,56 @@ impl<'a, 'info> InitializeLpPositionContext<'a, 'info> {
     }
 }
 
+pub(crate) struct BatchInitializeLpPositionsContext<'a, 'info> {
+    pub(crate) system_program: Program<'a, 'info>,
+    /// Pairs of (lp_position_owner, lp_position)
+    pub(crate) lp_positions: Vec<(&'a AccountInfo<'info>, EmptyAccount<'a, 'info>)>,
+}
+
+impl<'a, 'info> BatchInitializeLpPositionsContext<'a, 'info> {
+    pub(crate) fn load(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_iter = &mut accounts.iter();
+        let system_program = Program::new(next_account_info(account_iter)?, &system_program::id())?;
+        let pairs = account_iter.as_slice().chunks_exact(2);
+        assert_with_msg(
+            pairs.remainder().is_empty(),
+            ProgramError::NotEnoughAccountKeys,
+            "Each LP position must be preceded by its owner",
+        )?;
+        let lp_positions = pairs
+            .map(|pair| Ok((&pair[0], EmptyAccount::new(&pair[1])?)))
+            .collect::<Result<Vec<_>, ProgramError>>()?;
+        Ok(Self {
+            system_program,
+            lp_positions,
+        })
+    }
+}
+
+pub(crate) struct CrankVestingContext<'a, 'info> {
+    pub(crate) lp_position_owner: &'a AccountInfo<'info>,
+    pub(crate) lp_position: LpPositionAccountInfo<'a, 'info>,
+}
+
+impl<'a, 'info> CrankVestingContext<'a, 'info> {
+    pub(crate) fn load(
+        pool_context: &PlasmaPoolContext<'a, 'info>,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let account_iter = &mut accounts.iter();
+        let lp_position_owner = next_account_info(account_iter)?;
+        let lp_position = LpPositionAccountInfo::new(
+            next_account_info(account_iter)?,
+            pool_context.pool_info.key,
+            lp_position_owner.key,
+        )?;
+        Ok(Self {
+            lp_position_owner,
+            lp_position,
+        })
+    }
+}
+
 pub(crate) struct WithdrawLpFeesContext<'a, 'info> {
     // This is only used for limit order instructions
     pub(crate) lp_position: LpPositionAccountInfo<'a, 'info>,
@@ -1,14 +1,24 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use bytemuck::{Pod, Zeroable};
-use solana_program::pubkey::Pubkey;
+use solana_program::{pubkey::Pubkey, system_program};
+
+use crate::{
+    errors::{PlasmaError, PlasmaResult},
+    fixed::I80F48,
+    get_vault_address,
+};
 
 pub const POOL_LEN: u64 = 624;
 pub const POOL_DISCRIMINATOR: [u8; 8] = [116, 210, 187, 119, 196, 196, 52, 137];
+/// Pools are created with protocol fee recipient shares summing to less than this
+pub const PROTOCOL_FEE_SHARES_LIMIT: u64 = 10_000;
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, BorshDeserialize, BorshSerialize)]
 pub struct LpPosition {
-    reward_factor_snapshot: i128,
+    /// The pool's reward factor when fees were last accrued to this position. This is a
+    /// fixed-point value: its raw `i128` bits are not the reward factor itself.
+    pub reward_factor_snapshot: I80F48,
     pub lp_shares: u64,
     pub withdrawable_lp_shares: u64,
     uncollected_fees: u64,
@@ -25,7 +35,78 @@ pub struct PoolHeader {
     pub quote_params: TokenParams,
     pub fee_recipients: ProtocolFeeRecipients,
     pub swap_sequence_number: u64,
-    pub padding: [u64; 12],
+    pub authority: Pubkey,
+    pub min_quote_deposit: u64,
+    pub max_quote_tvl: u64,
+    pub creation_slot: u64,
+    pub last_price_update_slot: u64,
+    /// Raw `I80F48` bits of the time-weighted running sum of the spot price, see
+    /// `PoolHeader::cumulative_price`
+    pub cumulative_price_bits: [u64; 2],
+    pub padding: [u64; 2],
+}
+
+impl PoolHeader {
+    /// Checks whether `data` (the raw pool account data) holds a fully initialized pool header,
+    /// meaning the discriminator is set and the mints and vaults of both tokens are non-default.
+    ///
+    /// A pool account that was allocated but never written to (all zeroes) returns `false`.
+    pub fn is_fully_initialized(data: &[u8]) -> bool {
+        let Some(header_bytes) = data.get(..std::mem::size_of::<PoolHeader>()) else {
+            return false;
+        };
+        let header: PoolHeader = bytemuck::pod_read_unaligned(header_bytes);
+        header.discriminator == POOL_DISCRIMINATOR
+            && [header.base_params, header.quote_params]
+                .iter()
+                .all(|params| {
+                    params.mint_key != Pubkey::default() && params.vault_key != Pubkey::default()
+                })
+    }
+
+    /// Re-derives the base and quote vaults from the stored mints and `pool_key` and checks that
+    /// they match the stored vault keys. A mismatch means the header is corrupted or does not
+    /// belong to `pool_key`, so clients should not interact with the pool.
+    pub fn verify_integrity(&self, program_id: &Pubkey, pool_key: &Pubkey) -> PlasmaResult<()> {
+        for params in [&self.base_params, &self.quote_params] {
+            let (expected_vault, _) = get_vault_address(program_id, pool_key, &params.mint_key);
+            if expected_vault != params.vault_key {
+                return Err(PlasmaError::VaultMismatch {
+                    mint: params.mint_key,
+                    expected_vault,
+                    stored_vault: params.vault_key,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of slots since the pool was initialized, or zero if `current_slot` is before the
+    /// creation slot. Pools created before the creation slot was recorded store zero, so their age
+    /// is `current_slot`.
+    pub fn age_slots(&self, current_slot: u64) -> u64 {
+        current_slot.saturating_sub(self.creation_slot)
+    }
+
+    /// Time-weighted running sum of the spot price, advanced before every change to the reserves
+    /// (swaps, adding or removing liquidity and reinvesting protocol fees). The reading is only
+    /// current as of `last_price_update_slot`, not the slot the account was fetched at. Pass two
+    /// readings to `amm::Amm::twap` along with their `last_price_update_slot` values, rather than
+    /// the current slot, to get the average price between them
+    pub fn cumulative_price(&self) -> I80F48 {
+        let [low, high] = self.cumulative_price_bits;
+        I80F48::from_bits(((high as i128) << 64) | low as i128)
+    }
+
+    /// Total protocol fees accrued to all recipients that have not been withdrawn yet
+    pub fn protocol_fees_pending_total(&self) -> u64 {
+        self.fee_recipients
+            .recipients
+            .iter()
+            .fold(0_u64, |total, recipient| {
+                total.saturating_add(recipient.pending())
+            })
+    }
 }
 
 #[repr(C)]
@@ -53,9 +134,284 @@ pub struct ProtocolFeeRecipient {
     pub collected_quote_fees: u64,
 }
 
+impl ProtocolFeeRecipient {
+    /// Protocol fees accrued to this recipient since its last withdrawal
+    pub fn pending(&self) -> u64 {
+        self.total_accumulated_quote_fees
+            .saturating_sub(self.collected_quote_fees)
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Default, Copy, Clone, Zeroable, Pod, BorshDeserialize, BorshSerialize)]
 pub struct ProtocolFeeRecipients {
     pub recipients: [ProtocolFeeRecipient; 3],
     _padding: [u64; 12],
 }
+
+impl ProtocolFeeRecipients {
+    /// Returns whether `recipient` is one of the pool's protocol fee recipients, which is what the
+    /// program checks before paying out a `WithdrawProtocolFees` instruction
+    pub fn is_registered(&self, recipient: &Pubkey) -> bool {
+        self.recipients
+            .iter()
+            .any(|registered| registered.recipient == *recipient)
+    }
+
+    /// Returns the recipients that are owed protocol fees along with the amount owed, largest
+    /// first. Each `WithdrawProtocolFees` instruction only pays its signer, so this is the order
+    /// in which withdrawals recover the most fees with the fewest transactions.
+    pub fn withdrawal_plan(&self) -> Vec<(Pubkey, u64)> {
+        let mut plan = self
+            .recipients
+            .iter()
+            .map(|recipient| (recipient.recipient, recipient.pending()))
+            .filter(|(_, owed)| *owed > 0)
+            .collect::<Vec<_>>();
+        plan.sort_by(|(_, a), (_, b)| b.cmp(a));
+        plan
+    }
+
+    /// Returns the total shares of all recipients. Protocol fees are split between recipients in
+    /// proportion to their shares of this total.
+    pub fn shares_sum(&self) -> u64 {
+        self.recipients
+            .iter()
+            .fold(0_u64, |sum, recipient| sum.saturating_add(recipient.shares))
+    }
+
+    /// Returns whether the shares are consistent with what pool creation allows: the sum is
+    /// positive (otherwise protocol fees cannot be split) and below `PROTOCOL_FEE_SHARES_LIMIT`,
+    /// and unused placeholder slots (the system program key) hold no shares.
+    pub fn is_valid_allocation(&self) -> bool {
+        let placeholders_empty = self
+            .recipients
+            .iter()
+            .filter(|recipient| recipient.recipient == system_program::ID)
+            .all(|recipient| recipient.shares == 0);
+        let shares_sum = self.shares_sum();
+        placeholders_empty && shares_sum > 0 && shares_sum < PROTOCOL_FEE_SHARES_LIMIT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amm::Amm;
+    use std::mem::{align_of, size_of};
+
+    fn assert_size<T>(name: &str, expected: usize) {
+        assert_eq!(
+            size_of::<T>(),
+            expected,
+            "{} has size {} but expected {}",
+            name,
+            size_of::<T>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_account_struct_sizes() {
+        assert_size::<TokenParams>("TokenParams", 72);
+        assert_size::<ProtocolFeeRecipient>("ProtocolFeeRecipient", 56);
+        assert_size::<ProtocolFeeRecipients>("ProtocolFeeRecipients", 264);
+        assert_size::<PoolHeader>("PoolHeader", 528);
+        assert_size::<Amm>("Amm", 96);
+        assert_size::<LpPosition>("LpPosition", 64);
+
+        let pool_len = size_of::<PoolHeader>() + size_of::<Amm>();
+        assert_eq!(
+            pool_len as u64, POOL_LEN,
+            "PoolHeader + Amm has size {} but POOL_LEN is {}",
+            pool_len, POOL_LEN
+        );
+    }
+
+    #[test]
+    fn test_is_fully_initialized() {
+        let token_params = || TokenParams {
+            decimals: 6,
+            vault_bump: 255,
+            mint_key: Pubkey::new_unique(),
+            vault_key: Pubkey::new_unique(),
+        };
+        let mut header = PoolHeader::zeroed();
+        header.discriminator = POOL_DISCRIMINATOR;
+        header.base_params = token_params();
+        header.quote_params = token_params();
+
+        let mut data = bytemuck::bytes_of(&header).to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(&Amm::zeroed()));
+        assert!(PoolHeader::is_fully_initialized(&data));
+
+        // Truncated data cannot hold a header
+        assert!(!PoolHeader::is_fully_initialized(&data[..100]));
+
+        // Missing quote vault
+        header.quote_params.vault_key = Pubkey::default();
+        assert!(!PoolHeader::is_fully_initialized(bytemuck::bytes_of(
+            &header
+        )));
+    }
+
+    #[test]
+    fn test_zeroed_pool_is_not_initialized() {
+        let data = vec![0_u8; POOL_LEN as usize];
+        assert!(!PoolHeader::is_fully_initialized(&data));
+    }
+
+    #[test]
+    fn test_account_struct_alignment() {
+        // `TokenParams` only holds u32s and pubkeys
+        assert_eq!(align_of::<TokenParams>(), 4);
+        assert_eq!(align_of::<ProtocolFeeRecipient>(), 8);
+        assert_eq!(align_of::<ProtocolFeeRecipients>(), 8);
+        assert_eq!(align_of::<PoolHeader>(), 8);
+
+        // The `Amm` body contains an `I80F48`, whose alignment depends on the target's i128
+        // alignment. It must still land on an aligned offset directly after the header.
+        assert!(align_of::<Amm>() >= 8);
+        assert_eq!(
+            size_of::<PoolHeader>() % align_of::<Amm>(),
+            0,
+            "Amm is not aligned when placed after PoolHeader"
+        );
+    }
+
+    #[test]
+    fn test_withdrawal_plan() {
+        let recipient = |total_accumulated_quote_fees, collected_quote_fees| ProtocolFeeRecipient {
+            recipient: Pubkey::new_unique(),
+            shares: 1,
+            total_accumulated_quote_fees,
+            collected_quote_fees,
+        };
+        let mut recipients = ProtocolFeeRecipients::zeroed();
+        recipients.recipients = [recipient(100, 90), recipient(500, 500), recipient(80, 0)];
+
+        // The fully collected recipient is skipped and the largest amount owed comes first
+        assert_eq!(
+            recipients.withdrawal_plan(),
+            vec![
+                (recipients.recipients[2].recipient, 80),
+                (recipients.recipients[0].recipient, 10),
+            ]
+        );
+
+        assert!(ProtocolFeeRecipients::zeroed().withdrawal_plan().is_empty());
+    }
+
+    #[test]
+    fn test_is_valid_allocation() {
+        let recipient = |shares| ProtocolFeeRecipient {
+            recipient: Pubkey::new_unique(),
+            shares,
+            total_accumulated_quote_fees: 0,
+            collected_quote_fees: 0,
+        };
+        let placeholder = ProtocolFeeRecipient::default();
+
+        let mut recipients = ProtocolFeeRecipients::zeroed();
+        recipients.recipients = [recipient(5000), recipient(3000), recipient(1000)];
+        assert_eq!(recipients.shares_sum(), 9000);
+        assert!(recipients.is_valid_allocation());
+
+        // Zero-share placeholders are allowed
+        recipients.recipients = [recipient(5000), placeholder, placeholder];
+        assert!(recipients.is_valid_allocation());
+
+        // Without any shares protocol fees cannot be split
+        recipients.recipients = [recipient(0), placeholder, placeholder];
+        assert_eq!(recipients.shares_sum(), 0);
+        assert!(!recipients.is_valid_allocation());
+
+        recipients.recipients = [recipient(5000), recipient(5000), placeholder];
+        assert!(!recipients.is_valid_allocation());
+
+        recipients.recipients = [
+            recipient(5000),
+            ProtocolFeeRecipient {
+                shares: 1,
+                ..placeholder
+            },
+            placeholder,
+        ];
+        assert!(!recipients.is_valid_allocation());
+    }
+
+    #[test]
+    fn test_lp_position_reward_factor_snapshot_encoding() {
+        use borsh::BorshDeserialize;
+        use plasma_amm_state::{fixed::I80F48 as StateI80F48, lp::LpPosition as StateLpPosition};
+
+        // 1.5 is stored on chain as the raw i128 1.5 * 2^48
+        let raw_bits = 3_i128 << 47;
+        assert_eq!(I80F48::from_bits(raw_bits), I80F48::from_fraction(3, 2));
+
+        let on_chain =
+            StateLpPosition::new_with_reward_factor_snapshot(StateI80F48::from_bits(raw_bits));
+        let position = LpPosition::try_from_slice(bytemuck::bytes_of(&on_chain)).unwrap();
+        assert_eq!(position.reward_factor_snapshot, I80F48::from_fraction(3, 2));
+        assert_eq!(position.reward_factor_snapshot.to_bits(), raw_bits);
+        assert_eq!(position.lp_shares, 0);
+    }
+
+    #[test]
+    fn test_protocol_fees_pending() {
+        let recipient = |total_accumulated_quote_fees, collected_quote_fees| ProtocolFeeRecipient {
+            recipient: Pubkey::new_unique(),
+            shares: 1,
+            total_accumulated_quote_fees,
+            collected_quote_fees,
+        };
+        // Partially collected
+        assert_eq!(recipient(100, 40).pending(), 60);
+        assert_eq!(recipient(100, 100).pending(), 0);
+        assert_eq!(recipient(100, 120).pending(), 0);
+
+        let mut header = PoolHeader::zeroed();
+        assert_eq!(header.protocol_fees_pending_total(), 0);
+        header.fee_recipients.recipients = [recipient(100, 40), recipient(50, 0), recipient(7, 7)];
+        assert_eq!(header.protocol_fees_pending_total(), 110);
+    }
+
+    #[test]
+    fn test_verify_integrity() {
+        let pool_key = Pubkey::new_unique();
+        let mut header = PoolHeader::zeroed();
+        for params in [&mut header.base_params, &mut header.quote_params] {
+            params.mint_key = Pubkey::new_unique();
+            params.vault_key = get_vault_address(&crate::ID, &pool_key, &params.mint_key).0;
+        }
+        assert!(header.verify_integrity(&crate::ID, &pool_key).is_ok());
+        // The vaults are derived from the pool key and program id
+        assert!(header
+            .verify_integrity(&crate::ID, &Pubkey::new_unique())
+            .is_err());
+        assert!(header
+            .verify_integrity(&Pubkey::new_unique(), &pool_key)
+            .is_err());
+
+        let mut tampered = header;
+        let spoofed_vault = Pubkey::new_unique();
+        tampered.quote_params.vault_key = spoofed_vault;
+        assert_eq!(
+            tampered.verify_integrity(&crate::ID, &pool_key),
+            Err(PlasmaError::VaultMismatch {
+                mint: header.quote_params.mint_key,
+                expected_vault: header.quote_params.vault_key,
+                stored_vault: spoofed_vault,
+            })
+        );
+    }
+
+    #[test]
+    fn test_age_slots() {
+        let mut header = PoolHeader::zeroed();
+        header.creation_slot = 1_000;
+        assert_eq!(header.age_slots(1_000), 0);
+        assert_eq!(header.age_slots(1_250), 250);
+        assert_eq!(header.age_slots(999), 0);
+    }
+}
@@ -0,0 +1,189 @@
+use std::{fmt::Display, mem::size_of};
+
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    accounts::{PoolHeader, POOL_LEN},
+    amm::{Amm, SwapResult},
+    errors::PlasmaError,
+    instructions::SwapParams,
+    quote::quote_swap_at_slot,
+};
+
+/// Backend used by [`PlasmaClient`] to load raw account data, e.g. an RPC node, a test validator
+/// or an in-memory mock.
+pub trait AccountFetcher {
+    type Error;
+
+    /// Returns the raw data of the account at `key`
+    fn fetch(&self, key: &Pubkey) -> Result<Vec<u8>, Self::Error>;
+}
+
+#[cfg(feature = "rpc")]
+impl AccountFetcher for solana_client::rpc_client::RpcClient {
+    type Error = solana_client::client_error::ClientError;
+
+    fn fetch(&self, key: &Pubkey) -> Result<Vec<u8>, Self::Error> {
+        self.get_account_data(key)
+    }
+}
+
+#[derive(Debug)]
+pub enum PlasmaClientError<E> {
+    /// The account fetcher failed to load the account
+    Fetch(E),
+    /// The account at the given key does not hold an initialized pool
+    InvalidPoolAccount(Pubkey),
+    Plasma(PlasmaError),
+}
+
+impl<E> From<PlasmaError> for PlasmaClientError<E> {
+    fn from(error: PlasmaError) -> Self {
+        PlasmaClientError::Plasma(error)
+    }
+}
+
+impl<E: Display> Display for PlasmaClientError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlasmaClientError::Fetch(error) => write!(f, "Failed to fetch account: {}", error),
+            PlasmaClientError::InvalidPoolAccount(key) => {
+                write!(f, "Account {} is not an initialized pool", key)
+            }
+            PlasmaClientError::Plasma(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+/// Read-only client for Plasma pools that is generic over the account-fetching backend
+pub struct PlasmaClient<F: AccountFetcher> {
+    pub fetcher: F,
+}
+
+impl<F: AccountFetcher> PlasmaClient<F> {
+    pub fn new(fetcher: F) -> Self {
+        Self { fetcher }
+    }
+
+    /// Loads and parses the pool account at `pool_key`
+    pub fn get_pool(
+        &self,
+        pool_key: &Pubkey,
+    ) -> Result<(PoolHeader, Amm), PlasmaClientError<F::Error>> {
+        let data = self
+            .fetcher
+            .fetch(pool_key)
+            .map_err(PlasmaClientError::Fetch)?;
+        if data.len() < POOL_LEN as usize || !PoolHeader::is_fully_initialized(&data) {
+            return Err(PlasmaClientError::InvalidPoolAccount(*pool_key));
+        }
+        let (header_bytes, amm_bytes) = data[..POOL_LEN as usize].split_at(size_of::<PoolHeader>());
+        Ok((
+            bytemuck::pod_read_unaligned(header_bytes),
+            bytemuck::pod_read_unaligned(amm_bytes),
+        ))
+    }
+
+    /// Quotes a swap against the current state of the pool at `pool_key`. Slippage limits in
+    /// `params` are not enforced.
+    pub fn quote_swap(
+        &self,
+        pool_key: &Pubkey,
+        slot: u64,
+        params: SwapParams,
+    ) -> Result<SwapResult, PlasmaClientError<F::Error>> {
+        let (_, amm) = self.get_pool(pool_key)?;
+        Ok(quote_swap_at_slot(&amm, slot, params)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bytemuck::Zeroable;
+
+    use super::*;
+    use crate::{
+        accounts::{TokenParams, POOL_DISCRIMINATOR},
+        amm::Side,
+        instructions::SwapType,
+    };
+    use plasma_amm_state::test_utils::new_pool;
+
+    struct MockFetcher {
+        accounts: HashMap<Pubkey, Vec<u8>>,
+    }
+
+    impl AccountFetcher for MockFetcher {
+        type Error = String;
+
+        fn fetch(&self, key: &Pubkey) -> Result<Vec<u8>, Self::Error> {
+            self.accounts
+                .get(key)
+                .cloned()
+                .ok_or_else(|| format!("Account {} not found", key))
+        }
+    }
+
+    fn synthetic_pool() -> (Amm, Vec<u8>) {
+        let token_params = || TokenParams {
+            decimals: 6,
+            vault_bump: 255,
+            mint_key: Pubkey::new_unique(),
+            vault_key: Pubkey::new_unique(),
+        };
+        let mut header = PoolHeader::zeroed();
+        header.discriminator = POOL_DISCRIMINATOR;
+        header.base_params = token_params();
+        header.quote_params = token_params();
+
+        let amm = new_pool(30);
+
+        let mut data = bytemuck::bytes_of(&header).to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(&amm));
+        (amm, data)
+    }
+
+    #[test]
+    fn test_client_quotes_with_mock_fetcher() {
+        let pool_key = Pubkey::new_unique();
+        let uninitialized_key = Pubkey::new_unique();
+        let (mut amm, data) = synthetic_pool();
+        let client = PlasmaClient::new(MockFetcher {
+            accounts: HashMap::from([
+                (pool_key, data),
+                (uninitialized_key, vec![0; POOL_LEN as usize]),
+            ]),
+        });
+
+        let params = SwapParams {
+            side: Side::Buy,
+            swap_type: SwapType::ExactIn {
+                amount_in: 10_000_000,
+                min_amount_out: 0,
+            },
+            deadline_slot: None,
+        };
+        let quote = client.quote_swap(&pool_key, 1, params).unwrap();
+        let expected = amm.buy_exact_in(1, 10_000_000).unwrap();
+        assert_eq!(
+            quote.base_amount_to_transfer,
+            expected.base_amount_to_transfer
+        );
+        assert_eq!(
+            quote.quote_amount_to_transfer,
+            expected.quote_amount_to_transfer
+        );
+        assert_eq!(quote.fee_in_quote, expected.fee_in_quote);
+
+        assert!(matches!(
+            client.get_pool(&uninitialized_key),
+            Err(PlasmaClientError::InvalidPoolAccount(key)) if key == uninitialized_key
+        ));
+        assert!(matches!(
+            client.get_pool(&Pubkey::new_unique()),
+            Err(PlasmaClientError::Fetch(_))
+        ));
+    }
+}
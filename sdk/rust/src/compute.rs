@@ -0,0 +1,132 @@
+use std::sync::RwLock;
+
+use crate::instructions::PlasmaInstruction;
+
+/// Compute units to request for each Plasma instruction, e.g. when setting a compute unit limit.
+///
+/// The defaults are rough upper bounds. Bots that profile their own transactions can replace them
+/// with measured values, either per call with `estimated_compute_units_with` or for the whole
+/// process with `set_global_estimates`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComputeEstimates {
+    pub swap: u32,
+    pub add_liquidity: u32,
+    pub remove_liquidity: u32,
+    pub renounce_liquidity: u32,
+    pub withdraw_lp_fees: u32,
+    pub initialize_lp_position: u32,
+    pub initialize_pool: u32,
+    pub withdraw_protocol_fees: u32,
+    pub log: u32,
+    pub transfer_liquidity: u32,
+    pub set_lp_fee: u32,
+    pub batch_initialize_lp_positions: u32,
+    pub crank_vesting: u32,
+    pub reinvest_protocol_fees: u32,
+    pub release_vesting: u32,
+}
+
+impl ComputeEstimates {
+    pub const DEFAULT: Self = Self {
+        swap: 60_000,
+        add_liquidity: 60_000,
+        remove_liquidity: 60_000,
+        renounce_liquidity: 20_000,
+        withdraw_lp_fees: 40_000,
+        initialize_lp_position: 30_000,
+        initialize_pool: 80_000,
+        withdraw_protocol_fees: 40_000,
+        log: 5_000,
+        transfer_liquidity: 25_000,
+        set_lp_fee: 15_000,
+        batch_initialize_lp_positions: 150_000,
+        crank_vesting: 20_000,
+        reinvest_protocol_fees: 20_000,
+        release_vesting: 20_000,
+    };
+}
+
+impl Default for ComputeEstimates {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+static GLOBAL_ESTIMATES: RwLock<ComputeEstimates> = RwLock::new(ComputeEstimates::DEFAULT);
+
+/// Replaces the estimates used by `PlasmaInstruction::estimated_compute_units` for the whole process
+pub fn set_global_estimates(estimates: ComputeEstimates) {
+    *GLOBAL_ESTIMATES
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = estimates;
+}
+
+/// Returns the estimates used by `PlasmaInstruction::estimated_compute_units`
+pub fn global_estimates() -> ComputeEstimates {
+    *GLOBAL_ESTIMATES
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl PlasmaInstruction {
+    /// Returns the compute units to request for this instruction from the global estimates
+    pub fn estimated_compute_units(&self) -> u32 {
+        self.estimated_compute_units_with(&global_estimates())
+    }
+
+    /// Returns the compute units to request for this instruction from `estimates`
+    pub fn estimated_compute_units_with(&self, estimates: &ComputeEstimates) -> u32 {
+        match self {
+            PlasmaInstruction::Swap => estimates.swap,
+            PlasmaInstruction::AddLiquidity => estimates.add_liquidity,
+            PlasmaInstruction::RemoveLiquidity => estimates.remove_liquidity,
+            PlasmaInstruction::RenounceLiquidity => estimates.renounce_liquidity,
+            PlasmaInstruction::WithdrawLpFees => estimates.withdraw_lp_fees,
+            PlasmaInstruction::InitializeLpPosition => estimates.initialize_lp_position,
+            PlasmaInstruction::InitializePool => estimates.initialize_pool,
+            PlasmaInstruction::WithdrawProtocolFees => estimates.withdraw_protocol_fees,
+            PlasmaInstruction::Log => estimates.log,
+            PlasmaInstruction::TransferLiquidity => estimates.transfer_liquidity,
+            PlasmaInstruction::SetLpFee => estimates.set_lp_fee,
+            PlasmaInstruction::BatchInitializeLpPositions => {
+                estimates.batch_initialize_lp_positions
+            }
+            PlasmaInstruction::CrankVesting => estimates.crank_vesting,
+            PlasmaInstruction::ReinvestProtocolFees => estimates.reinvest_protocol_fees,
+            PlasmaInstruction::ReleaseVesting => estimates.release_vesting,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_estimates_override_defaults() {
+        let custom = ComputeEstimates {
+            swap: 42_000,
+            ..ComputeEstimates::default()
+        };
+        assert_eq!(
+            PlasmaInstruction::Swap.estimated_compute_units_with(&ComputeEstimates::default()),
+            ComputeEstimates::DEFAULT.swap
+        );
+        assert_eq!(
+            PlasmaInstruction::Swap.estimated_compute_units_with(&custom),
+            42_000
+        );
+        assert_eq!(
+            PlasmaInstruction::AddLiquidity.estimated_compute_units_with(&custom),
+            ComputeEstimates::DEFAULT.add_liquidity
+        );
+
+        set_global_estimates(custom);
+        assert_eq!(PlasmaInstruction::Swap.estimated_compute_units(), 42_000);
+        set_global_estimates(ComputeEstimates::default());
+        assert_eq!(
+            PlasmaInstruction::Swap.estimated_compute_units(),
+            ComputeEstimates::DEFAULT.swap
+        );
+    }
+}
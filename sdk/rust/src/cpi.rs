@@ -0,0 +1,102 @@
+use solana_program::account_info::AccountInfo;
+
+/// Returns the accounts of a `Swap` instruction in the order of its account annotations, i.e.
+/// the order of the metas built by `instructions::swap`, ready to pass to `invoke`.
+#[allow(clippy::too_many_arguments)]
+pub fn swap_account_infos<'a>(
+    plasma_program: &AccountInfo<'a>,
+    log_authority: &AccountInfo<'a>,
+    pool: &AccountInfo<'a>,
+    trader: &AccountInfo<'a>,
+    base_account: &AccountInfo<'a>,
+    quote_account: &AccountInfo<'a>,
+    base_vault: &AccountInfo<'a>,
+    quote_vault: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+) -> [AccountInfo<'a>; 9] {
+    [
+        plasma_program.clone(),
+        log_authority.clone(),
+        pool.clone(),
+        trader.clone(),
+        base_account.clone(),
+        quote_account.clone(),
+        base_vault.clone(),
+        quote_vault.clone(),
+        token_program.clone(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_program::pubkey::Pubkey;
+
+    use super::*;
+    use crate::{
+        amm::Side,
+        get_log_authority, get_vault_address,
+        instructions::{swap, SwapParams, SwapType},
+        spl_token, ID,
+    };
+
+    #[test]
+    fn test_swap_account_infos_match_swap_metas() {
+        let pool = Pubkey::new_unique();
+        let trader = Pubkey::new_unique();
+        let base_mint = Pubkey::new_unique();
+        let quote_mint = Pubkey::new_unique();
+        let base_account = Pubkey::new_unique();
+        let quote_account = Pubkey::new_unique();
+        let ix = swap(
+            &pool,
+            &trader,
+            &base_mint,
+            &quote_mint,
+            &base_account,
+            &quote_account,
+            SwapParams {
+                side: Side::Buy,
+                swap_type: SwapType::ExactIn {
+                    amount_in: 1,
+                    min_amount_out: 0,
+                },
+                deadline_slot: None,
+            },
+        );
+
+        // Mock the accounts of the calling program's instruction
+        let keys = [
+            ID,
+            get_log_authority(&ID),
+            pool,
+            trader,
+            base_account,
+            quote_account,
+            get_vault_address(&ID, &pool, &base_mint).0,
+            get_vault_address(&ID, &pool, &quote_mint).0,
+            spl_token::ID,
+        ];
+        let owner = Pubkey::new_unique();
+        let mut lamports = [0_u64; 9];
+        let mut data = [[0_u8; 0]; 9];
+        let infos = keys
+            .iter()
+            .zip(lamports.iter_mut())
+            .zip(data.iter_mut())
+            .enumerate()
+            .map(|(i, ((key, lamports), data))| {
+                AccountInfo::new(key, i == 3, false, lamports, data, &owner, false, 0)
+            })
+            .collect::<Vec<_>>();
+
+        let ordered = swap_account_infos(
+            &infos[0], &infos[1], &infos[2], &infos[3], &infos[4], &infos[5], &infos[6], &infos[7],
+            &infos[8],
+        );
+        assert_eq!(ordered.len(), ix.accounts.len());
+        for (info, meta) in ordered.iter().zip(ix.accounts.iter()) {
+            assert_eq!(*info.key, meta.pubkey);
+            assert_eq!(info.is_signer, meta.is_signer);
+        }
+    }
+}
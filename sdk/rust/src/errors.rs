@@ -1,5 +1,12 @@
 use std::fmt::Display;
 
+use plasma_amm_state::errors::PlasmaStateError;
+pub use plasma_amm_state::errors::UNINITIALIZED_POOL_ERROR_CODE;
+use solana_program::pubkey::Pubkey;
+
+/// Result type returned by the SDK's fallible helpers
+pub type PlasmaResult<T> = Result<T, PlasmaError>;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PlasmaError {
     InvariantViolation(u128, u128),
@@ -21,6 +28,43 @@ pub enum PlasmaError {
     SwapExactOutTooLarge,
     SwapExactInTooLarge,
     SwapOutputGreaterThanOrEqualToReserves(u128, u128),
+    PriceImpactTooHigh {
+        price_impact_bps: u64,
+        max_price_impact_bps: u16,
+    },
+    BelowMinimumDeposit {
+        quote_amount_deposited: u64,
+        min_quote_deposit: u64,
+    },
+    InconsistentLpPosition {
+        lp_shares: u64,
+        withdrawable_lp_shares: u64,
+        lp_shares_to_vest: u64,
+    },
+    TvlCapExceeded {
+        quote_tvl: u128,
+        max_quote_tvl: u64,
+    },
+    DeadlineExceeded {
+        deadline_slot: u64,
+        current_slot: u64,
+    },
+    VaultMismatch {
+        mint: Pubkey,
+        expected_vault: Pubkey,
+        stored_vault: Pubkey,
+    },
+    InvalidAccountCount {
+        expected: usize,
+        actual: usize,
+    },
+    InvalidAccountFlags {
+        index: usize,
+        expected_signer: bool,
+        expected_writable: bool,
+        is_signer: bool,
+        is_writable: bool,
+    },
 }
 
 impl Display for PlasmaError {
@@ -74,6 +118,141 @@ impl Display for PlasmaError {
                     input, reserves
                 )
             }
+            PlasmaError::PriceImpactTooHigh {
+                price_impact_bps,
+                max_price_impact_bps,
+            } => write!(
+                f,
+                "Price impact of {} bps exceeds the maximum of {} bps",
+                price_impact_bps, max_price_impact_bps
+            ),
+            PlasmaError::BelowMinimumDeposit {
+                quote_amount_deposited,
+                min_quote_deposit,
+            } => write!(
+                f,
+                "Deposit of {} quote is below the pool minimum of {} quote",
+                quote_amount_deposited, min_quote_deposit
+            ),
+            PlasmaError::InconsistentLpPosition {
+                lp_shares,
+                withdrawable_lp_shares,
+                lp_shares_to_vest,
+            } => write!(
+                f,
+                "LP position has {} shares but {} withdrawable and {} vesting",
+                lp_shares, withdrawable_lp_shares, lp_shares_to_vest
+            ),
+            PlasmaError::TvlCapExceeded {
+                quote_tvl,
+                max_quote_tvl,
+            } => write!(
+                f,
+                "Pool TVL of {} quote exceeds the cap of {} quote",
+                quote_tvl, max_quote_tvl
+            ),
+            PlasmaError::DeadlineExceeded {
+                deadline_slot,
+                current_slot,
+            } => write!(
+                f,
+                "Deadline slot {} has passed (current slot {})",
+                deadline_slot, current_slot
+            ),
+            PlasmaError::VaultMismatch {
+                mint,
+                expected_vault,
+                stored_vault,
+            } => write!(
+                f,
+                "Vault {} stored for mint {} does not match the derived vault {}",
+                stored_vault, mint, expected_vault
+            ),
+            PlasmaError::InvalidAccountCount { expected, actual } => write!(
+                f,
+                "Instruction has {} accounts but expected {}",
+                actual, expected
+            ),
+            PlasmaError::InvalidAccountFlags {
+                index,
+                expected_signer,
+                expected_writable,
+                is_signer,
+                is_writable,
+            } => write!(
+                f,
+                "Account {} has signer={}, writable={} but expected signer={}, writable={}",
+                index, is_signer, is_writable, expected_signer, expected_writable
+            ),
+        }
+    }
+}
+
+impl From<PlasmaStateError> for PlasmaError {
+    fn from(error: PlasmaStateError) -> Self {
+        match error {
+            PlasmaStateError::InvariantViolation(k_start, k_end) => {
+                PlasmaError::InvariantViolation(k_start, k_end)
+            }
+            PlasmaStateError::MismatchedFees(expected, actual) => {
+                PlasmaError::MismatchedFees(expected, actual)
+            }
+            PlasmaStateError::UninitializedPool => PlasmaError::UninitializedPool,
+            PlasmaStateError::SwapAmountMismatch => PlasmaError::SwapAmountMismatch,
+            PlasmaStateError::Overflow => PlasmaError::Overflow,
+            PlasmaStateError::Underflow => PlasmaError::Underflow,
+            PlasmaStateError::UnexpectedArgument => PlasmaError::UnexpectedArgument,
+            PlasmaStateError::MissingExpectedArgument => PlasmaError::MissingExpectedArgument,
+            PlasmaStateError::BelowMinimumLpSharesRequired => {
+                PlasmaError::BelowMinimumLpSharesRequired
+            }
+            PlasmaStateError::BelowMinimumWithdrawaRequired {
+                quote_amount_to_withdraw,
+                base_amount_to_withdraw,
+            } => PlasmaError::BelowMinimumWithdrawaRequired {
+                quote_amount_to_withdraw,
+                base_amount_to_withdraw,
+            },
+            PlasmaStateError::VestingPeriodNotOver => PlasmaError::VestingPeriodNotOver,
+            PlasmaStateError::IncorrectProtocolFeeRecipient => {
+                PlasmaError::IncorrectProtocolFeeRecipient
+            }
+            PlasmaStateError::TooManyShares => PlasmaError::TooManyShares,
+            PlasmaStateError::SwapExactOutTooLarge => PlasmaError::SwapExactOutTooLarge,
+            PlasmaStateError::SwapExactInTooLarge => PlasmaError::SwapExactInTooLarge,
+            PlasmaStateError::SwapOutputGreaterThanOrEqualToReserves(output, reserves) => {
+                PlasmaError::SwapOutputGreaterThanOrEqualToReserves(output, reserves)
+            }
+            PlasmaStateError::BelowMinimumDeposit {
+                quote_amount_deposited,
+                min_quote_deposit,
+            } => PlasmaError::BelowMinimumDeposit {
+                quote_amount_deposited,
+                min_quote_deposit,
+            },
+            PlasmaStateError::InconsistentLpPosition {
+                lp_shares,
+                withdrawable_lp_shares,
+                lp_shares_to_vest,
+            } => PlasmaError::InconsistentLpPosition {
+                lp_shares,
+                withdrawable_lp_shares,
+                lp_shares_to_vest,
+            },
+            PlasmaStateError::TvlCapExceeded {
+                quote_tvl,
+                max_quote_tvl,
+            } => PlasmaError::TvlCapExceeded {
+                quote_tvl,
+                max_quote_tvl,
+            },
+            PlasmaStateError::DeadlineExceeded {
+                deadline_slot,
+                current_slot,
+            } => PlasmaError::DeadlineExceeded {
+                deadline_slot,
+                current_slot,
+            },
         }
     }
 }
@@ -1,6 +1,14 @@
-use crate::{amm::SwapResult, instructions::ProtocolFeeRecipientParams};
+use crate::{
+    amm::{Amm, SwapResult},
+    errors::{PlasmaError, PlasmaResult},
+    fixed::I80F48,
+    instructions::ProtocolFeeRecipientParams,
+    ID,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
 pub struct PlasmaEventHeader {
@@ -56,6 +64,26 @@ pub enum PlasmaEvent {
         header: PlasmaEventHeader,
         event: TransferLiquidityEvent,
     },
+    SetLpFee {
+        header: PlasmaEventHeader,
+        event: SetLpFeeEvent,
+    },
+    BatchInitializeLpPositions {
+        header: PlasmaEventHeader,
+        event: BatchInitializeLpPositionsEvent,
+    },
+    CrankVesting {
+        header: PlasmaEventHeader,
+        event: CrankVestingEvent,
+    },
+    ReinvestProtocolFees {
+        header: PlasmaEventHeader,
+        event: ReinvestProtocolFeesEvent,
+    },
+    ReleaseVesting {
+        header: PlasmaEventHeader,
+        event: ReleaseVestingEvent,
+    },
 }
 
 #[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
@@ -68,6 +96,10 @@ pub struct SwapEvent {
     pub snapshot_base_liquidity: u64,
     pub snapshot_quote_liquidity: u64,
     pub swap_result: SwapResult,
+    /// Part of `swap_result.fee_in_quote` that accrued to LPs
+    pub lp_fee_quote: u64,
+    /// Part of `swap_result.fee_in_quote` allocated to the protocol fee recipients
+    pub protocol_fee_quote: u64,
 }
 impl From<(PlasmaEventHeader, SwapEvent)> for PlasmaEvent {
     fn from(value: (PlasmaEventHeader, SwapEvent)) -> Self {
@@ -94,6 +126,55 @@ pub struct AddLiquidityEvent {
     pub user_total_withdrawable_base: u64,
     pub user_total_withdrawable_quote: u64,
 }
+impl AddLiquidityEvent {
+    /// Returns the pool reserves and share supply after the deposit. The reward factor, fees and
+    /// vesting window are not part of the event and must be obtained elsewhere.
+    pub fn to_partial_amm(&self) -> PartialAmm {
+        PartialAmm {
+            base_reserves: self.pool_total_base_liquidity,
+            quote_reserves: self.pool_total_quote_liquitidy,
+            total_lp_shares: self.pool_total_lp_shares,
+            base_reserves_snapshot: self.snapshot_base_liquidity,
+            quote_reserves_snapshot: self.snapshot_quote_liquidity,
+        }
+    }
+}
+
+/// The parts of a pool's `Amm` state that can be recovered from a liquidity event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartialAmm {
+    pub base_reserves: u64,
+    pub quote_reserves: u64,
+    pub total_lp_shares: u64,
+    pub base_reserves_snapshot: u64,
+    pub quote_reserves_snapshot: u64,
+}
+
+impl PartialAmm {
+    /// Builds a synthetic `Amm` with these reserves for quoting. The reward factor and the
+    /// cumulative fee counters start at zero.
+    pub fn to_amm(
+        &self,
+        fee_in_bps: u32,
+        protocol_allocation_in_bps: u32,
+        lp_vesting_window: u64,
+        slot: u64,
+    ) -> Amm {
+        let mut amm = Amm::new_with_protocol_allocation_bps(
+            fee_in_bps,
+            protocol_allocation_in_bps,
+            lp_vesting_window,
+            slot,
+        );
+        amm.base_reserves = self.base_reserves;
+        amm.quote_reserves = self.quote_reserves;
+        amm.total_lp_shares = self.total_lp_shares;
+        amm.base_reserves_snapshot = self.base_reserves_snapshot;
+        amm.quote_reserves_snapshot = self.quote_reserves_snapshot;
+        amm
+    }
+}
+
 impl From<(PlasmaEventHeader, AddLiquidityEvent)> for PlasmaEvent {
     fn from(value: (PlasmaEventHeader, AddLiquidityEvent)) -> Self {
         PlasmaEvent::AddLiquidity {
@@ -170,8 +251,12 @@ impl From<(PlasmaEventHeader, WithdrawLpFeesEvent)> for PlasmaEvent {
 #[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
 pub struct InitializePoolEvent {
     pub lp_fee_in_bps: u64,
+    /// The protocol's share of the LP fee rounded down to a whole percentage
     pub protocol_fee_in_pct: u64,
     pub fee_recipient_params: [ProtocolFeeRecipientParams; 3],
+    /// The protocol's share of the LP fee in basis points. `decode_event_lenient` fills this in
+    /// from `protocol_fee_in_pct` for events emitted before it was added
+    pub protocol_fee_in_bps: u64,
 }
 impl From<(PlasmaEventHeader, InitializePoolEvent)> for PlasmaEvent {
     fn from(value: (PlasmaEventHeader, InitializePoolEvent)) -> Self {
@@ -210,3 +295,989 @@ impl From<(PlasmaEventHeader, TransferLiquidityEvent)> for PlasmaEvent {
         }
     }
 }
+
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct SetLpFeeEvent {
+    pub old_lp_fee_in_bps: u64,
+    pub new_lp_fee_in_bps: u64,
+}
+impl From<(PlasmaEventHeader, SetLpFeeEvent)> for PlasmaEvent {
+    fn from(value: (PlasmaEventHeader, SetLpFeeEvent)) -> Self {
+        PlasmaEvent::SetLpFee {
+            header: value.0,
+            event: value.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct BatchInitializeLpPositionsEvent {
+    pub owners: Vec<Pubkey>,
+}
+impl From<(PlasmaEventHeader, BatchInitializeLpPositionsEvent)> for PlasmaEvent {
+    fn from(value: (PlasmaEventHeader, BatchInitializeLpPositionsEvent)) -> Self {
+        PlasmaEvent::BatchInitializeLpPositions {
+            header: value.0,
+            event: value.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct CrankVestingEvent {
+    pub owner: Pubkey,
+    pub shares_vested: u64,
+}
+impl From<(PlasmaEventHeader, CrankVestingEvent)> for PlasmaEvent {
+    fn from(value: (PlasmaEventHeader, CrankVestingEvent)) -> Self {
+        PlasmaEvent::CrankVesting {
+            header: value.0,
+            event: value.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct ReinvestProtocolFeesEvent {
+    pub protocol_fee_recipient: Pubkey,
+    pub fees_reinvested: u64,
+}
+impl From<(PlasmaEventHeader, ReinvestProtocolFeesEvent)> for PlasmaEvent {
+    fn from(value: (PlasmaEventHeader, ReinvestProtocolFeesEvent)) -> Self {
+        PlasmaEvent::ReinvestProtocolFees {
+            header: value.0,
+            event: value.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct ReleaseVestingEvent {
+    pub owner: Pubkey,
+    pub shares_released: u64,
+}
+impl From<(PlasmaEventHeader, ReleaseVestingEvent)> for PlasmaEvent {
+    fn from(value: (PlasmaEventHeader, ReleaseVestingEvent)) -> Self {
+        PlasmaEvent::ReleaseVesting {
+            header: value.0,
+            event: value.1,
+        }
+    }
+}
+
+/// Size of the fields appended to `SwapEvent` after it was first emitted: `lp_fee_quote` and
+/// `protocol_fee_quote`
+const SWAP_EVENT_APPENDED_LEN: usize = 16;
+
+/// Size of the field appended to `InitializePoolEvent` after it was first emitted:
+/// `protocol_fee_in_bps`
+const INITIALIZE_POOL_EVENT_APPENDED_LEN: usize = 8;
+
+/// Decodes a Borsh-encoded Plasma event, ignoring any zero bytes after the end of the event as
+/// some RPC sources pad event data with trailing zeros.
+///
+/// Swap events emitted before the fee split was added to `SwapEvent` are exactly
+/// `SWAP_EVENT_APPENDED_LEN` bytes short, and decode with `lp_fee_quote` and `protocol_fee_quote`
+/// set to zero. Initialize pool events emitted before the protocol fee was recorded in basis
+/// points are `INITIALIZE_POOL_EVENT_APPENDED_LEN` bytes short, and decode with
+/// `protocol_fee_in_bps` derived from their percentage.
+///
+/// Data that is too short for the event returns `MissingExpectedArgument` and nonzero trailing
+/// bytes return `UnexpectedArgument`.
+pub fn decode_event_lenient(data: &[u8]) -> PlasmaResult<PlasmaEvent> {
+    let mut remaining = data;
+    let Ok(event) = PlasmaEvent::deserialize(&mut remaining) else {
+        return decode_legacy_event(data)
+            .map(fill_legacy_fields)
+            .ok_or(PlasmaError::MissingExpectedArgument);
+    };
+    if remaining.iter().any(|&byte| byte != 0) {
+        return Err(PlasmaError::UnexpectedArgument);
+    }
+    // Padding after a legacy event is indistinguishable from zeroed appended fields
+    Ok(fill_legacy_fields(event))
+}
+
+fn decode_legacy_event(data: &[u8]) -> Option<PlasmaEvent> {
+    match decode_padded_event(data, SWAP_EVENT_APPENDED_LEN) {
+        Some(event @ PlasmaEvent::Swap { .. }) => Some(event),
+        _ => match decode_padded_event(data, INITIALIZE_POOL_EVENT_APPENDED_LEN) {
+            Some(event @ PlasmaEvent::InitializePool { .. }) => Some(event),
+            _ => None,
+        },
+    }
+}
+
+fn decode_padded_event(data: &[u8], appended_len: usize) -> Option<PlasmaEvent> {
+    let padded = [data, &vec![0; appended_len]].concat();
+    let mut remaining = padded.as_slice();
+    let event = PlasmaEvent::deserialize(&mut remaining).ok()?;
+    remaining.is_empty().then_some(event)
+}
+
+/// Derives the fields that events emitted before they were added decode as zero
+fn fill_legacy_fields(event: PlasmaEvent) -> PlasmaEvent {
+    match event {
+        PlasmaEvent::InitializePool { header, mut event } => {
+            // Current events record at least 100 bps whenever the percentage is nonzero
+            if event.protocol_fee_in_bps == 0 {
+                event.protocol_fee_in_bps = event.protocol_fee_in_pct * 100;
+            }
+            PlasmaEvent::InitializePool { header, event }
+        }
+        event => event,
+    }
+}
+
+/// Parses every Plasma event out of a transaction's log messages.
+///
+/// Each event is paired with the index of the top-level Plasma invocation that emitted it,
+/// counted in order of the `Program <plasma> invoke [n]` markers in the logs. Plasma invoking
+/// itself to record events does not count as a separate invocation.
+pub fn parse_all_events_with_invocation(logs: &[String]) -> Vec<(u32, PlasmaEvent)> {
+    parse_events(logs)
+        .into_iter()
+        .map(|(invocation, event, _)| (invocation, event))
+        .collect()
+}
+
+/// Parses every Plasma event out of a transaction's log messages, pairing each event with the
+/// bytes it was decoded from, e.g. for indexers that archive raw payloads for replay. The bytes
+/// are the base64-decoded log data as emitted, including any trailing zero padding, and decode
+/// back to the event with `decode_event_lenient`.
+pub fn parse_plasma_events_with_raw(logs: &[String]) -> Vec<(PlasmaEvent, Vec<u8>)> {
+    parse_events(logs)
+        .into_iter()
+        .map(|(_, event, raw)| (event, raw))
+        .collect()
+}
+
+fn parse_events(logs: &[String]) -> Vec<(u32, PlasmaEvent, Vec<u8>)> {
+    let program_id = ID.to_string();
+    let mut events = vec![];
+    // Tracks whether each program on the invocation stack is Plasma
+    let mut invocation_stack: Vec<bool> = vec![];
+    let mut next_invocation_index = 0;
+    let mut current_invocation_index = 0;
+
+    for log in logs {
+        if let Some(data) = log.strip_prefix("Program data: ") {
+            if invocation_stack.last() != Some(&true) {
+                continue;
+            }
+            let Some(bytes) = data
+                .split_whitespace()
+                .next()
+                .and_then(|encoded| STANDARD.decode(encoded).ok())
+            else {
+                continue;
+            };
+            let Ok(event) = decode_event_lenient(&bytes) else {
+                continue;
+            };
+            events.push((current_invocation_index, event, bytes));
+        } else if let Some(rest) = log.strip_prefix("Program ") {
+            let mut parts = rest.split_whitespace();
+            let (Some(program), Some(status)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            match status {
+                "invoke" => {
+                    let is_plasma = program == program_id;
+                    if is_plasma && !invocation_stack.contains(&true) {
+                        current_invocation_index = next_invocation_index;
+                        next_invocation_index += 1;
+                    }
+                    invocation_stack.push(is_plasma);
+                }
+                "success" | "failed:" => {
+                    invocation_stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    events
+}
+
+/// Returns the volume-weighted average execution price of the `Swap` events in `events`, in
+/// atomic quote per atomic base. Each swap's price (quote transferred over base transferred, so
+/// including fees) is weighted by its base volume. Returns `None` if no base was swapped.
+pub fn vwap_from_events(events: &[PlasmaEvent]) -> Option<I80F48> {
+    let (base_volume, quote_volume) = events
+        .iter()
+        .filter_map(|event| match event {
+            PlasmaEvent::Swap { event, .. } => Some(&event.swap_result),
+            _ => None,
+        })
+        .fold((0_u128, 0_u128), |(base, quote), swap_result| {
+            (
+                base + swap_result.base_amount_to_transfer as u128,
+                quote + swap_result.quote_amount_to_transfer as u128,
+            )
+        });
+    if base_volume == 0 {
+        return None;
+    }
+    // sum(price * base) / sum(base) = sum(quote) / sum(base)
+    let vwap_bits = quote_volume
+        .checked_shl(48)
+        .filter(|bits| bits >> 48 == quote_volume)?
+        / base_volume;
+    i128::try_from(vwap_bits).ok().map(I80F48::from_bits)
+}
+
+/// Returns the number of distinct LP positions, keyed by pool and owner, touched by the
+/// `AddLiquidity`, `RemoveLiquidity`, `InitializeLpPosition` and `WithdrawLpFees` events in
+/// `events`. The owner is taken from the `InitializeLpPosition` event (whose signer is the payer)
+/// and from the header signer otherwise.
+pub fn active_lp_positions(events: &[PlasmaEvent]) -> usize {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            PlasmaEvent::InitializeLpPosition { header, event } => Some((header.pool, event.owner)),
+            PlasmaEvent::AddLiquidity { header, .. }
+            | PlasmaEvent::RemoveLiquidity { header, .. }
+            | PlasmaEvent::WithdrawLpFees { header, .. } => Some((header.pool, header.signer)),
+            _ => None,
+        })
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+/// Running totals of the LP fees withdrawn from each pool, built from `WithdrawLpFees` events
+#[derive(Debug, Clone, Default)]
+pub struct LpFeesTracker {
+    fees_withdrawn_by_pool: HashMap<Pubkey, u64>,
+}
+
+impl LpFeesTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the fees withdrawn in `event` to the total of the pool in `header`
+    pub fn ingest(&mut self, header: &PlasmaEventHeader, event: &WithdrawLpFeesEvent) {
+        let total = self.fees_withdrawn_by_pool.entry(header.pool).or_default();
+        *total = total.saturating_add(event.fees_withdrawn);
+    }
+
+    /// Ingests `event` if it is a `WithdrawLpFees` event, and ignores it otherwise
+    pub fn ingest_event(&mut self, event: &PlasmaEvent) {
+        if let PlasmaEvent::WithdrawLpFees { header, event } = event {
+            self.ingest(header, event);
+        }
+    }
+
+    /// Total LP fees withdrawn from `pool` across all ingested events
+    pub fn total_withdrawn(&self, pool: &Pubkey) -> u64 {
+        self.fees_withdrawn_by_pool
+            .get(pool)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// Lifetime liquidity flows into and out of a pool, built from `AddLiquidity` and
+/// `RemoveLiquidity` events
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolFlows {
+    pub base_deposited: u128,
+    pub quote_deposited: u128,
+    pub base_withdrawn: u128,
+    pub quote_withdrawn: u128,
+}
+
+impl PoolFlows {
+    /// Sums the deposits and withdrawals of every `AddLiquidity` and `RemoveLiquidity` event in
+    /// `events`, regardless of pool. Use `by_pool` when `events` spans several pools.
+    pub fn from_events(events: &[PlasmaEvent]) -> PoolFlows {
+        let mut flows = PoolFlows::default();
+        for event in events {
+            flows.ingest_event(event);
+        }
+        flows
+    }
+
+    /// Returns the flows of each pool with liquidity events in `events`
+    pub fn by_pool(events: &[PlasmaEvent]) -> HashMap<Pubkey, PoolFlows> {
+        let mut flows_by_pool = HashMap::<Pubkey, PoolFlows>::new();
+        for event in events {
+            if let PlasmaEvent::AddLiquidity { header, .. }
+            | PlasmaEvent::RemoveLiquidity { header, .. } = event
+            {
+                flows_by_pool
+                    .entry(header.pool)
+                    .or_default()
+                    .ingest_event(event);
+            }
+        }
+        flows_by_pool
+    }
+
+    fn ingest_event(&mut self, event: &PlasmaEvent) {
+        match event {
+            PlasmaEvent::AddLiquidity { event, .. } => {
+                self.base_deposited += event.user_base_deposited as u128;
+                self.quote_deposited += event.user_quote_deposited as u128;
+            }
+            PlasmaEvent::RemoveLiquidity { event, .. } => {
+                self.base_withdrawn += event.user_base_withdrawn as u128;
+                self.quote_withdrawn += event.user_quote_withdrawn as u128;
+            }
+            _ => {}
+        }
+    }
+
+    /// Base deposited minus base withdrawn. Negative if LPs withdrew more base than they
+    /// deposited, e.g. after traders sold base into the pool.
+    pub fn net_base_inflow(&self) -> i128 {
+        self.base_deposited as i128 - self.base_withdrawn as i128
+    }
+
+    /// Quote deposited minus quote withdrawn. Negative if LPs withdrew more quote than they
+    /// deposited, e.g. after traders bought base from the pool.
+    pub fn net_quote_inflow(&self) -> i128 {
+        self.quote_deposited as i128 - self.quote_withdrawn as i128
+    }
+}
+
+/// Fees withdrawn from a single pool, built from `WithdrawLpFees` and `WithdrawProtocolFees` events
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolFeesWithdrawn {
+    pub lp_fees_withdrawn: u128,
+    pub protocol_fees_withdrawn: u128,
+}
+
+impl PoolFeesWithdrawn {
+    /// LP and protocol fees withdrawn combined
+    pub fn total(&self) -> u128 {
+        self.lp_fees_withdrawn + self.protocol_fees_withdrawn
+    }
+}
+
+/// Quote fees withdrawn by LPs and protocol fee recipients, per pool
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeeDistributionSummary {
+    pub pools: HashMap<Pubkey, PoolFeesWithdrawn>,
+}
+
+impl FeeDistributionSummary {
+    /// Sums the fees of every `WithdrawLpFees` and `WithdrawProtocolFees` event in `events` by
+    /// the pool they were withdrawn from
+    pub fn from_events(events: &[PlasmaEvent]) -> FeeDistributionSummary {
+        let mut summary = FeeDistributionSummary::default();
+        for event in events {
+            match event {
+                PlasmaEvent::WithdrawLpFees { header, event } => {
+                    summary
+                        .pools
+                        .entry(header.pool)
+                        .or_default()
+                        .lp_fees_withdrawn += event.fees_withdrawn as u128;
+                }
+                PlasmaEvent::WithdrawProtocolFees { header, event } => {
+                    summary
+                        .pools
+                        .entry(header.pool)
+                        .or_default()
+                        .protocol_fees_withdrawn += event.fees_withdrawn as u128;
+                }
+                _ => {}
+            }
+        }
+        summary
+    }
+
+    /// LP fees withdrawn across all pools
+    pub fn lp_fees_withdrawn(&self) -> u128 {
+        self.pools.values().map(|pool| pool.lp_fees_withdrawn).sum()
+    }
+
+    /// Protocol fees withdrawn across all pools
+    pub fn protocol_fees_withdrawn(&self) -> u128 {
+        self.pools
+            .values()
+            .map(|pool| pool.protocol_fees_withdrawn)
+            .sum()
+    }
+
+    /// LP and protocol fees withdrawn across all pools
+    pub fn total_fees_withdrawn(&self) -> u128 {
+        self.pools.values().map(PoolFeesWithdrawn::total).sum()
+    }
+}
+
+/// Tracks the deposits of one LP into one pool, along with the pool prices observed since, from
+/// `AddLiquidity` and `Swap` events
+#[derive(Debug, Clone)]
+pub struct LpCostBasisTracker {
+    pool: Pubkey,
+    owner: Pubkey,
+    base_deposited: u64,
+    quote_deposited: u64,
+    price_range: Option<(I80F48, I80F48)>,
+}
+
+impl LpCostBasisTracker {
+    pub fn new(pool: Pubkey, owner: Pubkey) -> Self {
+        Self {
+            pool,
+            owner,
+            base_deposited: 0,
+            quote_deposited: 0,
+            price_range: None,
+        }
+    }
+
+    /// Ingests `event` if it is an `AddLiquidity` or `Swap` event of the tracked pool, and
+    /// ignores it otherwise. Only deposits signed by the tracked owner count towards the cost
+    /// basis, but every event of the pool counts towards the observed price range.
+    pub fn ingest_event(&mut self, event: &PlasmaEvent) {
+        match event {
+            PlasmaEvent::AddLiquidity { header, event } if header.pool == self.pool => {
+                if header.signer == self.owner {
+                    self.base_deposited = self
+                        .base_deposited
+                        .saturating_add(event.user_base_deposited);
+                    self.quote_deposited = self
+                        .quote_deposited
+                        .saturating_add(event.user_quote_deposited);
+                }
+                self.observe_price(
+                    event.pool_total_base_liquidity,
+                    event.pool_total_quote_liquitidy,
+                );
+            }
+            PlasmaEvent::Swap { header, event } if header.pool == self.pool => {
+                self.observe_price(event.post_base_liquidity, event.post_quote_liquidity);
+            }
+            _ => {}
+        }
+    }
+
+    /// Total base deposited by the owner across all ingested events
+    pub fn base_deposited(&self) -> u64 {
+        self.base_deposited
+    }
+
+    /// Total quote deposited by the owner across all ingested events
+    pub fn quote_deposited(&self) -> u64 {
+        self.quote_deposited
+    }
+
+    /// Returns the lowest and highest spot prices (atomic quote per atomic base) of the pool
+    /// across all ingested events, or `None` if no price was observed
+    pub fn price_range(&self) -> Option<(I80F48, I80F48)> {
+        self.price_range
+    }
+
+    fn observe_price(&mut self, base_reserves: u64, quote_reserves: u64) {
+        if base_reserves == 0 {
+            return;
+        }
+        let price = I80F48::from_fraction(quote_reserves, base_reserves);
+        self.price_range = Some(match self.price_range {
+            Some((low, high)) => (
+                if price < low { price } else { low },
+                if price > high { price } else { high },
+            ),
+            None => (price, price),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{amm::Side, instructions::PlasmaInstruction};
+
+    fn header(sequence_number: u64) -> PlasmaEventHeader {
+        PlasmaEventHeader {
+            sequence_number,
+            slot: 100,
+            timestamp: 0,
+            pool: Pubkey::new_unique(),
+            signer: Pubkey::new_unique(),
+            base_decimals: 9,
+            quote_decimals: 6,
+        }
+    }
+
+    fn data_log(event: PlasmaEvent) -> String {
+        format!(
+            "Program data: {}",
+            STANDARD.encode(event.try_to_vec().unwrap())
+        )
+    }
+
+    #[test]
+    fn test_parse_all_events_with_invocation() {
+        let plasma = ID.to_string();
+        let other = Pubkey::new_unique().to_string();
+        let logs = vec![
+            format!("Program {} invoke [1]", plasma),
+            "Program log: WithdrawLpFees".to_string(),
+            data_log((header(0), WithdrawLpFeesEvent { fees_withdrawn: 10 }).into()),
+            format!("Program {} invoke [2]", plasma),
+            format!("Program {} success", plasma),
+            format!("Program {} success", plasma),
+            // Events logged by other programs are ignored
+            format!("Program {} invoke [1]", other),
+            data_log((header(5), WithdrawLpFeesEvent { fees_withdrawn: 99 }).into()),
+            format!("Program {} invoke [2]", plasma),
+            data_log((header(1), WithdrawLpFeesEvent { fees_withdrawn: 20 }).into()),
+            format!("Program {} invoke [3]", plasma),
+            format!("Program {} success", plasma),
+            format!("Program {} success", plasma),
+            format!("Program {} success", other),
+        ];
+
+        let events = parse_all_events_with_invocation(&logs);
+        assert_eq!(events.len(), 2);
+        for ((invocation, event), (expected_invocation, expected_fees)) in
+            events.iter().zip([(0, 10), (1, 20)])
+        {
+            assert_eq!(*invocation, expected_invocation);
+            match event {
+                PlasmaEvent::WithdrawLpFees { event, .. } => {
+                    assert_eq!(event.fees_withdrawn, expected_fees)
+                }
+                _ => panic!("Unexpected event {:?}", event),
+            }
+        }
+    }
+
+    #[test]
+    fn test_lp_fees_tracker() {
+        let pool_a = header(0);
+        let pool_b = header(1);
+        let events: Vec<PlasmaEvent> = vec![
+            (pool_a.clone(), WithdrawLpFeesEvent { fees_withdrawn: 10 }).into(),
+            (pool_b.clone(), WithdrawLpFeesEvent { fees_withdrawn: 7 }).into(),
+            (
+                pool_a.clone(),
+                TransferLiquidityEvent {
+                    src: Pubkey::new_unique(),
+                    dst: Pubkey::new_unique(),
+                    lp_shares_transferred: 1_000,
+                },
+            )
+                .into(),
+            (pool_a.clone(), WithdrawLpFeesEvent { fees_withdrawn: 15 }).into(),
+        ];
+
+        let mut tracker = LpFeesTracker::new();
+        for event in events.iter() {
+            tracker.ingest_event(event);
+        }
+        assert_eq!(tracker.total_withdrawn(&pool_a.pool), 25);
+        assert_eq!(tracker.total_withdrawn(&pool_b.pool), 7);
+        assert_eq!(tracker.total_withdrawn(&Pubkey::new_unique()), 0);
+    }
+
+    #[test]
+    fn test_decode_crank_vesting_event() {
+        let owner = Pubkey::new_unique();
+        let event: PlasmaEvent = (
+            header(3),
+            CrankVestingEvent {
+                owner,
+                shares_vested: 1_000,
+            },
+        )
+            .into();
+        let bytes = event.try_to_vec().unwrap();
+        assert_eq!(bytes[0], PlasmaInstruction::CrankVesting as u8);
+
+        let plasma = ID.to_string();
+        let logs = vec![
+            format!("Program {} invoke [1]", plasma),
+            "Program log: CrankVesting".to_string(),
+            data_log(event),
+            format!("Program {} success", plasma),
+        ];
+        let events = parse_all_events_with_invocation(&logs);
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            (0, PlasmaEvent::CrankVesting { header, event }) => {
+                assert_eq!(header.sequence_number, 3);
+                assert_eq!(event.owner, owner);
+                assert_eq!(event.shares_vested, 1_000);
+            }
+            other => panic!("Unexpected event {:?}", other),
+        }
+    }
+
+    fn swap(
+        side: Side,
+        base_amount_to_transfer: u64,
+        quote_amount_to_transfer: u64,
+    ) -> PlasmaEvent {
+        (
+            header(0),
+            SwapEvent {
+                swap_sequence_number: 0,
+                pre_base_liquidity: 0,
+                pre_quote_liquidity: 0,
+                post_base_liquidity: 0,
+                post_quote_liquidity: 0,
+                snapshot_base_liquidity: 0,
+                snapshot_quote_liquidity: 0,
+                swap_result: SwapResult {
+                    side,
+                    base_amount_to_transfer,
+                    quote_amount_to_transfer,
+                    base_matched_as_limit_order: 0,
+                    quote_matched_as_limit_order: 0,
+                    base_matched_as_swap: base_amount_to_transfer,
+                    quote_matched_as_swap: quote_amount_to_transfer,
+                    fee_in_quote: 0,
+                },
+                lp_fee_quote: 0,
+                protocol_fee_quote: 0,
+            },
+        )
+            .into()
+    }
+
+    #[test]
+    fn test_vwap_from_events() {
+        assert_eq!(vwap_from_events(&[]), None);
+
+        // 100 base at 2, 300 base at 3 and 600 base at 4.5
+        let events = vec![
+            swap(Side::Buy, 100, 200),
+            (header(1), WithdrawLpFeesEvent { fees_withdrawn: 10 }).into(),
+            swap(Side::Sell, 300, 900),
+            swap(Side::Buy, 600, 2_700),
+        ];
+        // (2 * 100 + 3 * 300 + 4.5 * 600) / 1000 = 3.8
+        assert_eq!(
+            vwap_from_events(&events),
+            Some(I80F48::from_fraction(38, 10))
+        );
+        assert_eq!(vwap_from_events(&events[1..2]), None);
+    }
+
+    #[test]
+    fn test_parse_plasma_events_with_raw() {
+        let plasma = ID.to_string();
+        let mut padded = swap(Side::Sell, 10, 11).try_to_vec().unwrap();
+        padded.extend_from_slice(&[0; 8]);
+        let logs = vec![
+            format!("Program {} invoke [1]", plasma),
+            data_log((header(0), WithdrawLpFeesEvent { fees_withdrawn: 10 }).into()),
+            format!("Program data: {}", STANDARD.encode(&padded)),
+            format!("Program {} success", plasma),
+        ];
+
+        let events = parse_plasma_events_with_raw(&logs);
+        assert_eq!(events.len(), 2);
+        // The raw bytes are kept as logged, padding included
+        assert_eq!(events[1].1, padded);
+        for (event, raw) in events.iter() {
+            let decoded = decode_event_lenient(raw).unwrap();
+            assert_eq!(decoded.try_to_vec().unwrap(), event.try_to_vec().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_decode_event_lenient() {
+        let event = swap(Side::Buy, 10, 11);
+        let mut bytes = event.try_to_vec().unwrap();
+        let unpadded_len = bytes.len();
+        bytes.extend_from_slice(&[0; 32]);
+        assert!(PlasmaEvent::try_from_slice(&bytes).is_err());
+
+        match decode_event_lenient(&bytes).unwrap() {
+            PlasmaEvent::Swap { event, .. } => {
+                assert_eq!(event.swap_result.base_amount_to_transfer, 10);
+                assert_eq!(event.swap_result.quote_amount_to_transfer, 11);
+            }
+            other => panic!("Unexpected event {:?}", other),
+        }
+
+        // The parser accepts padded event data too
+        let plasma = ID.to_string();
+        let logs = vec![
+            format!("Program {} invoke [1]", plasma),
+            format!("Program data: {}", STANDARD.encode(&bytes)),
+            format!("Program {} success", plasma),
+        ];
+        assert_eq!(parse_all_events_with_invocation(&logs).len(), 1);
+
+        bytes.push(1);
+        assert_eq!(
+            decode_event_lenient(&bytes).unwrap_err(),
+            PlasmaError::UnexpectedArgument
+        );
+        assert_eq!(
+            decode_event_lenient(&bytes[..unpadded_len - 1]).unwrap_err(),
+            PlasmaError::MissingExpectedArgument
+        );
+    }
+
+    #[test]
+    fn test_decode_swap_event_fee_split() {
+        let PlasmaEvent::Swap { header, mut event } = swap(Side::Buy, 1_000, 1_003) else {
+            unreachable!()
+        };
+        event.swap_result.fee_in_quote = 3;
+        event.lp_fee_quote = 2;
+        event.protocol_fee_quote = 1;
+        let bytes = PlasmaEvent::from((header, event)).try_to_vec().unwrap();
+
+        let PlasmaEvent::Swap { event, .. } = decode_event_lenient(&bytes).unwrap() else {
+            panic!("Expected a swap event");
+        };
+        assert_eq!(event.lp_fee_quote, 2);
+        assert_eq!(event.protocol_fee_quote, 1);
+        assert_eq!(
+            event.lp_fee_quote + event.protocol_fee_quote,
+            event.swap_result.fee_in_quote
+        );
+
+        // Events emitted before the split was added lack the trailing fields
+        let legacy = &bytes[..bytes.len() - SWAP_EVENT_APPENDED_LEN];
+        assert!(PlasmaEvent::try_from_slice(legacy).is_err());
+        let PlasmaEvent::Swap { event, .. } = decode_event_lenient(legacy).unwrap() else {
+            panic!("Expected a swap event");
+        };
+        assert_eq!(event.swap_result.fee_in_quote, 3);
+        assert_eq!((event.lp_fee_quote, event.protocol_fee_quote), (0, 0));
+
+        // Other truncations are still rejected
+        assert_eq!(
+            decode_event_lenient(&bytes[..bytes.len() - 1]).unwrap_err(),
+            PlasmaError::MissingExpectedArgument
+        );
+    }
+
+    #[test]
+    fn test_lp_cost_basis_tracker_price_range() {
+        let pool_header = header(0);
+        let owner = pool_header.signer;
+        let mut tracker = LpCostBasisTracker::new(pool_header.pool, owner);
+        assert_eq!(tracker.price_range(), None);
+
+        let swap_to = |base, quote| -> PlasmaEvent {
+            match swap(Side::Buy, 1, 1) {
+                PlasmaEvent::Swap { mut event, .. } => {
+                    event.post_base_liquidity = base;
+                    event.post_quote_liquidity = quote;
+                    (pool_header.clone(), event).into()
+                }
+                _ => unreachable!(),
+            }
+        };
+        let deposit: PlasmaEvent = (
+            pool_header.clone(),
+            AddLiquidityEvent {
+                pool_total_lp_shares: 2_000,
+                pool_total_base_liquidity: 2_000,
+                pool_total_quote_liquitidy: 4_000,
+                snapshot_base_liquidity: 2_000,
+                snapshot_quote_liquidity: 4_000,
+                user_lp_shares_received: 1_000,
+                user_lp_shares_available: 0,
+                user_lp_shares_locked: 1_000,
+                user_lp_shares_unlocked_for_withdrawal: 0,
+                user_base_deposited: 1_000,
+                user_quote_deposited: 2_000,
+                user_total_withdrawable_base: 0,
+                user_total_withdrawable_quote: 0,
+            },
+        )
+            .into();
+
+        for event in [
+            deposit,
+            swap_to(4_000, 2_000),
+            swap_to(1_000, 8_000),
+            swap_to(2_000, 4_000),
+            // Swaps in other pools are ignored
+            swap(Side::Sell, 100, 1),
+        ] {
+            tracker.ingest_event(&event);
+        }
+        assert_eq!(
+            tracker.price_range(),
+            Some((I80F48::from_fraction(1, 2), I80F48::from_num(8)))
+        );
+        assert_eq!(tracker.base_deposited(), 1_000);
+        assert_eq!(tracker.quote_deposited(), 2_000);
+    }
+
+    #[test]
+    fn test_add_liquidity_event_to_partial_amm() {
+        let mut amm = Amm::new(30, 0, 0, 0);
+        amm.mint(0, 2_000_000_000, 3_000_000_000, Some(2_449_489_742))
+            .unwrap();
+        let event = AddLiquidityEvent {
+            pool_total_lp_shares: amm.total_lp_shares,
+            pool_total_base_liquidity: amm.base_reserves,
+            pool_total_quote_liquitidy: amm.quote_reserves,
+            snapshot_base_liquidity: amm.base_reserves_snapshot,
+            snapshot_quote_liquidity: amm.quote_reserves_snapshot,
+            user_lp_shares_received: amm.total_lp_shares,
+            user_lp_shares_available: 0,
+            user_lp_shares_locked: amm.total_lp_shares,
+            user_lp_shares_unlocked_for_withdrawal: 0,
+            user_base_deposited: 2_000_000_000,
+            user_quote_deposited: 3_000_000_000,
+            user_total_withdrawable_base: 0,
+            user_total_withdrawable_quote: 0,
+        };
+
+        let partial = event.to_partial_amm();
+        assert_eq!(partial.base_reserves, 2_000_000_000);
+        assert_eq!(partial.quote_reserves, 3_000_000_000);
+        assert_eq!(partial.total_lp_shares, 2_449_489_742);
+
+        // The seeded pool quotes like the original one
+        let mut seeded = partial.to_amm(30, 0, 0, 0);
+        let expected = amm.buy_exact_in(0, 10_000_000).unwrap();
+        let actual = seeded.buy_exact_in(0, 10_000_000).unwrap();
+        assert_eq!(
+            actual.base_amount_to_transfer,
+            expected.base_amount_to_transfer
+        );
+        assert_eq!(actual.fee_in_quote, expected.fee_in_quote);
+    }
+
+    #[test]
+    fn test_active_lp_positions() {
+        let pool = Pubkey::new_unique();
+        let owners = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let signed_by = |signer: Pubkey| PlasmaEventHeader {
+            pool,
+            signer,
+            ..header(0)
+        };
+        let add_liquidity = AddLiquidityEvent::try_from_slice(&[0; 104]).unwrap();
+        let events: Vec<PlasmaEvent> = vec![
+            // The payer signs, but the position belongs to the owner in the event
+            (
+                signed_by(Pubkey::new_unique()),
+                InitializeLpPositionEvent { owner: owners[0] },
+            )
+                .into(),
+            (signed_by(owners[0]), add_liquidity.clone()).into(),
+            (signed_by(owners[1]), add_liquidity.clone()).into(),
+            (signed_by(owners[1]), add_liquidity).into(),
+            (
+                signed_by(owners[2]),
+                WithdrawLpFeesEvent { fees_withdrawn: 1 },
+            )
+                .into(),
+            (
+                signed_by(owners[2]),
+                WithdrawLpFeesEvent { fees_withdrawn: 2 },
+            )
+                .into(),
+            // Swaps don't touch LP positions
+            swap(Side::Buy, 1, 1),
+        ];
+        assert_eq!(active_lp_positions(&events), 3);
+        assert_eq!(active_lp_positions(&[]), 0);
+    }
+
+    #[test]
+    fn test_pool_flows() {
+        let pool_header = header(0);
+        let mut deposit = AddLiquidityEvent::try_from_slice(&[0; 104]).unwrap();
+        deposit.user_base_deposited = 1_000;
+        deposit.user_quote_deposited = 2_000;
+        let mut withdrawal = RemoveLiquidityEvent::try_from_slice(&[0; 104]).unwrap();
+        withdrawal.user_base_withdrawn = 400;
+        withdrawal.user_quote_withdrawn = 900;
+
+        let other_header = header(1);
+        let events: Vec<PlasmaEvent> = vec![
+            (pool_header.clone(), deposit.clone()).into(),
+            (pool_header.clone(), withdrawal).into(),
+            (other_header.clone(), deposit).into(),
+            swap(Side::Buy, 1, 1),
+        ];
+
+        let flows_by_pool = PoolFlows::by_pool(&events);
+        assert_eq!(flows_by_pool.len(), 2);
+        let flows = flows_by_pool[&pool_header.pool];
+        assert_eq!(flows, PoolFlows::from_events(&events[..2]));
+        assert_eq!(flows.net_base_inflow(), 600);
+        assert_eq!(flows.net_quote_inflow(), 1_100);
+        assert_eq!(flows_by_pool[&other_header.pool].net_base_inflow(), 1_000);
+
+        // Without splitting by pool, every event counts
+        let all = PoolFlows::from_events(&events);
+        assert_eq!(all.base_deposited, 2_000);
+        assert_eq!(all.net_quote_inflow(), 3_100);
+    }
+
+    #[test]
+    fn test_fee_distribution_summary() {
+        let pool_header = header(0);
+        let other_header = header(1);
+        let lp_fees = |fees_withdrawn| WithdrawLpFeesEvent { fees_withdrawn };
+        let events: Vec<PlasmaEvent> = vec![
+            (pool_header.clone(), lp_fees(300)).into(),
+            swap(Side::Buy, 1, 1),
+            (pool_header.clone(), lp_fees(200)).into(),
+            (
+                other_header.clone(),
+                WithdrawProtocolFeesEvent {
+                    protocol_fee_recipient: Pubkey::new_unique(),
+                    fees_withdrawn: 150,
+                },
+            )
+                .into(),
+        ];
+
+        let summary = FeeDistributionSummary::from_events(&events);
+        assert_eq!(summary.pools.len(), 2);
+        assert_eq!(
+            summary.pools[&pool_header.pool],
+            PoolFeesWithdrawn {
+                lp_fees_withdrawn: 500,
+                protocol_fees_withdrawn: 0,
+            }
+        );
+        assert_eq!(summary.pools[&other_header.pool].total(), 150);
+        assert_eq!(summary.lp_fees_withdrawn(), 500);
+        assert_eq!(summary.protocol_fees_withdrawn(), 150);
+        assert_eq!(summary.total_fees_withdrawn(), 650);
+    }
+
+    #[test]
+    fn test_decode_legacy_initialize_pool_event() {
+        let event = InitializePoolEvent {
+            lp_fee_in_bps: 30,
+            protocol_fee_in_pct: 20,
+            fee_recipient_params: [ProtocolFeeRecipientParams::default(); 3],
+            protocol_fee_in_bps: 2_050,
+        };
+        let bytes = PlasmaEvent::from((header(0), event)).try_to_vec().unwrap();
+        let PlasmaEvent::InitializePool { event, .. } = decode_event_lenient(&bytes).unwrap()
+        else {
+            panic!("Expected an initialize pool event");
+        };
+        assert_eq!(event.protocol_fee_in_bps, 2_050);
+
+        // Events emitted before the basis points were added only record a percentage
+        let legacy = &bytes[..bytes.len() - INITIALIZE_POOL_EVENT_APPENDED_LEN];
+        assert!(PlasmaEvent::try_from_slice(legacy).is_err());
+        for data in [legacy.to_vec(), [legacy, &[0; 32]].concat()] {
+            let PlasmaEvent::InitializePool { event, .. } = decode_event_lenient(&data).unwrap()
+            else {
+                panic!("Expected an initialize pool event");
+            };
+            assert_eq!(event.protocol_fee_in_pct, 20);
+            assert_eq!(event.protocol_fee_in_bps, 2_000);
+        }
+    }
+}
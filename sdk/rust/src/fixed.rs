@@ -35,10 +35,14 @@ impl I80F48 {
         value.floor().to_num()
     }
 
+    /// Returns the raw bits of the value, i.e. the value multiplied by 2^48
     pub fn to_bits(&self) -> i128 {
         self.inner
     }
 
+    /// Reinterprets `bits` as a fixed-point value with 48 fractional bits. Use this when reading
+    /// an `I80F48` that was stored as a raw `i128`: the stored integer is the value times 2^48,
+    /// not the value itself.
     pub fn from_bits(bits: i128) -> Self {
         Self { inner: bits }
     }
@@ -0,0 +1,496 @@
+//! Account lists of each Plasma instruction, separate from the encoding of its parameters.
+//!
+//! Each struct holds the keys an instruction reads or writes, and `to_account_metas` returns them
+//! in the order of the instruction's account annotations with the expected signer and writable
+//! flags. The `new` constructors derive the vaults, LP positions and log authority; build the
+//! struct directly to supply keys that were derived elsewhere.
+
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey, system_program};
+
+use crate::{get_log_authority, get_lp_position_address, get_vault_address, spl_token, ID};
+
+/// Produces the ordered account metas of an instruction
+pub trait ToAccountMetas {
+    fn to_account_metas(&self) -> Vec<AccountMeta>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapAccounts {
+    pub log_authority: Pubkey,
+    pub pool: Pubkey,
+    pub trader: Pubkey,
+    pub base_account: Pubkey,
+    pub quote_account: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+}
+
+impl SwapAccounts {
+    pub fn new(
+        pool: &Pubkey,
+        trader: &Pubkey,
+        base_mint: &Pubkey,
+        quote_mint: &Pubkey,
+        base_account: &Pubkey,
+        quote_account: &Pubkey,
+    ) -> Self {
+        Self {
+            log_authority: get_log_authority(&ID),
+            pool: *pool,
+            trader: *trader,
+            base_account: *base_account,
+            quote_account: *quote_account,
+            base_vault: get_vault_address(&ID, pool, base_mint).0,
+            quote_vault: get_vault_address(&ID, pool, quote_mint).0,
+        }
+    }
+}
+
+impl ToAccountMetas for SwapAccounts {
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(ID, false),
+            AccountMeta::new_readonly(self.log_authority, false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new_readonly(self.trader, true),
+            AccountMeta::new(self.base_account, false),
+            AccountMeta::new(self.quote_account, false),
+            AccountMeta::new(self.base_vault, false),
+            AccountMeta::new(self.quote_vault, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ]
+    }
+}
+
+/// Accounts of `AddLiquidity` and `RemoveLiquidity`, which share the same layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiquidityAccounts {
+    pub log_authority: Pubkey,
+    pub pool: Pubkey,
+    pub trader: Pubkey,
+    pub lp_position: Pubkey,
+    pub base_account: Pubkey,
+    pub quote_account: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+}
+
+impl LiquidityAccounts {
+    pub fn new(
+        pool: &Pubkey,
+        trader: &Pubkey,
+        base_mint: &Pubkey,
+        quote_mint: &Pubkey,
+        base_account: &Pubkey,
+        quote_account: &Pubkey,
+    ) -> Self {
+        Self {
+            log_authority: get_log_authority(&ID),
+            pool: *pool,
+            trader: *trader,
+            lp_position: get_lp_position_address(&ID, pool, trader).0,
+            base_account: *base_account,
+            quote_account: *quote_account,
+            base_vault: get_vault_address(&ID, pool, base_mint).0,
+            quote_vault: get_vault_address(&ID, pool, quote_mint).0,
+        }
+    }
+}
+
+impl ToAccountMetas for LiquidityAccounts {
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(ID, false),
+            AccountMeta::new_readonly(self.log_authority, false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new_readonly(self.trader, true),
+            AccountMeta::new(self.lp_position, false),
+            AccountMeta::new(self.base_account, false),
+            AccountMeta::new(self.quote_account, false),
+            AccountMeta::new(self.base_vault, false),
+            AccountMeta::new(self.quote_vault, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenounceLiquidityAccounts {
+    pub log_authority: Pubkey,
+    pub pool: Pubkey,
+    pub trader: Pubkey,
+    pub lp_position: Pubkey,
+}
+
+impl RenounceLiquidityAccounts {
+    pub fn new(pool: &Pubkey, trader: &Pubkey) -> Self {
+        Self {
+            log_authority: get_log_authority(&ID),
+            pool: *pool,
+            trader: *trader,
+            lp_position: get_lp_position_address(&ID, pool, trader).0,
+        }
+    }
+}
+
+impl ToAccountMetas for RenounceLiquidityAccounts {
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(ID, false),
+            AccountMeta::new_readonly(self.log_authority, false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new_readonly(self.trader, true),
+            AccountMeta::new(self.lp_position, false),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InitializePoolAccounts {
+    pub log_authority: Pubkey,
+    pub pool: Pubkey,
+    pub pool_creator: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+}
+
+impl InitializePoolAccounts {
+    pub fn new(
+        pool: &Pubkey,
+        pool_creator: &Pubkey,
+        base_mint: &Pubkey,
+        quote_mint: &Pubkey,
+    ) -> Self {
+        Self {
+            log_authority: get_log_authority(&ID),
+            pool: *pool,
+            pool_creator: *pool_creator,
+            base_mint: *base_mint,
+            quote_mint: *quote_mint,
+            base_vault: get_vault_address(&ID, pool, base_mint).0,
+            quote_vault: get_vault_address(&ID, pool, quote_mint).0,
+        }
+    }
+}
+
+impl ToAccountMetas for InitializePoolAccounts {
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(ID, false),
+            AccountMeta::new_readonly(self.log_authority, false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new(self.pool_creator, true),
+            AccountMeta::new_readonly(self.base_mint, false),
+            AccountMeta::new_readonly(self.quote_mint, false),
+            AccountMeta::new(self.base_vault, false),
+            AccountMeta::new(self.quote_vault, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InitializeLpPositionAccounts {
+    pub log_authority: Pubkey,
+    pub pool: Pubkey,
+    pub payer: Pubkey,
+    pub lp_position_owner: Pubkey,
+    pub lp_position: Pubkey,
+}
+
+impl InitializeLpPositionAccounts {
+    pub fn new(pool: &Pubkey, payer: &Pubkey, lp_position_owner: &Pubkey) -> Self {
+        Self {
+            log_authority: get_log_authority(&ID),
+            pool: *pool,
+            payer: *payer,
+            lp_position_owner: *lp_position_owner,
+            lp_position: get_lp_position_address(&ID, pool, lp_position_owner).0,
+        }
+    }
+}
+
+impl ToAccountMetas for InitializeLpPositionAccounts {
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(ID, false),
+            AccountMeta::new_readonly(self.log_authority, false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new(self.payer, true),
+            AccountMeta::new_readonly(self.lp_position_owner, false),
+            AccountMeta::new(self.lp_position, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ]
+    }
+}
+
+/// Accounts of `BatchInitializeLpPositions`. Each entry of `lp_positions` is an owner and the
+/// address of its LP position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchInitializeLpPositionsAccounts {
+    pub log_authority: Pubkey,
+    pub pool: Pubkey,
+    pub payer: Pubkey,
+    pub lp_positions: Vec<(Pubkey, Pubkey)>,
+}
+
+impl BatchInitializeLpPositionsAccounts {
+    pub fn new(pool: &Pubkey, payer: &Pubkey, owners: &[Pubkey]) -> Self {
+        Self {
+            log_authority: get_log_authority(&ID),
+            pool: *pool,
+            payer: *payer,
+            lp_positions: owners
+                .iter()
+                .map(|owner| (*owner, get_lp_position_address(&ID, pool, owner).0))
+                .collect(),
+        }
+    }
+}
+
+impl ToAccountMetas for BatchInitializeLpPositionsAccounts {
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        let mut accounts = vec![
+            AccountMeta::new_readonly(ID, false),
+            AccountMeta::new_readonly(self.log_authority, false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new(self.payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ];
+        for (owner, lp_position) in &self.lp_positions {
+            accounts.push(AccountMeta::new_readonly(*owner, false));
+            accounts.push(AccountMeta::new(*lp_position, false));
+        }
+        accounts
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferLiquidityAccounts {
+    pub log_authority: Pubkey,
+    pub pool: Pubkey,
+    pub src: Pubkey,
+    pub src_lp_position: Pubkey,
+    pub dst_lp_position: Pubkey,
+}
+
+impl TransferLiquidityAccounts {
+    pub fn new(pool: &Pubkey, src: &Pubkey, dst: &Pubkey) -> Self {
+        Self {
+            log_authority: get_log_authority(&ID),
+            pool: *pool,
+            src: *src,
+            src_lp_position: get_lp_position_address(&ID, pool, src).0,
+            dst_lp_position: get_lp_position_address(&ID, pool, dst).0,
+        }
+    }
+}
+
+impl ToAccountMetas for TransferLiquidityAccounts {
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(ID, false),
+            AccountMeta::new_readonly(self.log_authority, false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new(self.src, true),
+            AccountMeta::new(self.src_lp_position, false),
+            AccountMeta::new(self.dst_lp_position, false),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrankVestingAccounts {
+    pub log_authority: Pubkey,
+    pub pool: Pubkey,
+    pub cranker: Pubkey,
+    pub lp_position_owner: Pubkey,
+    pub lp_position: Pubkey,
+}
+
+impl CrankVestingAccounts {
+    pub fn new(pool: &Pubkey, cranker: &Pubkey, lp_position_owner: &Pubkey) -> Self {
+        Self {
+            log_authority: get_log_authority(&ID),
+            pool: *pool,
+            cranker: *cranker,
+            lp_position_owner: *lp_position_owner,
+            lp_position: get_lp_position_address(&ID, pool, lp_position_owner).0,
+        }
+    }
+}
+
+impl ToAccountMetas for CrankVestingAccounts {
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(ID, false),
+            AccountMeta::new_readonly(self.log_authority, false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new_readonly(self.cranker, true),
+            AccountMeta::new_readonly(self.lp_position_owner, false),
+            AccountMeta::new(self.lp_position, false),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReleaseVestingAccounts {
+    pub log_authority: Pubkey,
+    pub pool: Pubkey,
+    pub pool_authority: Pubkey,
+    pub lp_position_owner: Pubkey,
+    pub lp_position: Pubkey,
+}
+
+impl ReleaseVestingAccounts {
+    pub fn new(pool: &Pubkey, pool_authority: &Pubkey, lp_position_owner: &Pubkey) -> Self {
+        Self {
+            log_authority: get_log_authority(&ID),
+            pool: *pool,
+            pool_authority: *pool_authority,
+            lp_position_owner: *lp_position_owner,
+            lp_position: get_lp_position_address(&ID, pool, lp_position_owner).0,
+        }
+    }
+}
+
+impl ToAccountMetas for ReleaseVestingAccounts {
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(ID, false),
+            AccountMeta::new_readonly(self.log_authority, false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new_readonly(self.pool_authority, true),
+            AccountMeta::new_readonly(self.lp_position_owner, false),
+            AccountMeta::new(self.lp_position, false),
+        ]
+    }
+}
+
+/// Accounts of the instructions that only touch the pool and are signed by a single authority:
+/// `SetLpFee` (signed by the pool authority) and `ReinvestProtocolFees` (signed by a protocol
+/// fee recipient)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WithdrawProtocolFeesAccounts {
+    pub log_authority: Pubkey,
+    pub pool: Pubkey,
+    pub protocol_fee_recipient: Pubkey,
+    pub quote_account: Pubkey,
+    pub quote_vault: Pubkey,
+}
+
+impl WithdrawProtocolFeesAccounts {
+    pub fn new(
+        pool: &Pubkey,
+        protocol_fee_recipient: &Pubkey,
+        quote_mint: &Pubkey,
+        quote_account: &Pubkey,
+    ) -> Self {
+        Self {
+            log_authority: get_log_authority(&ID),
+            pool: *pool,
+            protocol_fee_recipient: *protocol_fee_recipient,
+            quote_account: *quote_account,
+            quote_vault: get_vault_address(&ID, pool, quote_mint).0,
+        }
+    }
+}
+
+impl ToAccountMetas for WithdrawProtocolFeesAccounts {
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(ID, false),
+            AccountMeta::new_readonly(self.log_authority, false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new_readonly(self.protocol_fee_recipient, true),
+            AccountMeta::new(self.quote_account, false),
+            AccountMeta::new(self.quote_vault, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolAuthorityAccounts {
+    pub log_authority: Pubkey,
+    pub pool: Pubkey,
+    pub signer: Pubkey,
+}
+
+impl PoolAuthorityAccounts {
+    pub fn new(pool: &Pubkey, signer: &Pubkey) -> Self {
+        Self {
+            log_authority: get_log_authority(&ID),
+            pool: *pool,
+            signer: *signer,
+        }
+    }
+}
+
+impl ToAccountMetas for PoolAuthorityAccounts {
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(ID, false),
+            AccountMeta::new_readonly(self.log_authority, false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new_readonly(self.signer, true),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        amm::Side,
+        instructions::{swap, SwapParams, SwapType},
+    };
+
+    #[test]
+    fn test_swap_accounts_match_swap_builder() {
+        let keys = [(); 6].map(|_| Pubkey::new_unique());
+        let [pool, trader, base_mint, quote_mint, base_account, quote_account] = keys;
+        let ix = swap(
+            &pool,
+            &trader,
+            &base_mint,
+            &quote_mint,
+            &base_account,
+            &quote_account,
+            SwapParams {
+                side: Side::Buy,
+                swap_type: SwapType::ExactIn {
+                    amount_in: 1,
+                    min_amount_out: 0,
+                },
+                deadline_slot: None,
+            },
+        );
+        let accounts = SwapAccounts::new(
+            &pool,
+            &trader,
+            &base_mint,
+            &quote_mint,
+            &base_account,
+            &quote_account,
+        );
+        assert_eq!(accounts.to_account_metas(), ix.accounts);
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new_readonly(ID, false),
+                AccountMeta::new_readonly(get_log_authority(&ID), false),
+                AccountMeta::new(pool, false),
+                AccountMeta::new_readonly(trader, true),
+                AccountMeta::new(base_account, false),
+                AccountMeta::new(quote_account, false),
+                AccountMeta::new(get_vault_address(&ID, &pool, &base_mint).0, false),
+                AccountMeta::new(get_vault_address(&ID, &pool, &quote_mint).0, false),
+                AccountMeta::new_readonly(spl_token::ID, false),
+            ]
+        );
+    }
+}
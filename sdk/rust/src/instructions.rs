@@ -1,5 +1,17 @@
 use crate::{
-    amm::Side, get_log_authority, get_lp_position_address, get_vault_address, spl_token, ID,
+    accounts::PoolHeader,
+    amm::{Amm, Side, BPS_BASE},
+    errors::{PlasmaError, PlasmaResult},
+    events::InitializePoolEvent,
+    instruction_accounts::{
+        BatchInitializeLpPositionsAccounts, CrankVestingAccounts, InitializeLpPositionAccounts,
+        InitializePoolAccounts, LiquidityAccounts, PoolAuthorityAccounts, ReleaseVestingAccounts,
+        RenounceLiquidityAccounts, SwapAccounts, ToAccountMetas, TransferLiquidityAccounts,
+        WithdrawProtocolFeesAccounts,
+    },
+    quote::{price_impact_bps, quote_swap_at_slot},
+    spl_token, CLOSE_TOKEN_ACCOUNT_TAG, ID, INITIALIZE_TOKEN_ACCOUNT_3_TAG, SYNC_NATIVE_TAG,
+    TOKEN_ACCOUNT_LEN,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 use num_enum::TryFromPrimitive;
@@ -7,15 +19,62 @@ use shank::ShankInstruction;
 use solana_program::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
-    system_program,
+    system_instruction,
 };
 
+pub use crate::transfer_fee::TransferFeeBps;
+
+#[cfg(test)]
+use crate::{get_log_authority, get_lp_position_address, get_vault_address};
+#[cfg(test)]
+use plasma_amm_state::test_utils::new_pool;
+
 pub const SWAP_DISCRIMINATOR: u8 = 0;
 pub const ADD_LIQUIDITY_DISCRIMINATOR: u8 = 1;
 pub const REMOVE_LIQUIDITY_DISCRIMINATOR: u8 = 2;
+pub const RENOUNCE_LIQUIDITY_DISCRIMINATOR: u8 = 3;
+pub const WITHDRAW_LP_FEES_DISCRIMINATOR: u8 = 4;
 pub const INITIALIZE_LP_POSITION_DISCRIMINATOR: u8 = 5;
 pub const INITIALIZE_POOL_DISCRIMINATOR: u8 = 6;
+pub const WITHDRAW_PROTOCOL_FEES_DISCRIMINATOR: u8 = 7;
+pub const LOG_DISCRIMINATOR: u8 = 8;
 pub const TRANSFER_LIQUIDITY_DISCRIMINATOR: u8 = 9;
+pub const SET_LP_FEE_DISCRIMINATOR: u8 = 10;
+pub const BATCH_INITIALIZE_LP_POSITIONS_DISCRIMINATOR: u8 = 11;
+pub const CRANK_VESTING_DISCRIMINATOR: u8 = 12;
+pub const REINVEST_PROTOCOL_FEES_DISCRIMINATOR: u8 = 13;
+pub const RELEASE_VESTING_DISCRIMINATOR: u8 = 14;
+
+/// Every instruction discriminator along with the name of its instruction
+pub const DISCRIMINATORS: [(u8, &str); 15] = [
+    (SWAP_DISCRIMINATOR, "Swap"),
+    (ADD_LIQUIDITY_DISCRIMINATOR, "AddLiquidity"),
+    (REMOVE_LIQUIDITY_DISCRIMINATOR, "RemoveLiquidity"),
+    (RENOUNCE_LIQUIDITY_DISCRIMINATOR, "RenounceLiquidity"),
+    (WITHDRAW_LP_FEES_DISCRIMINATOR, "WithdrawLpFees"),
+    (INITIALIZE_LP_POSITION_DISCRIMINATOR, "InitializeLpPosition"),
+    (INITIALIZE_POOL_DISCRIMINATOR, "InitializePool"),
+    (WITHDRAW_PROTOCOL_FEES_DISCRIMINATOR, "WithdrawProtocolFees"),
+    (LOG_DISCRIMINATOR, "Log"),
+    (TRANSFER_LIQUIDITY_DISCRIMINATOR, "TransferLiquidity"),
+    (SET_LP_FEE_DISCRIMINATOR, "SetLpFee"),
+    (
+        BATCH_INITIALIZE_LP_POSITIONS_DISCRIMINATOR,
+        "BatchInitializeLpPositions",
+    ),
+    (CRANK_VESTING_DISCRIMINATOR, "CrankVesting"),
+    (REINVEST_PROTOCOL_FEES_DISCRIMINATOR, "ReinvestProtocolFees"),
+    (RELEASE_VESTING_DISCRIMINATOR, "ReleaseVesting"),
+];
+
+/// Returns the name of the instruction with the given discriminator, or `None` if no Plasma
+/// instruction uses it
+pub fn instruction_name(discriminator: u8) -> Option<&'static str> {
+    DISCRIMINATORS
+        .iter()
+        .find(|(d, _)| *d == discriminator)
+        .map(|(_, name)| *name)
+}
 
 #[repr(u8)]
 #[derive(TryFromPrimitive, Debug, Copy, Clone, ShankInstruction, PartialEq, Eq, BorshDeserialize, BorshSerialize)]
@@ -123,12 +182,276 @@ pub enum PlasmaInstruction {
     #[account(4, writable, name = "src_lp_position")]
     #[account(5, writable, name = "dst_lp_position")]
     TransferLiquidity = 9,
+
+    /// Update the LP fee of the pool
+    #[account(0, name = "plasma_program", desc = "Plasma program")]
+    #[account(1, name = "log_authority", desc = "Plasma log authority")]
+    #[account(2, writable, name = "pool", desc = "This account holds the pool state")]
+    #[account(3, signer, name = "pool_authority", desc = "The authority of the pool")]
+    SetLpFee = 10,
+
+    /// Initialize up to 8 LP positions at once, with the payer funding all of them.
+    /// Each LP position is passed after the system program, preceded by its owner
+    #[account(0, name = "plasma_program", desc = "Plasma program")]
+    #[account(1, name = "log_authority", desc = "Plasma log authority")]
+    #[account(2, writable, name = "pool", desc = "This account holds the pool state")]
+    #[account(3, writable, signer, name = "payer")]
+    #[account(4, name = "system_program", desc = "System program")]
+    BatchInitializeLpPositions = 11,
+
+    /// Vest the pending shares of an LP position. This instruction is permissionless
+    #[account(0, name = "plasma_program", desc = "Plasma program")]
+    #[account(1, name = "log_authority", desc = "Plasma log authority")]
+    #[account(2, writable, name = "pool", desc = "This account holds the pool state")]
+    #[account(3, signer, name = "cranker")]
+    #[account(4, name = "lp_position_owner")]
+    #[account(5, writable, name = "lp_position")]
+    CrankVesting = 12,
+
+    /// Donate the signer's accrued protocol fees to the pool's quote reserves without minting LP shares
+    #[account(0, name = "plasma_program", desc = "Plasma program")]
+    #[account(1, name = "log_authority", desc = "Plasma log authority")]
+    #[account(2, writable, name = "pool", desc = "This account holds the pool state")]
+    #[account(3, signer, name = "protocol_fee_recipient", desc = "Recipient of protocol fees")]
+    ReinvestProtocolFees = 13,
+
+    /// Immediately vest the pending shares of an LP position, e.g. when migrating positions
+    #[account(0, name = "plasma_program", desc = "Plasma program")]
+    #[account(1, name = "log_authority", desc = "Plasma log authority")]
+    #[account(2, writable, name = "pool", desc = "This account holds the pool state")]
+    #[account(3, signer, name = "pool_authority", desc = "The authority of the pool")]
+    #[account(4, name = "lp_position_owner")]
+    #[account(5, writable, name = "lp_position")]
+    ReleaseVesting = 14,
 }
 
 impl PlasmaInstruction {
     pub fn to_vec(&self) -> Vec<u8> {
         vec![*self as u8]
     }
+
+    /// Indices of the accounts marked as writable in the account annotations of this instruction.
+    ///
+    /// For `BatchInitializeLpPositions` this excludes the variable number of LP positions passed
+    /// after the annotated accounts, see `writable_accounts`.
+    pub fn writable_account_indices(&self) -> &'static [usize] {
+        match self {
+            PlasmaInstruction::Swap => &[2, 4, 5, 6, 7],
+            PlasmaInstruction::AddLiquidity => &[2, 4, 5, 6, 7, 8],
+            PlasmaInstruction::RemoveLiquidity => &[2, 4, 5, 6, 7, 8],
+            PlasmaInstruction::RenounceLiquidity => &[2, 4],
+            PlasmaInstruction::WithdrawLpFees => &[2, 5, 6, 7],
+            PlasmaInstruction::InitializeLpPosition => &[2, 3, 5],
+            PlasmaInstruction::InitializePool => &[2, 3, 6, 7],
+            PlasmaInstruction::WithdrawProtocolFees => &[2, 4, 5],
+            PlasmaInstruction::Log => &[],
+            PlasmaInstruction::TransferLiquidity => &[2, 4, 5],
+            PlasmaInstruction::SetLpFee => &[2],
+            PlasmaInstruction::BatchInitializeLpPositions => &[2, 3],
+            PlasmaInstruction::CrankVesting => &[2, 5],
+            PlasmaInstruction::ReinvestProtocolFees => &[2],
+            PlasmaInstruction::ReleaseVesting => &[2, 5],
+        }
+    }
+
+    /// Indices of the accounts marked as signers in the account annotations of this instruction
+    pub fn signer_account_indices(&self) -> &'static [usize] {
+        match self {
+            PlasmaInstruction::Log => &[0],
+            _ => &[3],
+        }
+    }
+
+    /// Number of accounts in the account annotations of this instruction.
+    ///
+    /// `BatchInitializeLpPositions` takes an owner and LP position pair per position after these.
+    pub fn annotated_account_count(&self) -> usize {
+        match self {
+            PlasmaInstruction::Swap => 9,
+            PlasmaInstruction::AddLiquidity => 10,
+            PlasmaInstruction::RemoveLiquidity => 10,
+            PlasmaInstruction::RenounceLiquidity => 5,
+            PlasmaInstruction::WithdrawLpFees => 9,
+            PlasmaInstruction::InitializeLpPosition => 7,
+            PlasmaInstruction::InitializePool => 10,
+            PlasmaInstruction::WithdrawProtocolFees => 7,
+            PlasmaInstruction::Log => 1,
+            PlasmaInstruction::TransferLiquidity => 6,
+            PlasmaInstruction::SetLpFee => 4,
+            PlasmaInstruction::BatchInitializeLpPositions => 5,
+            PlasmaInstruction::CrankVesting => 6,
+            PlasmaInstruction::ReinvestProtocolFees => 4,
+            PlasmaInstruction::ReleaseVesting => 6,
+        }
+    }
+
+    /// Returns the writable accounts of this instruction given its ordered account keys,
+    /// e.g. the keys of an instruction created by one of the builders in this module.
+    pub fn writable_accounts(&self, account_keys: &[Pubkey]) -> Vec<Pubkey> {
+        let mut writable_accounts = self
+            .writable_account_indices()
+            .iter()
+            .filter_map(|&i| account_keys.get(i).copied())
+            .collect::<Vec<_>>();
+        if *self == PlasmaInstruction::BatchInitializeLpPositions {
+            // Every other remaining account is an LP position, the rest are their owners
+            writable_accounts.extend(account_keys.iter().skip(6).step_by(2));
+        }
+        writable_accounts
+    }
+
+    /// Decodes raw Plasma instruction data into the instruction and its parameters.
+    ///
+    /// Payloads that are too short return `MissingExpectedArgument` and payloads with trailing
+    /// bytes return `UnexpectedArgument`. Instructions without parameters ignore any payload,
+    /// matching the program.
+    pub fn decode(data: &[u8]) -> PlasmaResult<DecodedPlasmaInstruction> {
+        let (tag, payload) = data
+            .split_first()
+            .ok_or(PlasmaError::MissingExpectedArgument)?;
+        let instruction =
+            PlasmaInstruction::try_from(*tag).map_err(|_| PlasmaError::UnexpectedArgument)?;
+
+        Ok(match instruction {
+            PlasmaInstruction::Swap => DecodedPlasmaInstruction::Swap(SwapParams::decode(payload)?),
+            PlasmaInstruction::AddLiquidity => {
+                DecodedPlasmaInstruction::AddLiquidity(AddLiquidityParams::decode(payload)?)
+            }
+            PlasmaInstruction::RemoveLiquidity => {
+                // The share count is encoded as raw little-endian bytes rather than Borsh
+                let shares = match payload.len() {
+                    len if len < 8 => return Err(PlasmaError::MissingExpectedArgument),
+                    8 => u64::from_le_bytes(payload.try_into().unwrap()),
+                    _ => return Err(PlasmaError::UnexpectedArgument),
+                };
+                DecodedPlasmaInstruction::RemoveLiquidity { shares }
+            }
+            PlasmaInstruction::RenounceLiquidity => DecodedPlasmaInstruction::RenounceLiquidity {
+                allow_fee_withdrawal: decode_borsh(payload)?,
+            },
+            PlasmaInstruction::WithdrawLpFees => DecodedPlasmaInstruction::WithdrawLpFees,
+            PlasmaInstruction::InitializeLpPosition => {
+                DecodedPlasmaInstruction::InitializeLpPosition
+            }
+            PlasmaInstruction::InitializePool => {
+                DecodedPlasmaInstruction::InitializePool(InitializePoolParams::decode(payload)?)
+            }
+            PlasmaInstruction::WithdrawProtocolFees => {
+                DecodedPlasmaInstruction::WithdrawProtocolFees
+            }
+            PlasmaInstruction::Log => DecodedPlasmaInstruction::Log,
+            PlasmaInstruction::TransferLiquidity => DecodedPlasmaInstruction::TransferLiquidity,
+            PlasmaInstruction::SetLpFee => {
+                DecodedPlasmaInstruction::SetLpFee(decode_borsh(payload)?)
+            }
+            PlasmaInstruction::BatchInitializeLpPositions => {
+                DecodedPlasmaInstruction::BatchInitializeLpPositions
+            }
+            PlasmaInstruction::CrankVesting => DecodedPlasmaInstruction::CrankVesting,
+            PlasmaInstruction::ReinvestProtocolFees => {
+                DecodedPlasmaInstruction::ReinvestProtocolFees
+            }
+            PlasmaInstruction::ReleaseVesting => DecodedPlasmaInstruction::ReleaseVesting,
+        })
+    }
+}
+
+/// Renders a Plasma instruction as a human-readable string of its decoded parameters and the
+/// number of accounts it references, e.g. for debugging captured transactions.
+///
+/// Returns a description of the problem if `ix` is not a valid Plasma instruction.
+pub fn describe_instruction(ix: &Instruction) -> String {
+    if ix.program_id != ID {
+        return format!("Not a Plasma instruction (program id {})", ix.program_id);
+    }
+    match PlasmaInstruction::decode(&ix.data) {
+        Ok(decoded) => format!("{:?} ({} accounts)", decoded, ix.accounts.len()),
+        Err(err) => format!("Invalid Plasma instruction: {}", err),
+    }
+}
+
+/// Checks that the accounts of a manually assembled Plasma instruction match the account
+/// annotations of the instruction its discriminator selects: the number of accounts and, for
+/// each account in order, that it is a signer and writable if the annotations require it. For
+/// `BatchInitializeLpPositions`, every position must be passed as an owner followed by its
+/// writable LP position.
+///
+/// Accounts may carry more privileges than annotated, e.g. a signer that is also writable
+/// because it pays for the transaction, since the program accepts them.
+///
+/// Returns `UnexpectedArgument` if `ix` is not a Plasma instruction, `InvalidAccountCount` if
+/// the number of accounts is wrong and `InvalidAccountFlags` for the first account whose flags
+/// do not match. Account keys are not checked.
+pub fn validate_instruction_accounts(ix: &Instruction) -> PlasmaResult<()> {
+    if ix.program_id != ID {
+        return Err(PlasmaError::UnexpectedArgument);
+    }
+    let tag = ix
+        .data
+        .first()
+        .ok_or(PlasmaError::MissingExpectedArgument)?;
+    let instruction =
+        PlasmaInstruction::try_from(*tag).map_err(|_| PlasmaError::UnexpectedArgument)?;
+
+    let expected = instruction.annotated_account_count();
+    let actual = ix.accounts.len();
+    let count_matches = if instruction == PlasmaInstruction::BatchInitializeLpPositions {
+        actual >= expected && (actual - expected).is_multiple_of(2)
+    } else {
+        actual == expected
+    };
+    if !count_matches {
+        return Err(PlasmaError::InvalidAccountCount { expected, actual });
+    }
+
+    let writable_indices = instruction.writable_account_indices();
+    for (index, account) in ix.accounts.iter().enumerate() {
+        let expected_signer = instruction.signer_account_indices().contains(&index);
+        let expected_writable = if index < expected {
+            writable_indices.contains(&index)
+        } else {
+            // Owners and LP positions alternate after the annotated accounts
+            !(index - expected).is_multiple_of(2)
+        };
+        if (expected_signer && !account.is_signer) || (expected_writable && !account.is_writable) {
+            return Err(PlasmaError::InvalidAccountFlags {
+                index,
+                expected_signer,
+                expected_writable,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// A Plasma instruction along with its decoded parameters
+#[derive(Clone, Copy, Debug)]
+pub enum DecodedPlasmaInstruction {
+    Swap(SwapParams),
+    AddLiquidity(AddLiquidityParams),
+    RemoveLiquidity { shares: u64 },
+    RenounceLiquidity { allow_fee_withdrawal: bool },
+    WithdrawLpFees,
+    InitializeLpPosition,
+    InitializePool(InitializePoolParams),
+    WithdrawProtocolFees,
+    Log,
+    TransferLiquidity,
+    SetLpFee(SetLpFeeParams),
+    BatchInitializeLpPositions,
+    CrankVesting,
+    ReinvestProtocolFees,
+    ReleaseVesting,
+}
+
+fn decode_borsh<T: BorshDeserialize>(mut payload: &[u8]) -> PlasmaResult<T> {
+    let value = T::deserialize(&mut payload).map_err(|_| PlasmaError::MissingExpectedArgument)?;
+    if !payload.is_empty() {
+        return Err(PlasmaError::UnexpectedArgument);
+    }
+    Ok(value)
 }
 
 #[derive(Clone, Copy, Debug, BorshDeserialize, BorshSerialize)]
@@ -142,6 +465,68 @@ pub enum SwapType {
 pub struct SwapParams {
     pub side: Side,
     pub swap_type: SwapType,
+    /// The program rejects the swap with `DeadlineExceeded` if it lands after this slot
+    pub deadline_slot: Option<u64>,
+}
+
+impl SwapParams {
+    /// Version tag of the wire format produced by `to_versioned_bytes`. Version 2 added
+    /// `deadline_slot`; version 1 payloads end after `swap_type`.
+    pub const WIRE_VERSION: u8 = 2;
+
+    /// Serializes the params as a version byte followed by the Borsh encoding
+    pub fn to_versioned_bytes(&self) -> Vec<u8> {
+        [vec![Self::WIRE_VERSION], self.try_to_vec().unwrap()].concat()
+    }
+
+    /// Deserializes params written by `to_versioned_bytes`, rejecting unknown versions.
+    /// Version 1 payloads decode without a deadline.
+    pub fn from_versioned_bytes(data: &[u8]) -> PlasmaResult<SwapParams> {
+        let (version, payload) = data
+            .split_first()
+            .ok_or(PlasmaError::MissingExpectedArgument)?;
+        match *version {
+            1 => {
+                let (side, swap_type, payload) = Self::decode_side_and_swap_type(payload)?;
+                if !payload.is_empty() {
+                    return Err(PlasmaError::UnexpectedArgument);
+                }
+                Ok(SwapParams {
+                    side,
+                    swap_type,
+                    deadline_slot: None,
+                })
+            }
+            Self::WIRE_VERSION => decode_borsh(payload),
+            _ => Err(PlasmaError::UnexpectedArgument),
+        }
+    }
+
+    /// Deserializes the Borsh encoding of the params. Like the program, this accepts encodings
+    /// that omit `deadline_slot` entirely, which were written before it was added.
+    pub fn decode(payload: &[u8]) -> PlasmaResult<SwapParams> {
+        let (side, swap_type, payload) = Self::decode_side_and_swap_type(payload)?;
+        let deadline_slot = if payload.is_empty() {
+            None
+        } else {
+            decode_borsh(payload)?
+        };
+        Ok(SwapParams {
+            side,
+            swap_type,
+            deadline_slot,
+        })
+    }
+
+    fn decode_side_and_swap_type(payload: &[u8]) -> PlasmaResult<(Side, SwapType, &[u8])> {
+        let (side, mut payload) = payload
+            .split_first()
+            .ok_or(PlasmaError::MissingExpectedArgument)?;
+        let side = Side::try_from_u8(*side)?;
+        let swap_type = SwapType::deserialize(&mut payload)
+            .map_err(|_| PlasmaError::MissingExpectedArgument)?;
+        Ok((side, swap_type, payload))
+    }
 }
 
 pub fn swap(
@@ -153,36 +538,395 @@ pub fn swap(
     quote_account_key: &Pubkey,
     params: SwapParams,
 ) -> Instruction {
-    let log_authority = get_log_authority(&ID);
-    let base_vault_key = get_vault_address(&ID, pool_key, base_mint).0;
-    let quote_vault_key = get_vault_address(&ID, pool_key, quote_mint).0;
+    let accounts = SwapAccounts::new(
+        pool_key,
+        trader,
+        base_mint,
+        quote_mint,
+        base_account_key,
+        quote_account_key,
+    );
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(),
+        data: [vec![SWAP_DISCRIMINATOR], params.try_to_vec().unwrap()].concat(),
+    }
+}
 
+/// Same as `swap`, but takes the vault and log authority keys directly instead of deriving them
+#[allow(clippy::too_many_arguments)]
+pub fn swap_prederived(
+    pool_key: &Pubkey,
+    trader: &Pubkey,
+    base_account_key: &Pubkey,
+    quote_account_key: &Pubkey,
+    base_vault_key: &Pubkey,
+    quote_vault_key: &Pubkey,
+    log_authority: &Pubkey,
+    params: SwapParams,
+) -> Instruction {
+    let accounts = SwapAccounts {
+        log_authority: *log_authority,
+        pool: *pool_key,
+        trader: *trader,
+        base_account: *base_account_key,
+        quote_account: *quote_account_key,
+        base_vault: *base_vault_key,
+        quote_vault: *quote_vault_key,
+    };
     Instruction {
         program_id: ID,
-        accounts: vec![
-            AccountMeta::new_readonly(ID, false),
-            AccountMeta::new_readonly(log_authority, false),
-            AccountMeta::new(*pool_key, false),
-            AccountMeta::new_readonly(*trader, true),
-            AccountMeta::new(*base_account_key, false),
-            AccountMeta::new(*quote_account_key, false),
-            AccountMeta::new(base_vault_key, false),
-            AccountMeta::new(quote_vault_key, false),
-            AccountMeta::new_readonly(spl_token::ID, false),
-        ],
+        accounts: accounts.to_account_metas(),
         data: [vec![SWAP_DISCRIMINATOR], params.try_to_vec().unwrap()].concat(),
     }
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct SmartSwapParams {
+    pub swap_params: SwapParams,
+    /// Slot at which the swap is quoted, this determines the pool snapshot
+    pub slot: u64,
+    /// If set, the instruction is not built when the quoted price impact exceeds this value
+    pub max_price_impact_bps: Option<u16>,
+}
+
+/// Quotes the swap against the current pool state before building the instruction, and
+/// returns `PriceImpactTooHigh` if the price impact of the swap exceeds `max_price_impact_bps`.
+pub fn smart_swap(
+    pool_key: &Pubkey,
+    pool_header: &PoolHeader,
+    amm: &Amm,
+    trader: &Pubkey,
+    base_account_key: &Pubkey,
+    quote_account_key: &Pubkey,
+    params: SmartSwapParams,
+) -> PlasmaResult<Instruction> {
+    let SmartSwapParams {
+        swap_params,
+        slot,
+        max_price_impact_bps,
+    } = params;
+    if let Some(max_price_impact_bps) = max_price_impact_bps {
+        let swap_result = quote_swap_at_slot(amm, slot, swap_params)?;
+        let price_impact_bps = price_impact_bps(amm, &swap_result);
+        if price_impact_bps > max_price_impact_bps as u64 {
+            return Err(PlasmaError::PriceImpactTooHigh {
+                price_impact_bps,
+                max_price_impact_bps,
+            });
+        }
+    }
+    Ok(swap(
+        pool_key,
+        trader,
+        &pool_header.base_params.mint_key,
+        &pool_header.quote_params.mint_key,
+        base_account_key,
+        quote_account_key,
+        swap_params,
+    ))
+}
+
+/// Builds an `ExactIn` swap of the trader's entire `input_balance` (quote for buys, base for
+/// sells). The minimum output is the output quoted against `amm` at its current snapshot slot,
+/// reduced by `slippage_bps`.
+///
+/// The SDK does not fetch accounts, so the caller must pass the current balance of the input
+/// token account and a recent pool state.
+#[allow(clippy::too_many_arguments)]
+pub fn swap_all(
+    pool_key: &Pubkey,
+    amm: &Amm,
+    trader: &Pubkey,
+    base_mint: &Pubkey,
+    quote_mint: &Pubkey,
+    base_account_key: &Pubkey,
+    quote_account_key: &Pubkey,
+    side: Side,
+    input_balance: u64,
+    slippage_bps: u16,
+) -> PlasmaResult<Instruction> {
+    let swap_type = |min_amount_out| SwapType::ExactIn {
+        amount_in: input_balance,
+        min_amount_out,
+    };
+    let swap_result = quote_swap_at_slot(
+        amm,
+        amm.get_slot(),
+        SwapParams {
+            side,
+            swap_type: swap_type(0),
+            deadline_slot: None,
+        },
+    )?;
+    let expected_amount_out = match side {
+        Side::Buy => swap_result.base_amount_to_transfer,
+        Side::Sell => swap_result.quote_amount_to_transfer,
+    };
+    let min_amount_out = (expected_amount_out as u128
+        * BPS_BASE.saturating_sub(slippage_bps as u128)
+        / BPS_BASE) as u64;
+
+    Ok(swap(
+        pool_key,
+        trader,
+        base_mint,
+        quote_mint,
+        base_account_key,
+        quote_account_key,
+        SwapParams {
+            side,
+            swap_type: swap_type(min_amount_out),
+            deadline_slot: None,
+        },
+    ))
+}
+
+/// Builds `num_slices` `ExactIn` swaps that together spend `total_input` (quote for buys, base for
+/// sells), for executing a large order as a TWAP. Each slice is `total_input / num_slices`, and
+/// the last slice also takes the remainder. The swaps are meant to be sent in separate
+/// transactions spread out over time.
+///
+/// As with `swap_all`, each slice's minimum output is its output quoted against `amm` at its
+/// current snapshot, reduced by `slippage_bps`. Every slice is quoted against the same snapshot,
+/// so the bounds drift from the actual pool price as the earlier slices execute and the market
+/// moves. Later slices may fail their minimum, or be filled at a worse price than intended, if
+/// the schedule is not rebuilt along the way.
+///
+/// Returns `UnexpectedArgument` if `num_slices` is zero.
+#[allow(clippy::too_many_arguments)]
+pub fn twap_swap_schedule(
+    pool_key: &Pubkey,
+    amm: &Amm,
+    trader: &Pubkey,
+    base_mint: &Pubkey,
+    quote_mint: &Pubkey,
+    base_account_key: &Pubkey,
+    quote_account_key: &Pubkey,
+    side: Side,
+    total_input: u64,
+    num_slices: u64,
+    slippage_bps: u16,
+) -> PlasmaResult<Vec<Instruction>> {
+    if num_slices == 0 {
+        return Err(PlasmaError::UnexpectedArgument);
+    }
+    let slice_input = total_input / num_slices;
+    let remainder = total_input % num_slices;
+    (0..num_slices)
+        .map(|i| {
+            let input = if i == num_slices - 1 {
+                slice_input + remainder
+            } else {
+                slice_input
+            };
+            swap_all(
+                pool_key,
+                amm,
+                trader,
+                base_mint,
+                quote_mint,
+                base_account_key,
+                quote_account_key,
+                side,
+                input,
+                slippage_bps,
+            )
+        })
+        .collect()
+}
+
+/// Prefix of the seed of the temporary wrapped SOL account created by `swap_with_native_sol`
+pub const NATIVE_SOL_ACCOUNT_SEED: &str = "plasma-wsol";
+
+/// Returns the seed of the temporary wrapped SOL account for `nonce`. Transactions that are in
+/// flight at the same time for one trader must use distinct nonces, or they would try to create
+/// the same account.
+pub fn native_sol_account_seed(nonce: u64) -> String {
+    format!("{NATIVE_SOL_ACCOUNT_SEED}-{nonce}")
+}
+
+/// Returns the address of the temporary wrapped SOL account created by `swap_with_native_sol`
+/// for `trader` and `nonce`
+pub fn get_native_sol_account_address(trader: &Pubkey, nonce: u64) -> Pubkey {
+    // The seed is at most 32 bytes, so this cannot fail
+    Pubkey::create_with_seed(trader, &native_sol_account_seed(nonce), &spl_token::ID).unwrap()
+}
+
+/// Builds the instructions to swap `sol_amount` native SOL into a pool where one side is wrapped
+/// SOL, in one transaction:
+///
+/// 1. Create a temporary wrapped SOL account owned by the trader, at the address derived from the
+///    trader's key and `nonce` (see `get_native_sol_account_address`), funded with
+///    `rent_exempt_lamports`
+/// 2. Fund it with `sol_amount` lamports and sync its token balance
+/// 3. Swap the entire amount with `swap_all`, sending the output to `token_account_key`
+/// 4. Close the account, returning its rent and any unspent SOL to the trader
+///
+/// `rent_exempt_lamports` must be the cluster's rent-exempt minimum for `TOKEN_ACCOUNT_LEN` bytes,
+/// e.g. from `getMinimumBalanceForRentExemption`, and is returned to the trader on close.
+///
+/// Wrapped SOL must be the input of the swap, i.e. the quote mint for buys and the base mint for
+/// sells. Returns `UnexpectedArgument` otherwise.
+#[allow(clippy::too_many_arguments)]
+pub fn swap_with_native_sol(
+    pool_key: &Pubkey,
+    amm: &Amm,
+    trader: &Pubkey,
+    base_mint: &Pubkey,
+    quote_mint: &Pubkey,
+    token_account_key: &Pubkey,
+    sol_amount: u64,
+    side: Side,
+    slippage_bps: u16,
+    nonce: u64,
+    rent_exempt_lamports: u64,
+) -> PlasmaResult<Vec<Instruction>> {
+    let input_mint = match side {
+        Side::Buy => quote_mint,
+        Side::Sell => base_mint,
+    };
+    if *input_mint != spl_token::native_mint::ID {
+        return Err(PlasmaError::UnexpectedArgument);
+    }
+
+    let seed = native_sol_account_seed(nonce);
+    let wsol_account_key = get_native_sol_account_address(trader, nonce);
+    let (base_account_key, quote_account_key) = match side {
+        Side::Buy => (token_account_key, &wsol_account_key),
+        Side::Sell => (&wsol_account_key, token_account_key),
+    };
+    let swap_ix = swap_all(
+        pool_key,
+        amm,
+        trader,
+        base_mint,
+        quote_mint,
+        base_account_key,
+        quote_account_key,
+        side,
+        sol_amount,
+        slippage_bps,
+    )?;
+
+    Ok(vec![
+        system_instruction::create_account_with_seed(
+            trader,
+            &wsol_account_key,
+            trader,
+            &seed,
+            rent_exempt_lamports,
+            TOKEN_ACCOUNT_LEN,
+            &spl_token::ID,
+        ),
+        Instruction {
+            program_id: spl_token::ID,
+            accounts: vec![
+                AccountMeta::new(wsol_account_key, false),
+                AccountMeta::new_readonly(spl_token::native_mint::ID, false),
+            ],
+            data: [
+                vec![INITIALIZE_TOKEN_ACCOUNT_3_TAG],
+                trader.to_bytes().to_vec(),
+            ]
+            .concat(),
+        },
+        system_instruction::transfer(trader, &wsol_account_key, sol_amount),
+        Instruction {
+            program_id: spl_token::ID,
+            accounts: vec![AccountMeta::new(wsol_account_key, false)],
+            data: vec![SYNC_NATIVE_TAG],
+        },
+        swap_ix,
+        Instruction {
+            program_id: spl_token::ID,
+            accounts: vec![
+                AccountMeta::new(wsol_account_key, false),
+                AccountMeta::new(*trader, false),
+                AccountMeta::new_readonly(*trader, true),
+            ],
+            data: vec![CLOSE_TOKEN_ACCOUNT_TAG],
+        },
+    ])
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, BorshDeserialize, BorshSerialize)]
 pub struct InitializePoolParams {
     pub lp_fee_in_bps: u64,
+    /// Whole percentage of the LP fee allocated to the protocol. Ignored if
+    /// `protocol_fee_allocation_in_bps` is set
     pub protocol_fee_allocation_in_pct: u64,
     pub fee_recipients_params: [ProtocolFeeRecipientParams; 3],
     /// This is the number of slots that the LP shares will be vested over
     /// If this value is not a multiple of the leader slot window, it will be rounded down
     pub num_slots_to_vest_lp_shares: Option<u64>,
+    /// Deposits with a quote amount below this value are rejected. Defaults to zero (no minimum)
+    pub min_quote_deposit: Option<u64>,
+    /// Deposits that would push the pool's quote-denominated TVL above this cap are rejected. Defaults to zero (no cap)
+    pub max_quote_tvl: Option<u64>,
+    /// Share of the LP fee allocated to the protocol in basis points, for allocations that are not
+    /// a whole percentage. Takes precedence over `protocol_fee_allocation_in_pct`
+    pub protocol_fee_allocation_in_bps: Option<u64>,
+}
+
+impl InitializePoolParams {
+    /// Deserializes the Borsh encoding of the params. Like the program, this accepts encodings
+    /// that end after `num_slots_to_vest_lp_shares`, which were written before the later fields
+    /// were added.
+    pub fn decode(mut payload: &[u8]) -> PlasmaResult<InitializePoolParams> {
+        let lp_fee_in_bps =
+            u64::deserialize(&mut payload).map_err(|_| PlasmaError::MissingExpectedArgument)?;
+        let protocol_fee_allocation_in_pct =
+            u64::deserialize(&mut payload).map_err(|_| PlasmaError::MissingExpectedArgument)?;
+        let fee_recipients_params = <[ProtocolFeeRecipientParams; 3]>::deserialize(&mut payload)
+            .map_err(|_| PlasmaError::MissingExpectedArgument)?;
+        let num_slots_to_vest_lp_shares = Option::<u64>::deserialize(&mut payload)
+            .map_err(|_| PlasmaError::MissingExpectedArgument)?;
+        let min_quote_deposit = if payload.is_empty() {
+            None
+        } else {
+            Option::<u64>::deserialize(&mut payload)
+                .map_err(|_| PlasmaError::MissingExpectedArgument)?
+        };
+        let max_quote_tvl = if payload.is_empty() {
+            None
+        } else {
+            Option::<u64>::deserialize(&mut payload)
+                .map_err(|_| PlasmaError::MissingExpectedArgument)?
+        };
+        let protocol_fee_allocation_in_bps = if payload.is_empty() {
+            None
+        } else {
+            decode_borsh(payload)?
+        };
+        Ok(InitializePoolParams {
+            lp_fee_in_bps,
+            protocol_fee_allocation_in_pct,
+            fee_recipients_params,
+            num_slots_to_vest_lp_shares,
+            min_quote_deposit,
+            max_quote_tvl,
+            protocol_fee_allocation_in_bps,
+        })
+    }
+
+    /// Share of the LP fee allocated to the protocol in basis points, as the program reads it
+    pub fn effective_protocol_fee_allocation_in_bps(&self) -> u64 {
+        self.protocol_fee_allocation_in_bps
+            .unwrap_or(self.protocol_fee_allocation_in_pct * 100)
+    }
+
+    /// Returns the event the program emits when a pool is created with these params
+    pub fn expected_event(&self) -> InitializePoolEvent {
+        let protocol_fee_in_bps = self.effective_protocol_fee_allocation_in_bps();
+        InitializePoolEvent {
+            lp_fee_in_bps: self.lp_fee_in_bps,
+            protocol_fee_in_pct: protocol_fee_in_bps / 100,
+            fee_recipient_params: self.fee_recipients_params,
+            protocol_fee_in_bps,
+        }
+    }
 }
 
 pub fn initialize_pool(
@@ -192,24 +936,10 @@ pub fn initialize_pool(
     quote_mint: &Pubkey,
     params: InitializePoolParams,
 ) -> Instruction {
-    let base_vault = get_vault_address(&ID, pool_key, base_mint).0;
-    let quote_vault = get_vault_address(&ID, pool_key, quote_mint).0;
-    let log_authority = get_log_authority(&ID);
-
     Instruction {
         program_id: crate::ID,
-        accounts: vec![
-            AccountMeta::new_readonly(ID, false),
-            AccountMeta::new_readonly(log_authority, false),
-            AccountMeta::new(*pool_key, false),
-            AccountMeta::new(*pool_creator, true),
-            AccountMeta::new_readonly(*base_mint, false),
-            AccountMeta::new_readonly(*quote_mint, false),
-            AccountMeta::new(base_vault, false),
-            AccountMeta::new(quote_vault, false),
-            AccountMeta::new_readonly(system_program::ID, false),
-            AccountMeta::new_readonly(spl_token::ID, false),
-        ],
+        accounts: InitializePoolAccounts::new(pool_key, pool_creator, base_mint, quote_mint)
+            .to_account_metas(),
         data: [
             vec![INITIALIZE_POOL_DISCRIMINATOR],
             params.try_to_vec().unwrap(),
@@ -230,29 +960,110 @@ pub fn initialize_lp_position(
     payer: &Pubkey,
     lp_position_owner: &Pubkey,
 ) -> Instruction {
-    let log_authority = get_log_authority(&ID);
-    let (lp_position_key, _) = get_lp_position_address(&ID, pool_key, lp_position_owner);
     Instruction {
         program_id: ID,
-        accounts: vec![
-            AccountMeta::new_readonly(ID, false),
-            AccountMeta::new_readonly(log_authority, false),
-            AccountMeta::new(*pool_key, false),
-            AccountMeta::new(*payer, true),
-            AccountMeta::new_readonly(*lp_position_owner, false),
-            AccountMeta::new(lp_position_key, false),
-            AccountMeta::new_readonly(system_program::ID, false),
-        ],
+        accounts: InitializeLpPositionAccounts::new(pool_key, payer, lp_position_owner)
+            .to_account_metas(),
         data: vec![INITIALIZE_LP_POSITION_DISCRIMINATOR],
     }
 }
 
+/// The maximum number of LP positions that can be created in a single `BatchInitializeLpPositions` instruction
+pub const MAX_LP_POSITIONS_PER_BATCH: usize = 8;
+
+/// Initializes the LP positions of all `owners` in one instruction, with `payer` funding each of them.
+/// Returns `UnexpectedArgument` if `owners` is empty or holds more than `MAX_LP_POSITIONS_PER_BATCH`
+/// keys, which the program would reject.
+pub fn batch_initialize_lp_positions(
+    pool_key: &Pubkey,
+    payer: &Pubkey,
+    owners: &[Pubkey],
+) -> PlasmaResult<Instruction> {
+    if owners.is_empty() || owners.len() > MAX_LP_POSITIONS_PER_BATCH {
+        return Err(PlasmaError::UnexpectedArgument);
+    }
+    Ok(Instruction {
+        program_id: ID,
+        accounts: BatchInitializeLpPositionsAccounts::new(pool_key, payer, owners)
+            .to_account_metas(),
+        data: vec![BATCH_INITIALIZE_LP_POSITIONS_DISCRIMINATOR],
+    })
+}
+
+/// Vests the pending shares of `lp_position_owner`'s LP position. Any account can crank vesting.
+pub fn crank_vesting(
+    pool_key: &Pubkey,
+    cranker: &Pubkey,
+    lp_position_owner: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: CrankVestingAccounts::new(pool_key, cranker, lp_position_owner)
+            .to_account_metas(),
+        data: vec![CRANK_VESTING_DISCRIMINATOR],
+    }
+}
+
+/// Immediately vests the pending shares of `lp_position_owner`'s LP position, e.g. when
+/// migrating positions. Must be signed by the pool authority, and fails if `lp_position_owner` is
+/// the pool authority itself. This bypasses the vesting window, so it relies on the pool authority
+/// not releasing positions that add and remove liquidity around a swap.
+pub fn release_vesting(
+    pool_key: &Pubkey,
+    pool_authority: &Pubkey,
+    lp_position_owner: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: ReleaseVestingAccounts::new(pool_key, pool_authority, lp_position_owner)
+            .to_account_metas(),
+        data: vec![RELEASE_VESTING_DISCRIMINATOR],
+    }
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, BorshDeserialize, BorshSerialize)]
 pub struct AddLiquidityParams {
     pub desired_base_amount_in: u64,
     pub desired_quote_amount_in: u64,
     pub initial_lp_shares: Option<u64>,
+    /// Transfer fees the depositor expects the base and quote mints to charge. Pools only hold
+    /// SPL Token mints, which charge no transfer fees, so the program rejects any non-zero fee.
+    pub expected_transfer_fee_bps: Option<TransferFeeBps>,
+}
+
+impl AddLiquidityParams {
+    /// Sets the transfer fees, in basis points, that the base and quote mints charge. The program
+    /// currently rejects deposits with non-zero fees.
+    pub fn with_expected_transfer_fee_bps(mut self, base_fee_bps: u16, quote_fee_bps: u16) -> Self {
+        self.expected_transfer_fee_bps = Some(TransferFeeBps {
+            base: base_fee_bps,
+            quote: quote_fee_bps,
+        });
+        self
+    }
+
+    /// Deserializes the Borsh encoding of the params. Like the program, this accepts encodings
+    /// that omit `expected_transfer_fee_bps` entirely, which were written before it was added.
+    pub fn decode(mut payload: &[u8]) -> PlasmaResult<AddLiquidityParams> {
+        let desired_base_amount_in =
+            u64::deserialize(&mut payload).map_err(|_| PlasmaError::MissingExpectedArgument)?;
+        let desired_quote_amount_in =
+            u64::deserialize(&mut payload).map_err(|_| PlasmaError::MissingExpectedArgument)?;
+        let initial_lp_shares = Option::<u64>::deserialize(&mut payload)
+            .map_err(|_| PlasmaError::MissingExpectedArgument)?;
+        let expected_transfer_fee_bps = if payload.is_empty() {
+            None
+        } else {
+            decode_borsh(payload)?
+        };
+        Ok(AddLiquidityParams {
+            desired_base_amount_in,
+            desired_quote_amount_in,
+            initial_lp_shares,
+            expected_transfer_fee_bps,
+        })
+    }
 }
 
 pub fn add_liquidity(
@@ -264,26 +1075,52 @@ pub fn add_liquidity(
     quote_mint_account_key: &Pubkey,
     params: AddLiquidityParams,
 ) -> Instruction {
-    let log_authority = get_log_authority(&ID);
-    let (lp_position_key, _) = get_lp_position_address(&ID, pool_key, trader);
-
-    let (base_vault_key, _) = get_vault_address(&ID, pool_key, base_mint);
-    let (quote_vault_key, _) = get_vault_address(&ID, pool_key, quote_mint);
+    let accounts = LiquidityAccounts::new(
+        pool_key,
+        trader,
+        base_mint,
+        quote_mint,
+        base_mint_account_key,
+        quote_mint_account_key,
+    );
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(),
+        data: [
+            vec![ADD_LIQUIDITY_DISCRIMINATOR],
+            params.try_to_vec().unwrap(),
+        ]
+        .concat(),
+    }
+}
 
+/// Same as `add_liquidity`, but takes the LP position, vault and log authority keys directly
+/// instead of deriving them
+#[allow(clippy::too_many_arguments)]
+pub fn add_liquidity_prederived(
+    pool_key: &Pubkey,
+    trader: &Pubkey,
+    lp_position_key: &Pubkey,
+    base_account_key: &Pubkey,
+    quote_account_key: &Pubkey,
+    base_vault_key: &Pubkey,
+    quote_vault_key: &Pubkey,
+    log_authority: &Pubkey,
+    params: AddLiquidityParams,
+) -> Instruction {
+    let accounts = LiquidityAccounts {
+        log_authority: *log_authority,
+        pool: *pool_key,
+        trader: *trader,
+        lp_position: *lp_position_key,
+        base_account: *base_account_key,
+        quote_account: *quote_account_key,
+        base_vault: *base_vault_key,
+        quote_vault: *quote_vault_key,
+    };
     Instruction {
         program_id: ID,
-        accounts: vec![
-            AccountMeta::new_readonly(ID, false),
-            AccountMeta::new_readonly(log_authority, false),
-            AccountMeta::new(*pool_key, false),
-            AccountMeta::new_readonly(*trader, true),
-            AccountMeta::new(lp_position_key, false),
-            AccountMeta::new(*base_mint_account_key, false),
-            AccountMeta::new(*quote_mint_account_key, false),
-            AccountMeta::new(base_vault_key, false),
-            AccountMeta::new(quote_vault_key, false),
-            AccountMeta::new_readonly(spl_token::ID, false),
-        ],
+        accounts: accounts.to_account_metas(),
         data: [
             vec![ADD_LIQUIDITY_DISCRIMINATOR],
             params.try_to_vec().unwrap(),
@@ -293,24 +1130,47 @@ pub fn add_liquidity(
 }
 
 pub fn transfer_liquidity(pool_key: &Pubkey, src: &Pubkey, dst: &Pubkey) -> Instruction {
-    let log_authority = get_log_authority(&ID);
-    let (src_lp_position_key, _) = get_lp_position_address(&ID, pool_key, src);
-    let (dst_lp_position_key, _) = get_lp_position_address(&ID, pool_key, dst);
-
     Instruction {
         program_id: ID,
-        accounts: vec![
-            AccountMeta::new_readonly(ID, false),
-            AccountMeta::new_readonly(log_authority, false),
-            AccountMeta::new(*pool_key, false),
-            AccountMeta::new(*src, true),
-            AccountMeta::new(src_lp_position_key, false),
-            AccountMeta::new(dst_lp_position_key, false),
-        ],
+        accounts: TransferLiquidityAccounts::new(pool_key, src, dst).to_account_metas(),
         data: vec![TRANSFER_LIQUIDITY_DISCRIMINATOR],
     }
 }
 
+pub fn renounce_liquidity(
+    pool_key: &Pubkey,
+    trader: &Pubkey,
+    allow_fee_withdrawal: bool,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: RenounceLiquidityAccounts::new(pool_key, trader).to_account_metas(),
+        data: [
+            vec![RENOUNCE_LIQUIDITY_DISCRIMINATOR],
+            allow_fee_withdrawal.try_to_vec().unwrap(),
+        ]
+        .concat(),
+    }
+}
+
+/// Returns a transfer of the `src` position's shares to the `dst` position followed by the
+/// renounce instruction for the `dst` position.
+///
+/// The program rejects transfers out of or into a renounced position, so the transfer has to run
+/// first and the renouncement applies to the destination that ends up holding the shares. The
+/// transaction must be signed by both `src` (for the transfer) and `dst` (for the renouncement).
+pub fn renounce_and_transfer(
+    pool_key: &Pubkey,
+    src: &Pubkey,
+    dst: &Pubkey,
+    allow_fee_withdrawal: bool,
+) -> Vec<Instruction> {
+    vec![
+        transfer_liquidity(pool_key, src, dst),
+        renounce_liquidity(pool_key, dst, allow_fee_withdrawal),
+    ]
+}
+
 pub fn remove_liquidity(
     pool_key: &Pubkey,
     trader: &Pubkey,
@@ -320,25 +1180,52 @@ pub fn remove_liquidity(
     quote_account_key: &Pubkey,
     shares: u64,
 ) -> Instruction {
-    let log_authority = get_log_authority(&ID);
-    let (lp_position_key, _) = get_lp_position_address(&ID, pool_key, trader);
-    let base_vault_key = get_vault_address(&ID, pool_key, base_mint).0;
-    let quote_vault_key = get_vault_address(&ID, pool_key, quote_mint).0;
+    let accounts = LiquidityAccounts::new(
+        pool_key,
+        trader,
+        base_mint,
+        quote_mint,
+        base_account_key,
+        quote_account_key,
+    );
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(),
+        data: [
+            vec![REMOVE_LIQUIDITY_DISCRIMINATOR],
+            shares.to_le_bytes().to_vec(),
+        ]
+        .concat(),
+    }
+}
 
+/// Same as `remove_liquidity`, but takes the LP position, vault and log authority keys directly
+/// instead of deriving them
+#[allow(clippy::too_many_arguments)]
+pub fn remove_liquidity_prederived(
+    pool_key: &Pubkey,
+    trader: &Pubkey,
+    lp_position_key: &Pubkey,
+    base_account_key: &Pubkey,
+    quote_account_key: &Pubkey,
+    base_vault_key: &Pubkey,
+    quote_vault_key: &Pubkey,
+    log_authority: &Pubkey,
+    shares: u64,
+) -> Instruction {
+    let accounts = LiquidityAccounts {
+        log_authority: *log_authority,
+        pool: *pool_key,
+        trader: *trader,
+        lp_position: *lp_position_key,
+        base_account: *base_account_key,
+        quote_account: *quote_account_key,
+        base_vault: *base_vault_key,
+        quote_vault: *quote_vault_key,
+    };
     Instruction {
         program_id: ID,
-        accounts: vec![
-            AccountMeta::new_readonly(ID, false),
-            AccountMeta::new_readonly(log_authority, false),
-            AccountMeta::new(*pool_key, false),
-            AccountMeta::new_readonly(*trader, true),
-            AccountMeta::new(lp_position_key, false),
-            AccountMeta::new(*base_account_key, false),
-            AccountMeta::new(*quote_account_key, false),
-            AccountMeta::new(base_vault_key, false),
-            AccountMeta::new(quote_vault_key, false),
-            AccountMeta::new_readonly(spl_token::ID, false),
-        ],
+        accounts: accounts.to_account_metas(),
         data: [
             vec![REMOVE_LIQUIDITY_DISCRIMINATOR],
             shares.to_le_bytes().to_vec(),
@@ -347,6 +1234,80 @@ pub fn remove_liquidity(
     }
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, BorshDeserialize, BorshSerialize)]
+pub struct SetLpFeeParams {
+    pub lp_fee_in_bps: u64,
+}
+
+/// Returns an instruction that sets the LP fee of the pool, signed by the pool authority.
+///
+/// The program rejects fees of 500 bps (5%) or more, the same cap as at pool initialization. Pools
+/// created before the authority was recorded have the default pubkey as their authority, so their
+/// fee cannot be changed.
+pub fn set_lp_fee(
+    pool_key: &Pubkey,
+    pool_authority: &Pubkey,
+    params: SetLpFeeParams,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: PoolAuthorityAccounts::new(pool_key, pool_authority).to_account_metas(),
+        data: [vec![SET_LP_FEE_DISCRIMINATOR], params.try_to_vec().unwrap()].concat(),
+    }
+}
+
+/// Donates the protocol fees owed to `protocol_fee_recipient` to the pool's quote reserves
+pub fn reinvest_protocol_fees(pool_key: &Pubkey, protocol_fee_recipient: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: PoolAuthorityAccounts::new(pool_key, protocol_fee_recipient).to_account_metas(),
+        data: vec![REINVEST_PROTOCOL_FEES_DISCRIMINATOR],
+    }
+}
+
+/// Builds a `WithdrawProtocolFees` instruction paying `protocol_fee_recipient`'s accrued fees to
+/// `quote_account_key`. The recipient signs, and must be one of the pool's protocol fee
+/// recipients for the instruction to succeed.
+pub fn withdraw_protocol_fees(
+    pool_key: &Pubkey,
+    protocol_fee_recipient: &Pubkey,
+    quote_mint: &Pubkey,
+    quote_account_key: &Pubkey,
+) -> Instruction {
+    let accounts = WithdrawProtocolFeesAccounts::new(
+        pool_key,
+        protocol_fee_recipient,
+        quote_mint,
+        quote_account_key,
+    );
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(),
+        data: vec![WITHDRAW_PROTOCOL_FEES_DISCRIMINATOR],
+    }
+}
+
+/// Same as `withdraw_protocol_fees`, but first checks that the recipient is registered in
+/// `header`. Returns `IncorrectProtocolFeeRecipient` otherwise, which is the check the program
+/// would fail on.
+pub fn withdraw_protocol_fees_checked(
+    header: &PoolHeader,
+    pool_key: &Pubkey,
+    protocol_fee_recipient: &Pubkey,
+    quote_account_key: &Pubkey,
+) -> PlasmaResult<Instruction> {
+    if !header.fee_recipients.is_registered(protocol_fee_recipient) {
+        return Err(PlasmaError::IncorrectProtocolFeeRecipient);
+    }
+    Ok(withdraw_protocol_fees(
+        pool_key,
+        protocol_fee_recipient,
+        &header.quote_params.mint_key,
+        quote_account_key,
+    ))
+}
+
 #[test]
 fn test_instruction_serialization() {
     for i in 0..=255 {
@@ -354,10 +1315,1022 @@ fn test_instruction_serialization() {
             Ok(j) => j,
             Err(_) => {
                 // This needs to be changed if new instructions are added
-                assert!(i > 7);
+                assert!(i > 14);
                 continue;
             }
         };
         assert_eq!(instruction as u8, i);
     }
 }
+
+#[test]
+fn test_add_liquidity_params_with_expected_transfer_fee_bps() {
+    let params = AddLiquidityParams {
+        desired_base_amount_in: 1_000,
+        desired_quote_amount_in: 2_000,
+        initial_lp_shares: None,
+        expected_transfer_fee_bps: None,
+    }
+    .with_expected_transfer_fee_bps(100, 0);
+    assert_eq!(
+        params.expected_transfer_fee_bps,
+        Some(TransferFeeBps {
+            base: 100,
+            quote: 0
+        })
+    );
+
+    let data = [
+        vec![ADD_LIQUIDITY_DISCRIMINATOR],
+        params.try_to_vec().unwrap(),
+    ]
+    .concat();
+    match PlasmaInstruction::decode(&data).unwrap() {
+        DecodedPlasmaInstruction::AddLiquidity(decoded) => {
+            assert_eq!(decoded.desired_base_amount_in, 1_000);
+            assert_eq!(
+                decoded.expected_transfer_fee_bps,
+                params.expected_transfer_fee_bps
+            );
+        }
+        other => panic!("Unexpected instruction {:?}", other),
+    }
+
+    // Params encoded before transfer fees were supported still decode
+    let legacy = [
+        vec![ADD_LIQUIDITY_DISCRIMINATOR],
+        1_000_u64.to_le_bytes().to_vec(),
+        2_000_u64.to_le_bytes().to_vec(),
+        vec![0],
+    ]
+    .concat();
+    match PlasmaInstruction::decode(&legacy).unwrap() {
+        DecodedPlasmaInstruction::AddLiquidity(decoded) => {
+            assert_eq!(decoded.desired_quote_amount_in, 2_000);
+            assert_eq!(decoded.expected_transfer_fee_bps, None);
+        }
+        other => panic!("Unexpected instruction {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_initialize_pool() {
+    let params = InitializePoolParams {
+        lp_fee_in_bps: 30,
+        protocol_fee_allocation_in_pct: 20,
+        num_slots_to_vest_lp_shares: Some(100),
+        min_quote_deposit: Some(1_000),
+        protocol_fee_allocation_in_bps: Some(2_050),
+        ..Default::default()
+    };
+    let ix = initialize_pool(
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        params,
+    );
+    match PlasmaInstruction::decode(&ix.data).unwrap() {
+        DecodedPlasmaInstruction::InitializePool(decoded) => {
+            assert_eq!(decoded.lp_fee_in_bps, 30);
+            assert_eq!(decoded.min_quote_deposit, Some(1_000));
+            assert_eq!(decoded.max_quote_tvl, None);
+            assert_eq!(decoded.effective_protocol_fee_allocation_in_bps(), 2_050);
+        }
+        other => panic!("Unexpected instruction {:?}", other),
+    }
+    assert_eq!(
+        PlasmaInstruction::decode(&[&ix.data[..], &[0]].concat()).unwrap_err(),
+        PlasmaError::UnexpectedArgument
+    );
+
+    // Params encoded before the allocation could be given in basis points use the percentage
+    let data = &ix.data[..ix.data.len() - 9];
+    match PlasmaInstruction::decode(data).unwrap() {
+        DecodedPlasmaInstruction::InitializePool(decoded) => {
+            assert_eq!(decoded.protocol_fee_allocation_in_bps, None);
+            assert_eq!(decoded.effective_protocol_fee_allocation_in_bps(), 2_000);
+        }
+        other => panic!("Unexpected instruction {:?}", other),
+    }
+
+    // Params encoded before the minimum deposit was added still decode
+    let legacy = &data[..data.len() - 10];
+    match PlasmaInstruction::decode(legacy).unwrap() {
+        DecodedPlasmaInstruction::InitializePool(decoded) => {
+            assert_eq!(decoded.num_slots_to_vest_lp_shares, Some(100));
+            assert_eq!(decoded.min_quote_deposit, None);
+        }
+        other => panic!("Unexpected instruction {:?}", other),
+    }
+    // So do params encoded before the TVL cap was added
+    match PlasmaInstruction::decode(&data[..data.len() - 1]).unwrap() {
+        DecodedPlasmaInstruction::InitializePool(decoded) => {
+            assert_eq!(decoded.min_quote_deposit, Some(1_000));
+            assert_eq!(decoded.max_quote_tvl, None);
+        }
+        other => panic!("Unexpected instruction {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_remove_liquidity() {
+    let pool = Pubkey::new_unique();
+    let trader = Pubkey::new_unique();
+    let ix = remove_liquidity(
+        &pool,
+        &trader,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        123_456_789,
+    );
+    match PlasmaInstruction::decode(&ix.data).unwrap() {
+        DecodedPlasmaInstruction::RemoveLiquidity { shares } => assert_eq!(shares, 123_456_789),
+        decoded => panic!("Unexpected instruction {:?}", decoded),
+    }
+
+    assert_eq!(
+        PlasmaInstruction::decode(&ix.data[..ix.data.len() - 1]).unwrap_err(),
+        PlasmaError::MissingExpectedArgument
+    );
+    assert_eq!(
+        PlasmaInstruction::decode(&[ix.data.as_slice(), &[0]].concat()).unwrap_err(),
+        PlasmaError::UnexpectedArgument
+    );
+}
+
+#[test]
+fn test_initialize_pool_expected_event() {
+    let params = InitializePoolParams {
+        lp_fee_in_bps: 25,
+        protocol_fee_allocation_in_pct: 20,
+        fee_recipients_params: [
+            ProtocolFeeRecipientParams {
+                recipient: Pubkey::new_unique(),
+                shares: 7_000,
+            },
+            ProtocolFeeRecipientParams {
+                recipient: Pubkey::new_unique(),
+                shares: 2_000,
+            },
+            ProtocolFeeRecipientParams::default(),
+        ],
+        num_slots_to_vest_lp_shares: Some(8),
+        min_quote_deposit: None,
+        max_quote_tvl: None,
+        protocol_fee_allocation_in_bps: None,
+    };
+    let event = params.expected_event();
+    assert_eq!(event.lp_fee_in_bps, params.lp_fee_in_bps);
+    assert_eq!(event.protocol_fee_in_pct, 20);
+    assert_eq!(event.protocol_fee_in_bps, 2_000);
+
+    // Basis points take precedence over the percentage
+    let event = InitializePoolParams {
+        protocol_fee_allocation_in_bps: Some(2_050),
+        ..params
+    }
+    .expected_event();
+    assert_eq!(event.protocol_fee_in_pct, 20);
+    assert_eq!(event.protocol_fee_in_bps, 2_050);
+    for (event_params, params) in event
+        .fee_recipient_params
+        .iter()
+        .zip(params.fee_recipients_params.iter())
+    {
+        assert_eq!(event_params.recipient, params.recipient);
+        assert_eq!(event_params.shares, params.shares);
+    }
+}
+
+#[test]
+fn test_swap_writable_accounts() {
+    let pool = Pubkey::new_unique();
+    let base_mint = Pubkey::new_unique();
+    let quote_mint = Pubkey::new_unique();
+    let base_account = Pubkey::new_unique();
+    let quote_account = Pubkey::new_unique();
+    let ix = swap(
+        &pool,
+        &Pubkey::new_unique(),
+        &base_mint,
+        &quote_mint,
+        &base_account,
+        &quote_account,
+        SwapParams {
+            side: Side::Buy,
+            swap_type: SwapType::ExactIn {
+                amount_in: 1,
+                min_amount_out: 0,
+            },
+            deadline_slot: None,
+        },
+    );
+    let keys = ix.accounts.iter().map(|a| a.pubkey).collect::<Vec<_>>();
+    let writable = PlasmaInstruction::Swap.writable_accounts(&keys);
+
+    assert_eq!(writable.len(), 5);
+    for key in [
+        pool,
+        base_account,
+        quote_account,
+        get_vault_address(&ID, &pool, &base_mint).0,
+        get_vault_address(&ID, &pool, &quote_mint).0,
+    ] {
+        assert!(writable.contains(&key));
+    }
+    assert!(!writable.contains(&ID));
+    assert!(!writable.contains(&spl_token::ID));
+
+    // The annotations agree with the builder
+    for meta in ix.accounts.iter() {
+        assert_eq!(meta.is_writable, writable.contains(&meta.pubkey));
+    }
+}
+
+#[test]
+fn test_withdraw_protocol_fees() {
+    let [pool_key, recipient, quote_mint, quote_account_key] =
+        [(); 4].map(|_| Pubkey::new_unique());
+    let ix = withdraw_protocol_fees(&pool_key, &recipient, &quote_mint, &quote_account_key);
+    assert_eq!(ix.program_id, ID);
+    assert_eq!(ix.data, vec![WITHDRAW_PROTOCOL_FEES_DISCRIMINATOR]);
+    assert!(matches!(
+        PlasmaInstruction::decode(&ix.data),
+        Ok(DecodedPlasmaInstruction::WithdrawProtocolFees)
+    ));
+    validate_instruction_accounts(&ix).unwrap();
+
+    let keys = ix
+        .accounts
+        .iter()
+        .map(|meta| meta.pubkey)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        keys,
+        vec![
+            ID,
+            get_log_authority(&ID),
+            pool_key,
+            recipient,
+            quote_account_key,
+            get_vault_address(&ID, &pool_key, &quote_mint).0,
+            spl_token::ID,
+        ]
+    );
+    assert_eq!(ix.accounts.iter().position(|meta| meta.is_signer), Some(3));
+}
+
+#[test]
+fn test_withdraw_protocol_fees_checked() {
+    use crate::accounts::ProtocolFeeRecipient;
+    use bytemuck::Zeroable;
+
+    let [pool_key, recipient, quote_account_key] = [(); 3].map(|_| Pubkey::new_unique());
+    let mut header = PoolHeader::zeroed();
+    header.quote_params.mint_key = Pubkey::new_unique();
+    header.fee_recipients.recipients[1] = ProtocolFeeRecipient {
+        recipient,
+        shares: 1,
+        total_accumulated_quote_fees: 0,
+        collected_quote_fees: 0,
+    };
+    assert!(header.fee_recipients.is_registered(&recipient));
+
+    let ix =
+        withdraw_protocol_fees_checked(&header, &pool_key, &recipient, &quote_account_key).unwrap();
+    assert_eq!(ix.data, vec![WITHDRAW_PROTOCOL_FEES_DISCRIMINATOR]);
+    validate_instruction_accounts(&ix).unwrap();
+    assert_eq!(ix.accounts[3].pubkey, recipient);
+    assert!(ix.accounts[3].is_signer);
+    assert_eq!(
+        ix.accounts[5].pubkey,
+        get_vault_address(&ID, &pool_key, &header.quote_params.mint_key).0
+    );
+
+    // A key that is not one of the pool's recipients would fail on-chain
+    let unregistered = Pubkey::new_unique();
+    assert!(!header.fee_recipients.is_registered(&unregistered));
+    assert_eq!(
+        withdraw_protocol_fees_checked(&header, &pool_key, &unregistered, &quote_account_key),
+        Err(PlasmaError::IncorrectProtocolFeeRecipient)
+    );
+}
+
+#[test]
+fn test_smart_swap_price_impact_guard() {
+    use bytemuck::Zeroable;
+
+    let amm = new_pool(30);
+    let mut header = PoolHeader::zeroed();
+    header.base_params.mint_key = Pubkey::new_unique();
+    header.quote_params.mint_key = Pubkey::new_unique();
+
+    let build = |amount_in: u64| {
+        smart_swap(
+            &Pubkey::new_unique(),
+            &header,
+            &amm,
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            SmartSwapParams {
+                swap_params: SwapParams {
+                    side: Side::Buy,
+                    swap_type: SwapType::ExactIn {
+                        amount_in,
+                        min_amount_out: 0,
+                    },
+                    deadline_slot: None,
+                },
+                slot: 0,
+                max_price_impact_bps: Some(100),
+            },
+        )
+    };
+
+    assert!(build(1_000_000).is_ok());
+    match build(100_000_000).unwrap_err() {
+        PlasmaError::PriceImpactTooHigh {
+            price_impact_bps,
+            max_price_impact_bps,
+        } => {
+            assert!(price_impact_bps > 100);
+            assert_eq!(max_price_impact_bps, 100);
+        }
+        e => panic!("Unexpected error {}", e),
+    }
+}
+
+#[test]
+fn test_swap_params_versioned_bytes() {
+    for params in [
+        SwapParams {
+            side: Side::Buy,
+            swap_type: SwapType::ExactIn {
+                amount_in: 100,
+                min_amount_out: 90,
+            },
+            deadline_slot: None,
+        },
+        SwapParams {
+            side: Side::Sell,
+            swap_type: SwapType::ExactOut {
+                amount_out: 50,
+                max_amount_in: 60,
+            },
+            deadline_slot: None,
+        },
+    ] {
+        let bytes = params.to_versioned_bytes();
+        assert_eq!(bytes[0], SwapParams::WIRE_VERSION);
+        let decoded = SwapParams::from_versioned_bytes(&bytes).unwrap();
+        assert_eq!(decoded.try_to_vec().unwrap(), params.try_to_vec().unwrap());
+
+        let mut unknown_version = bytes.clone();
+        unknown_version[0] = SwapParams::WIRE_VERSION + 1;
+        assert_eq!(
+            SwapParams::from_versioned_bytes(&unknown_version).unwrap_err(),
+            PlasmaError::UnexpectedArgument
+        );
+    }
+    assert_eq!(
+        SwapParams::from_versioned_bytes(&[]).unwrap_err(),
+        PlasmaError::MissingExpectedArgument
+    );
+
+    // Version 1 predates the deadline, so its payload ends after the swap type
+    let params = SwapParams {
+        side: Side::Buy,
+        swap_type: SwapType::ExactIn {
+            amount_in: 100,
+            min_amount_out: 90,
+        },
+        deadline_slot: Some(1_000),
+    };
+    let v2_bytes = params.to_versioned_bytes();
+    let mut v1_bytes = v2_bytes[..v2_bytes.len() - 9].to_vec();
+    v1_bytes[0] = 1;
+    let decoded = SwapParams::from_versioned_bytes(&v1_bytes).unwrap();
+    assert!(decoded.deadline_slot.is_none());
+    assert_eq!(
+        decoded.swap_type.try_to_vec().unwrap(),
+        params.swap_type.try_to_vec().unwrap()
+    );
+    assert_eq!(
+        SwapParams::from_versioned_bytes(&[&v1_bytes[..], &[0]].concat()).unwrap_err(),
+        PlasmaError::UnexpectedArgument
+    );
+    // Version 2 round-trips the deadline
+    let decoded = SwapParams::from_versioned_bytes(&v2_bytes).unwrap();
+    assert_eq!(decoded.deadline_slot, Some(1_000));
+}
+
+#[test]
+fn test_prederived_builders_match_standard_builders() {
+    let pool_key = Pubkey::new_unique();
+    let trader = Pubkey::new_unique();
+    let base_mint = Pubkey::new_unique();
+    let quote_mint = Pubkey::new_unique();
+    let base_account = Pubkey::new_unique();
+    let quote_account = Pubkey::new_unique();
+
+    let log_authority = get_log_authority(&ID);
+    let lp_position = get_lp_position_address(&ID, &pool_key, &trader).0;
+    let base_vault = get_vault_address(&ID, &pool_key, &base_mint).0;
+    let quote_vault = get_vault_address(&ID, &pool_key, &quote_mint).0;
+
+    let swap_params = SwapParams {
+        side: Side::Sell,
+        swap_type: SwapType::ExactIn {
+            amount_in: 1_000,
+            min_amount_out: 900,
+        },
+        deadline_slot: None,
+    };
+    assert_eq!(
+        swap(
+            &pool_key,
+            &trader,
+            &base_mint,
+            &quote_mint,
+            &base_account,
+            &quote_account,
+            swap_params,
+        ),
+        swap_prederived(
+            &pool_key,
+            &trader,
+            &base_account,
+            &quote_account,
+            &base_vault,
+            &quote_vault,
+            &log_authority,
+            swap_params,
+        )
+    );
+
+    let add_params = AddLiquidityParams {
+        desired_base_amount_in: 1_000,
+        desired_quote_amount_in: 2_000,
+        initial_lp_shares: None,
+        expected_transfer_fee_bps: None,
+    };
+    assert_eq!(
+        add_liquidity(
+            &pool_key,
+            &trader,
+            &base_mint,
+            &base_account,
+            &quote_mint,
+            &quote_account,
+            add_params,
+        ),
+        add_liquidity_prederived(
+            &pool_key,
+            &trader,
+            &lp_position,
+            &base_account,
+            &quote_account,
+            &base_vault,
+            &quote_vault,
+            &log_authority,
+            add_params,
+        )
+    );
+
+    assert_eq!(
+        remove_liquidity(
+            &pool_key,
+            &trader,
+            &base_mint,
+            &quote_mint,
+            &base_account,
+            &quote_account,
+            500,
+        ),
+        remove_liquidity_prederived(
+            &pool_key,
+            &trader,
+            &lp_position,
+            &base_account,
+            &quote_account,
+            &base_vault,
+            &quote_vault,
+            &log_authority,
+            500,
+        )
+    );
+}
+
+#[test]
+fn test_batch_initialize_lp_positions() {
+    let pool_key = Pubkey::new_unique();
+    let payer = Pubkey::new_unique();
+    let owners = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let ix = batch_initialize_lp_positions(&pool_key, &payer, &owners).unwrap();
+    assert_eq!(ix.accounts.len(), 5 + 2 * owners.len());
+
+    let lp_position_keys = owners
+        .iter()
+        .map(|owner| get_lp_position_address(&ID, &pool_key, owner).0)
+        .collect::<Vec<_>>();
+    for (i, owner) in owners.iter().enumerate() {
+        assert_eq!(ix.accounts[5 + 2 * i].pubkey, *owner);
+        assert_eq!(ix.accounts[6 + 2 * i].pubkey, lp_position_keys[i]);
+    }
+
+    // The writable accounts match the account metas of the builder
+    let account_keys = ix.accounts.iter().map(|a| a.pubkey).collect::<Vec<_>>();
+    let writable_accounts =
+        PlasmaInstruction::BatchInitializeLpPositions.writable_accounts(&account_keys);
+    assert_eq!(
+        writable_accounts,
+        ix.accounts
+            .iter()
+            .filter(|a| a.is_writable)
+            .map(|a| a.pubkey)
+            .collect::<Vec<_>>()
+    );
+    assert!(matches!(
+        PlasmaInstruction::decode(&ix.data),
+        Ok(DecodedPlasmaInstruction::BatchInitializeLpPositions)
+    ));
+
+    // Empty and oversized batches are rejected instead of building an instruction that fails
+    assert_eq!(
+        batch_initialize_lp_positions(&pool_key, &payer, &[]),
+        Err(PlasmaError::UnexpectedArgument)
+    );
+    let too_many_owners = [Pubkey::new_unique(); MAX_LP_POSITIONS_PER_BATCH + 1];
+    assert_eq!(
+        batch_initialize_lp_positions(&pool_key, &payer, &too_many_owners),
+        Err(PlasmaError::UnexpectedArgument)
+    );
+    assert!(batch_initialize_lp_positions(
+        &pool_key,
+        &payer,
+        &too_many_owners[..MAX_LP_POSITIONS_PER_BATCH]
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_reinvest_protocol_fees() {
+    let pool_key = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let ix = reinvest_protocol_fees(&pool_key, &recipient);
+    assert_eq!(ix.accounts.len(), 4);
+    assert!(ix.accounts[3].is_signer);
+
+    let account_keys = ix.accounts.iter().map(|a| a.pubkey).collect::<Vec<_>>();
+    assert_eq!(
+        PlasmaInstruction::ReinvestProtocolFees.writable_accounts(&account_keys),
+        vec![pool_key]
+    );
+    assert!(matches!(
+        PlasmaInstruction::decode(&ix.data),
+        Ok(DecodedPlasmaInstruction::ReinvestProtocolFees)
+    ));
+}
+
+#[test]
+fn test_release_vesting() {
+    let pool_key = Pubkey::new_unique();
+    let pool_authority = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let ix = release_vesting(&pool_key, &pool_authority, &owner);
+    assert_eq!(ix.accounts.len(), 6);
+    assert_eq!(ix.accounts[3].pubkey, pool_authority);
+    assert!(ix.accounts[3].is_signer);
+
+    let lp_position = get_lp_position_address(&ID, &pool_key, &owner).0;
+    let account_keys = ix.accounts.iter().map(|a| a.pubkey).collect::<Vec<_>>();
+    assert_eq!(
+        PlasmaInstruction::ReleaseVesting.writable_accounts(&account_keys),
+        vec![pool_key, lp_position]
+    );
+    assert!(matches!(
+        PlasmaInstruction::decode(&ix.data),
+        Ok(DecodedPlasmaInstruction::ReleaseVesting)
+    ));
+}
+
+#[test]
+fn test_swap_all_uses_full_balance() {
+    let amm = new_pool(30);
+    let keys = [(); 6].map(|_| Pubkey::new_unique());
+
+    for (side, balance) in [(Side::Buy, 12_345_678), (Side::Sell, 7_654_321)] {
+        let ix = swap_all(
+            &keys[0], &amm, &keys[1], &keys[2], &keys[3], &keys[4], &keys[5], side, balance, 50,
+        )
+        .unwrap();
+        let Ok(DecodedPlasmaInstruction::Swap(params)) = PlasmaInstruction::decode(&ix.data) else {
+            panic!("Expected a swap instruction");
+        };
+        let SwapType::ExactIn {
+            amount_in,
+            min_amount_out,
+        } = params.swap_type
+        else {
+            panic!("Expected an ExactIn swap");
+        };
+        assert_eq!(params.side, side);
+        assert_eq!(amount_in, balance);
+
+        let quote = quote_swap_at_slot(&amm, amm.get_slot(), params).unwrap();
+        let amount_out = match side {
+            Side::Buy => quote.base_amount_to_transfer,
+            Side::Sell => quote.quote_amount_to_transfer,
+        };
+        assert_eq!(min_amount_out, amount_out * 9_950 / 10_000);
+    }
+}
+
+#[test]
+fn test_twap_swap_schedule() {
+    let amm = new_pool(30);
+    let keys = [(); 6].map(|_| Pubkey::new_unique());
+    let schedule = |total_input, num_slices| {
+        twap_swap_schedule(
+            &keys[0],
+            &amm,
+            &keys[1],
+            &keys[2],
+            &keys[3],
+            &keys[4],
+            &keys[5],
+            Side::Buy,
+            total_input,
+            num_slices,
+            50,
+        )
+    };
+    let slice_inputs = |ixs: Vec<Instruction>| {
+        ixs.iter()
+            .map(|ix| match PlasmaInstruction::decode(&ix.data) {
+                Ok(DecodedPlasmaInstruction::Swap(SwapParams {
+                    swap_type: SwapType::ExactIn { amount_in, .. },
+                    ..
+                })) => amount_in,
+                _ => panic!("Expected an ExactIn swap"),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let inputs = slice_inputs(schedule(10_000_003, 4).unwrap());
+    assert_eq!(inputs, vec![2_500_000, 2_500_000, 2_500_000, 2_500_003]);
+    assert_eq!(inputs.iter().sum::<u64>(), 10_000_003);
+
+    let inputs = slice_inputs(schedule(7_777_777, 7).unwrap());
+    assert_eq!(inputs.len(), 7);
+    assert_eq!(inputs.iter().sum::<u64>(), 7_777_777);
+
+    assert_eq!(schedule(1_000, 0), Err(PlasmaError::UnexpectedArgument));
+}
+
+#[test]
+fn test_swap_with_native_sol() {
+    let amm = new_pool(30);
+    let [pool_key, trader, base_mint, token_account_key] = [(); 4].map(|_| Pubkey::new_unique());
+    let quote_mint = spl_token::native_mint::ID;
+
+    let ixs = swap_with_native_sol(
+        &pool_key,
+        &amm,
+        &trader,
+        &base_mint,
+        &quote_mint,
+        &token_account_key,
+        5_000_000,
+        Side::Buy,
+        50,
+        7,
+        2_039_280,
+    )
+    .unwrap();
+    assert_eq!(ixs.len(), 6);
+    let wsol_account_key = get_native_sol_account_address(&trader, 7);
+    assert_eq!(
+        wsol_account_key,
+        Pubkey::create_with_seed(&trader, "plasma-wsol-7", &spl_token::ID).unwrap()
+    );
+    // Swaps with other nonces use other accounts, so they can be in flight at the same time
+    assert_ne!(get_native_sol_account_address(&trader, 8), wsol_account_key);
+    // Every nonce yields a valid seed
+    assert_eq!(
+        native_sol_account_seed(u64::MAX).len(),
+        solana_program::pubkey::MAX_SEED_LEN
+    );
+
+    // The wrapped SOL account is created, initialized and funded before the swap
+    assert_eq!(ixs[0].program_id, solana_program::system_program::ID);
+    assert_eq!(ixs[0].accounts[1].pubkey, wsol_account_key);
+    assert_eq!(ixs[1].program_id, spl_token::ID);
+    assert_eq!(ixs[1].data[0], INITIALIZE_TOKEN_ACCOUNT_3_TAG);
+    assert_eq!(ixs[2].program_id, solana_program::system_program::ID);
+    assert_eq!(ixs[3].data, vec![SYNC_NATIVE_TAG]);
+
+    // The swap spends all of the wrapped SOL as quote and sends base to the token account
+    assert_eq!(ixs[4].program_id, ID);
+    let Ok(DecodedPlasmaInstruction::Swap(params)) = PlasmaInstruction::decode(&ixs[4].data) else {
+        panic!("Expected a swap instruction");
+    };
+    assert_eq!(params.side, Side::Buy);
+    assert!(matches!(
+        params.swap_type,
+        SwapType::ExactIn {
+            amount_in: 5_000_000,
+            ..
+        }
+    ));
+    let swap_accounts = SwapAccounts::new(
+        &pool_key,
+        &trader,
+        &base_mint,
+        &quote_mint,
+        &token_account_key,
+        &wsol_account_key,
+    );
+    assert_eq!(ixs[4].accounts, swap_accounts.to_account_metas());
+
+    // The account is closed back to the trader afterwards
+    assert_eq!(ixs[5].program_id, spl_token::ID);
+    assert_eq!(ixs[5].data, vec![CLOSE_TOKEN_ACCOUNT_TAG]);
+    assert_eq!(ixs[5].accounts[0].pubkey, wsol_account_key);
+    assert_eq!(ixs[5].accounts[1].pubkey, trader);
+
+    // Wrapped SOL has to be the input of the swap
+    assert_eq!(
+        swap_with_native_sol(
+            &pool_key,
+            &amm,
+            &trader,
+            &base_mint,
+            &quote_mint,
+            &token_account_key,
+            5_000_000,
+            Side::Sell,
+            50,
+            7,
+            2_039_280,
+        ),
+        Err(PlasmaError::UnexpectedArgument)
+    );
+}
+
+#[test]
+fn test_instruction_names() {
+    for i in 0..=255 {
+        match PlasmaInstruction::try_from(i) {
+            Ok(instruction) => {
+                assert_eq!(
+                    instruction_name(i),
+                    Some(format!("{:?}", instruction).as_str())
+                )
+            }
+            Err(_) => assert_eq!(instruction_name(i), None),
+        }
+    }
+    assert_eq!(instruction_name(SWAP_DISCRIMINATOR), Some("Swap"));
+}
+
+#[test]
+fn test_describe_instruction() {
+    let pool_key = Pubkey::new_unique();
+    let swap_ix = swap(
+        &pool_key,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        SwapParams {
+            side: Side::Buy,
+            swap_type: SwapType::ExactIn {
+                amount_in: 1_000_000,
+                min_amount_out: 990_000,
+            },
+            deadline_slot: None,
+        },
+    );
+    assert_eq!(
+        describe_instruction(&swap_ix),
+        "Swap(SwapParams { side: Buy, swap_type: ExactIn { amount_in: 1000000, min_amount_out: 990000 }, deadline_slot: None }) (9 accounts)"
+    );
+
+    let initialize_pool_ix = initialize_pool(
+        &pool_key,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        InitializePoolParams {
+            lp_fee_in_bps: 30,
+            ..Default::default()
+        },
+    );
+    let description = describe_instruction(&initialize_pool_ix);
+    assert!(description.starts_with("InitializePool(InitializePoolParams { lp_fee_in_bps: 30,"));
+    assert!(description.ends_with("(10 accounts)"));
+
+    let mut truncated_ix = swap_ix.clone();
+    truncated_ix.data.truncate(2);
+    assert!(describe_instruction(&truncated_ix).starts_with("Invalid Plasma instruction"));
+
+    let mut foreign_ix = swap_ix;
+    foreign_ix.program_id = Pubkey::new_unique();
+    assert!(describe_instruction(&foreign_ix).starts_with("Not a Plasma instruction"));
+}
+
+#[test]
+fn test_renounce_and_transfer() {
+    let pool_key = Pubkey::new_unique();
+    let src = Pubkey::new_unique();
+    let dst = Pubkey::new_unique();
+    let ixs = renounce_and_transfer(&pool_key, &src, &dst, true);
+    assert_eq!(ixs.len(), 2);
+    assert_eq!(ixs[0].data[0], TRANSFER_LIQUIDITY_DISCRIMINATOR);
+    assert_eq!(ixs[1].data[0], RENOUNCE_LIQUIDITY_DISCRIMINATOR);
+    assert!(matches!(
+        PlasmaInstruction::decode(&ixs[1].data).unwrap(),
+        DecodedPlasmaInstruction::RenounceLiquidity {
+            allow_fee_withdrawal: true
+        }
+    ));
+    // The source owner signs the transfer and the destination owner signs the renouncement
+    assert!(ixs[0].accounts[3].is_signer);
+    assert_eq!(ixs[0].accounts[3].pubkey, src);
+    assert_eq!(
+        ixs[0].accounts[5].pubkey,
+        get_lp_position_address(&ID, &pool_key, &dst).0
+    );
+    assert!(ixs[1].accounts[3].is_signer);
+    assert_eq!(ixs[1].accounts[3].pubkey, dst);
+    assert_eq!(
+        ixs[1].accounts[4].pubkey,
+        get_lp_position_address(&ID, &pool_key, &dst).0
+    );
+}
+
+#[test]
+fn test_swap_params_deadline_slot() {
+    let params = SwapParams {
+        side: Side::Buy,
+        swap_type: SwapType::ExactIn {
+            amount_in: 1_000,
+            min_amount_out: 900,
+        },
+        deadline_slot: Some(1_234),
+    };
+    let ix = swap(
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        params,
+    );
+    let DecodedPlasmaInstruction::Swap(decoded) = PlasmaInstruction::decode(&ix.data).unwrap()
+    else {
+        panic!("Expected a swap");
+    };
+    assert_eq!(decoded.deadline_slot, Some(1_234));
+
+    // Params encoded before the deadline was added decode without one
+    let legacy = [
+        vec![SWAP_DISCRIMINATOR],
+        params.side.try_to_vec().unwrap(),
+        params.swap_type.try_to_vec().unwrap(),
+    ]
+    .concat();
+    let DecodedPlasmaInstruction::Swap(decoded) = PlasmaInstruction::decode(&legacy).unwrap()
+    else {
+        panic!("Expected a swap");
+    };
+    assert_eq!(decoded.deadline_slot, None);
+}
+
+#[test]
+fn test_swap_params_decode_side() {
+    let swap_type = SwapType::ExactIn {
+        amount_in: 1_000,
+        min_amount_out: 900,
+    }
+    .try_to_vec()
+    .unwrap();
+    for (byte, side) in [(0, Side::Buy), (1, Side::Sell)] {
+        let decoded = SwapParams::decode(&[vec![byte], swap_type.clone()].concat()).unwrap();
+        assert_eq!(decoded.side, side);
+    }
+    assert_eq!(
+        SwapParams::decode(&[vec![2], swap_type].concat()).unwrap_err(),
+        PlasmaError::UnexpectedArgument
+    );
+    assert_eq!(
+        SwapParams::decode(&[]).unwrap_err(),
+        PlasmaError::MissingExpectedArgument
+    );
+}
+
+#[test]
+fn test_validate_instruction_accounts() {
+    let keys = [(); 6].map(|_| Pubkey::new_unique());
+    let [pool, trader, base_mint, quote_mint, base_account, quote_account] = keys;
+    let ix = swap(
+        &pool,
+        &trader,
+        &base_mint,
+        &quote_mint,
+        &base_account,
+        &quote_account,
+        SwapParams {
+            side: Side::Buy,
+            swap_type: SwapType::ExactIn {
+                amount_in: 1_000,
+                min_amount_out: 0,
+            },
+            deadline_slot: None,
+        },
+    );
+    assert_eq!(validate_instruction_accounts(&ix), Ok(()));
+
+    // The builders all produce valid instructions
+    for ix in [
+        add_liquidity(
+            &pool,
+            &trader,
+            &base_mint,
+            &base_account,
+            &quote_mint,
+            &quote_account,
+            AddLiquidityParams::default(),
+        ),
+        remove_liquidity(
+            &pool,
+            &trader,
+            &base_mint,
+            &quote_mint,
+            &base_account,
+            &quote_account,
+            1_000,
+        ),
+        renounce_liquidity(&pool, &trader, true),
+        initialize_pool(&pool, &trader, &base_mint, &quote_mint, Default::default()),
+        initialize_lp_position(&pool, &trader, &base_account),
+        batch_initialize_lp_positions(&pool, &trader, &keys[..3]).unwrap(),
+        transfer_liquidity(&pool, &trader, &base_account),
+        crank_vesting(&pool, &trader, &base_account),
+        release_vesting(&pool, &trader, &base_account),
+        set_lp_fee(&pool, &trader, SetLpFeeParams { lp_fee_in_bps: 30 }),
+        reinvest_protocol_fees(&pool, &trader),
+    ] {
+        assert_eq!(validate_instruction_accounts(&ix), Ok(()), "{:?}", ix);
+    }
+
+    // Extra privileges are allowed
+    let mut ix_with_writable_program = ix.clone();
+    ix_with_writable_program.accounts[0].is_writable = true;
+    assert_eq!(
+        validate_instruction_accounts(&ix_with_writable_program),
+        Ok(())
+    );
+
+    // A read-only base vault is caught
+    let mut invalid_ix = ix.clone();
+    invalid_ix.accounts[6].is_writable = false;
+    assert_eq!(
+        validate_instruction_accounts(&invalid_ix),
+        Err(PlasmaError::InvalidAccountFlags {
+            index: 6,
+            expected_signer: false,
+            expected_writable: true,
+            is_signer: false,
+            is_writable: false,
+        })
+    );
+
+    let mut invalid_ix = ix.clone();
+    invalid_ix.accounts.pop();
+    assert_eq!(
+        validate_instruction_accounts(&invalid_ix),
+        Err(PlasmaError::InvalidAccountCount {
+            expected: 9,
+            actual: 8
+        })
+    );
+
+    let mut invalid_ix = ix;
+    invalid_ix.program_id = Pubkey::new_unique();
+    assert_eq!(
+        validate_instruction_accounts(&invalid_ix),
+        Err(PlasmaError::UnexpectedArgument)
+    );
+}
@@ -1,14 +1,22 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::{declare_id, pubkey::Pubkey};
+use solana_program::{declare_id, instruction::Instruction, pubkey::Pubkey, rent::Rent};
 
 pub use plasma_amm_state::amm;
 pub use plasma_amm_state::lp;
+pub use plasma_amm_state::transfer_fee;
 
 pub mod accounts;
+pub mod client;
+pub mod compute;
+pub mod cpi;
 pub mod errors;
 pub mod events;
 pub mod fixed;
+pub mod instruction_accounts;
 pub mod instructions;
+pub mod liquidity;
+pub mod price;
+pub mod quote;
 
 declare_id!("srAMMzfVHVAtgSJc8iH6CfKzuWuUTzLHVCE81QU1rgi");
 
@@ -16,6 +24,13 @@ pub mod spl_token {
     use solana_program::declare_id;
 
     declare_id!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+    /// Mint of wrapped SOL
+    pub mod native_mint {
+        use solana_program::declare_id;
+
+        declare_id!("So11111111111111111111111111111111111111112");
+    }
 }
 
 pub fn get_vault_address(plasma_program_id: &Pubkey, pool: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
@@ -37,6 +52,54 @@ pub fn get_log_authority(plasma_program_id: &Pubkey) -> Pubkey {
     Pubkey::find_program_address(&[b"log"], plasma_program_id).0
 }
 
+/// Size of an SPL token account
+pub const TOKEN_ACCOUNT_LEN: u64 = 165;
+
+/// Lamports the pool creator pays in rent for the pool account and its two token vaults
+pub fn total_pool_creation_cost(rent: &Rent) -> u64 {
+    rent.minimum_balance(accounts::POOL_LEN as usize)
+        + 2 * rent.minimum_balance(TOKEN_ACCOUNT_LEN as usize)
+}
+
+/// Tag of the SPL token `CloseAccount` instruction
+pub(crate) const CLOSE_TOKEN_ACCOUNT_TAG: u8 = 9;
+/// Tag of the SPL token `SyncNative` instruction
+pub(crate) const SYNC_NATIVE_TAG: u8 = 17;
+/// Tag of the SPL token `InitializeAccount3` instruction
+pub(crate) const INITIALIZE_TOKEN_ACCOUNT_3_TAG: u8 = 18;
+
+/// Lamports a user nets from sending `instructions` in one transaction: the rent reclaimed by
+/// closing token accounts minus the signature fees of every distinct signer.
+///
+/// Only SPL token `CloseAccount` instructions reclaim rent, since Plasma LP positions cannot be
+/// closed.
+pub fn estimated_exit_net_lamports(
+    instructions: &[Instruction],
+    rent: &Rent,
+    lamports_per_signature: u64,
+) -> i64 {
+    let accounts_closed = instructions
+        .iter()
+        .filter(|ix| {
+            ix.program_id == spl_token::ID && ix.data.first() == Some(&CLOSE_TOKEN_ACCOUNT_TAG)
+        })
+        .count() as i128;
+    let rent_reclaimed = accounts_closed * rent.minimum_balance(TOKEN_ACCOUNT_LEN as usize) as i128;
+
+    let mut signers = instructions
+        .iter()
+        .flat_map(|ix| ix.accounts.iter())
+        .filter(|meta| meta.is_signer)
+        .map(|meta| meta.pubkey)
+        .collect::<Vec<_>>();
+    signers.sort();
+    signers.dedup();
+    // The fee payer always signs, even if no instruction requires a signature
+    let signature_fees = signers.len().max(1) as i128 * lamports_per_signature as i128;
+
+    (rent_reclaimed - signature_fees).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
 pub type SlotWindow = u64;
 
 #[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
@@ -65,3 +128,74 @@ impl SwapResult {
         }
     }
 }
+
+#[test]
+fn test_total_pool_creation_cost() {
+    let rent = Rent::default();
+    assert_eq!(
+        total_pool_creation_cost(&rent),
+        rent.minimum_balance(624) + rent.minimum_balance(165) + rent.minimum_balance(165)
+    );
+}
+
+#[test]
+fn test_estimated_exit_net_lamports() {
+    use solana_program::instruction::AccountMeta;
+
+    let pool = Pubkey::new_unique();
+    let trader = Pubkey::new_unique();
+    let base_mint = Pubkey::new_unique();
+    let quote_mint = Pubkey::new_unique();
+    let base_account = Pubkey::new_unique();
+    let quote_account = Pubkey::new_unique();
+    let close_account = |account: &Pubkey| Instruction {
+        program_id: spl_token::ID,
+        accounts: vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new(trader, false),
+            AccountMeta::new_readonly(trader, true),
+        ],
+        data: vec![CLOSE_TOKEN_ACCOUNT_TAG],
+    };
+    let exit = vec![
+        instructions::remove_liquidity(
+            &pool,
+            &trader,
+            &base_mint,
+            &quote_mint,
+            &base_account,
+            &quote_account,
+            1_000,
+        ),
+        close_account(&base_account),
+        close_account(&quote_account),
+    ];
+
+    let rent = Rent::default();
+    let net = estimated_exit_net_lamports(&exit, &rent, 5_000);
+    // The trader is the only signer
+    assert_eq!(
+        net,
+        2 * rent.minimum_balance(TOKEN_ACCOUNT_LEN as usize) as i64 - 5_000
+    );
+    assert!(net > 0);
+
+    // Without closing any accounts the exit only costs fees
+    assert_eq!(
+        estimated_exit_net_lamports(&exit[..1], &rent, 5_000),
+        -5_000
+    );
+}
+
+#[test]
+fn test_plasma_result_alias() {
+    use crate::errors::{PlasmaError, PlasmaResult};
+
+    fn parse(data: &[u8]) -> PlasmaResult<u8> {
+        data.first()
+            .copied()
+            .ok_or(PlasmaError::MissingExpectedArgument)
+    }
+    assert_eq!(parse(&[7]), Ok(7));
+    assert_eq!(parse(&[]), Err(PlasmaError::MissingExpectedArgument));
+}
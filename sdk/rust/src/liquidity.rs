@@ -0,0 +1,204 @@
+use plasma_amm_state::integer_sqrt;
+
+use crate::{amm::BPS_BASE, fixed::I80F48, price::PriceScale};
+
+/// Computes the token amounts for the first deposit into a pool so that it is seeded at
+/// `desired_price`, returned as `(base_amount, quote_amount, initial_lp_shares)`.
+///
+/// `desired_price` is denominated in quote tokens per base token, in whole (not atomic) units,
+/// and is converted with the pool's `scale`. The quote amount is rounded down.
+///
+/// `initial_lp_shares` is the geometric mean of the two amounts, `floor(sqrt(base * quote))`.
+/// This is the only value the pool accepts on the first deposit, since it requires
+/// `initial_lp_shares^2 <= base * quote < (initial_lp_shares + 1)^2`.
+///
+/// # Panics
+///
+/// Panics if `desired_price` is negative or if the quote amount does not fit in a u64.
+pub fn bootstrap_deposit(
+    desired_price: I80F48,
+    base_amount: u64,
+    scale: &PriceScale,
+) -> (u64, u64, u64) {
+    assert!(desired_price >= I80F48::ZERO, "price must be positive");
+    let quote_amount = scale
+        .quote_amount_at_price(desired_price, base_amount)
+        .expect("quote amount overflows u64");
+
+    let initial_lp_shares = integer_sqrt(base_amount as u128 * quote_amount as u128) as u64;
+
+    (base_amount, quote_amount, initial_lp_shares)
+}
+
+/// Returns the number of slots an LP should wait between fee claims so that the fees accrued
+/// over the interval cover the cost of a claim, i.e. `ceil(claim_cost_quote / fee_accrual_per_slot)`
+/// with a minimum of one slot. Both amounts are in atomic quote.
+///
+/// Returns `None` if fees do not accrue (so no interval justifies a claim) or if the interval does
+/// not fit in a u64.
+pub fn optimal_claim_interval_slots(
+    fee_accrual_per_slot: I80F48,
+    claim_cost_quote: u64,
+) -> Option<u64> {
+    if fee_accrual_per_slot <= I80F48::ZERO {
+        return None;
+    }
+    let accrual_bits = fee_accrual_per_slot.to_bits() as u128;
+    let cost_bits = (claim_cost_quote as u128) << 48;
+    let interval = cost_bits.div_ceil(accrual_bits).max(1);
+    u64::try_from(interval).ok()
+}
+
+/// Returns the number of swaps paying `avg_swap_fee_quote` in LP fees each that it takes for the
+/// fees to cover `creation_cost_quote`, i.e. `ceil(creation_cost_quote / avg_swap_fee_quote)`.
+/// Both amounts are in atomic quote.
+///
+/// Returns `u64::MAX` if swaps pay no fees, since the cost is never recovered.
+pub fn swaps_to_breakeven(creation_cost_quote: u64, avg_swap_fee_quote: u64) -> u64 {
+    if avg_swap_fee_quote == 0 {
+        return u64::MAX;
+    }
+    creation_cost_quote.div_ceil(avg_swap_fee_quote)
+}
+
+/// Returns the `(base, quote)` reserves a pool at `spot_price` needs so that a swap of
+/// `trade_size_quote` moves its average execution price by at most `max_slippage_bps`, ignoring
+/// fees. `spot_price` is in atomic quote per atomic base.
+///
+/// Buying base with `trade_size_quote` quote on a constant-product curve executes at
+/// `spot_price * (1 + trade_size_quote / quote_reserves)`, so the quote reserves must be at least
+/// `trade_size_quote * 10_000 / max_slippage_bps`. Selling the same value of base slips less, so
+/// these reserves bound both sides. Both amounts are rounded up.
+///
+/// Amounts that do not fit in a u64, e.g. because `max_slippage_bps` or `spot_price` is zero,
+/// saturate at `u64::MAX`.
+pub fn min_reserves_for_slippage(
+    trade_size_quote: u64,
+    max_slippage_bps: u16,
+    spot_price: I80F48,
+) -> (u64, u64) {
+    let quote_reserves = if max_slippage_bps > 0 {
+        (trade_size_quote as u128 * BPS_BASE).div_ceil(max_slippage_bps as u128)
+    } else {
+        u128::MAX
+    };
+    let quote_reserves = u64::try_from(quote_reserves).unwrap_or(u64::MAX);
+    let base_reserves = if spot_price > I80F48::ZERO {
+        ((quote_reserves as u128) << 48).div_ceil(spot_price.to_bits() as u128)
+    } else {
+        u128::MAX
+    };
+    (
+        u64::try_from(base_reserves).unwrap_or(u64::MAX),
+        quote_reserves,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+
+    use super::*;
+    use crate::{accounts::PoolHeader, amm::Amm, quote::price_impact_bps};
+
+    #[test]
+    fn test_bootstrap_deposit() {
+        for (price, base_decimals, quote_decimals) in [
+            (I80F48::from_fraction(51, 2), 9, 6),
+            (I80F48::from_num(150), 9, 6),
+            (I80F48::from_fraction(1, 1000), 6, 9),
+            (I80F48::from_num(1), 6, 6),
+        ] {
+            let mut header = PoolHeader::zeroed();
+            header.base_params.decimals = base_decimals;
+            header.quote_params.decimals = quote_decimals;
+            let scale = PriceScale::from_pool(&header);
+
+            let base_amount = 1_000 * 10_u64.pow(base_decimals);
+            let (base, quote, lp_shares) = bootstrap_deposit(price, base_amount, &scale);
+            assert_eq!(base, base_amount);
+
+            let mut amm = Amm::new(30, 0, 0, 0);
+            amm.mint(0, base, quote, Some(lp_shares)).unwrap();
+
+            // Convert the pool reserves back into a price in whole units
+            let pool_price = (amm.quote_reserves as f64 / 10_f64.powi(quote_decimals as i32))
+                / (amm.base_reserves as f64 / 10_f64.powi(base_decimals as i32));
+            let expected_price = price.to_bits() as f64 / (1_u64 << 48) as f64;
+            assert!((pool_price - expected_price).abs() / expected_price < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_optimal_claim_interval_slots() {
+        // A busy pool accruing 500 quote per slot covers a 5_000 quote claim in 10 slots
+        assert_eq!(
+            optimal_claim_interval_slots(I80F48::from_num(500), 5_000),
+            Some(10)
+        );
+        // A quiet pool accruing a quarter of a quote per slot needs 20_000 slots
+        assert_eq!(
+            optimal_claim_interval_slots(I80F48::from_fraction(1, 4), 5_000),
+            Some(20_000)
+        );
+        // A partial slot rounds up
+        assert_eq!(
+            optimal_claim_interval_slots(I80F48::from_num(3), 10),
+            Some(4)
+        );
+        // Free claims are worthwhile every slot
+        assert_eq!(
+            optimal_claim_interval_slots(I80F48::from_num(500), 0),
+            Some(1)
+        );
+
+        assert_eq!(optimal_claim_interval_slots(I80F48::ZERO, 5_000), None);
+        assert_eq!(
+            optimal_claim_interval_slots(I80F48::from_bits(1), u64::MAX),
+            None
+        );
+    }
+
+    #[test]
+    fn test_swaps_to_breakeven() {
+        // 0.03 SOL of rent worth 4.5 USDC, recovered by swaps paying 0.03 USDC each
+        assert_eq!(swaps_to_breakeven(4_500_000, 30_000), 150);
+        // A partial swap rounds up
+        assert_eq!(swaps_to_breakeven(4_500_001, 30_000), 151);
+        assert_eq!(swaps_to_breakeven(0, 30_000), 0);
+        assert_eq!(swaps_to_breakeven(4_500_000, 0), u64::MAX);
+    }
+
+    #[test]
+    fn test_min_reserves_for_slippage() {
+        let price = I80F48::from_num(4);
+        // A 100 quote trade moves the price by 1% against 10_000 quote of reserves
+        assert_eq!(min_reserves_for_slippage(100, 100, price), (2_500, 10_000));
+
+        let mut last_quote_reserves = 0;
+        for max_slippage_bps in [1_000, 100, 10, 1] {
+            let (base_reserves, quote_reserves) =
+                min_reserves_for_slippage(1_000_000, max_slippage_bps, price);
+            // Tighter slippage needs deeper reserves
+            assert!(quote_reserves > last_quote_reserves);
+            last_quote_reserves = quote_reserves;
+
+            // A buy of the trade size against these reserves stays within the bound
+            let mut amm = Amm::new(0, 0, 0, 0);
+            amm.base_reserves = base_reserves;
+            amm.quote_reserves = quote_reserves;
+            amm.base_reserves_snapshot = base_reserves;
+            amm.quote_reserves_snapshot = quote_reserves;
+            amm.total_lp_shares = 1;
+            let swap_result = amm.buy_exact_in(0, 1_000_000).unwrap();
+            assert!(price_impact_bps(&amm, &swap_result) <= max_slippage_bps as u64);
+        }
+
+        // Zero slippage is unreachable
+        assert_eq!(min_reserves_for_slippage(100, 0, price).1, u64::MAX);
+        assert_eq!(
+            min_reserves_for_slippage(100, 100, I80F48::ZERO),
+            (u64::MAX, 10_000)
+        );
+    }
+}
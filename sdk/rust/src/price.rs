@@ -0,0 +1,125 @@
+use crate::{accounts::PoolHeader, fixed::I80F48};
+
+const FRACTIONAL_BITS: u32 = 48;
+
+/// Converts between prices in whole units (quote tokens per base token) and atomic token amounts.
+///
+/// A `PriceScale` can only be built from a pool header, so the base and quote decimals always
+/// come from the pool's own token params and cannot be swapped by the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceScale {
+    base_decimals: u32,
+    quote_decimals: u32,
+}
+
+impl PriceScale {
+    pub fn from_pool(header: &PoolHeader) -> Self {
+        Self {
+            base_decimals: header.base_params.decimals,
+            quote_decimals: header.quote_params.decimals,
+        }
+    }
+
+    pub fn base_decimals(&self) -> u32 {
+        self.base_decimals
+    }
+
+    pub fn quote_decimals(&self) -> u32 {
+        self.quote_decimals
+    }
+
+    /// Returns the atomic quote amount that `base_amount` atomic base tokens are worth at `price`,
+    /// rounded down. Returns `None` if the price is negative or the result does not fit in a u64.
+    pub fn quote_amount_at_price(&self, price: I80F48, base_amount: u64) -> Option<u64> {
+        let price_bits = u128::try_from(price.to_bits()).ok()?;
+        let price_integer = price_bits >> FRACTIONAL_BITS;
+        let price_fraction = price_bits & ((1 << FRACTIONAL_BITS) - 1);
+
+        // Scale up before applying the price so that no precision is lost to the decimal adjustment
+        let (base_scaled, divisor) = if self.quote_decimals >= self.base_decimals {
+            (
+                (base_amount as u128)
+                    .checked_mul(10_u128.checked_pow(self.quote_decimals - self.base_decimals)?)?,
+                1,
+            )
+        } else {
+            (
+                base_amount as u128,
+                10_u128.checked_pow(self.base_decimals - self.quote_decimals)?,
+            )
+        };
+
+        base_scaled
+            .checked_mul(price_integer)
+            .zip(base_scaled.checked_mul(price_fraction))
+            .and_then(|(integer, fraction)| integer.checked_add(fraction >> FRACTIONAL_BITS))
+            .map(|quote_amount| quote_amount / divisor)
+            .and_then(|quote_amount| u64::try_from(quote_amount).ok())
+    }
+
+    /// Returns the price in whole units implied by exchanging `base_amount` atomic base tokens for
+    /// `quote_amount` atomic quote tokens, rounded down. Returns `None` if `base_amount` is zero or
+    /// the price cannot be represented.
+    pub fn price_from_amounts(&self, base_amount: u64, quote_amount: u64) -> Option<I80F48> {
+        if base_amount == 0 {
+            return None;
+        }
+        let (numerator_scale, denominator_scale) = if self.base_decimals >= self.quote_decimals {
+            (
+                10_u128.checked_pow(self.base_decimals - self.quote_decimals)?,
+                1,
+            )
+        } else {
+            (
+                1,
+                10_u128.checked_pow(self.quote_decimals - self.base_decimals)?,
+            )
+        };
+        let numerator = ((quote_amount as u128) << FRACTIONAL_BITS).checked_mul(numerator_scale)?;
+        let denominator = (base_amount as u128).checked_mul(denominator_scale)?;
+        i128::try_from(numerator / denominator)
+            .ok()
+            .map(I80F48::from_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+
+    use super::*;
+
+    fn header_with_decimals(base_decimals: u32, quote_decimals: u32) -> PoolHeader {
+        let mut header = PoolHeader::zeroed();
+        header.base_params.decimals = base_decimals;
+        header.quote_params.decimals = quote_decimals;
+        header
+    }
+
+    #[test]
+    fn test_price_scale_from_pool() {
+        // e.g. SOL/USDC
+        let scale = PriceScale::from_pool(&header_with_decimals(9, 6));
+        assert_eq!(scale.base_decimals(), 9);
+        assert_eq!(scale.quote_decimals(), 6);
+
+        // 1 SOL at 150 USDC is 150_000_000 atomic USDC
+        let price = I80F48::from_num(150);
+        assert_eq!(
+            scale.quote_amount_at_price(price, 1_000_000_000),
+            Some(150_000_000)
+        );
+        assert_eq!(
+            scale.price_from_amounts(1_000_000_000, 150_000_000),
+            Some(price)
+        );
+
+        // The decimals are not interchangeable
+        let inverted = PriceScale::from_pool(&header_with_decimals(6, 9));
+        assert_eq!(
+            inverted.quote_amount_at_price(price, 1_000_000),
+            Some(150_000_000_000)
+        );
+        assert_eq!(scale.price_from_amounts(0, 1), None);
+    }
+}
@@ -0,0 +1,358 @@
+use crate::{
+    amm::{Amm, Side, SwapResult, BPS_BASE},
+    errors::{PlasmaError, PlasmaResult},
+    instructions::{SwapParams, SwapType},
+    SlotWindow,
+};
+use plasma_amm_state::integer_sqrt;
+
+/// Executes a swap against the pool at the given slot, updating its state. Slippage limits in
+/// `params` are not enforced.
+///
+/// Returns `UninitializedPool` if the pool has no liquidity.
+pub fn apply_swap(amm: &mut Amm, slot: u64, params: SwapParams) -> PlasmaResult<SwapResult> {
+    if !amm.has_liquidity() {
+        return Err(PlasmaError::UninitializedPool);
+    }
+    let swap_result = match (params.side, params.swap_type) {
+        (Side::Buy, SwapType::ExactIn { amount_in, .. }) => amm.buy_exact_in(slot, amount_in),
+        (Side::Buy, SwapType::ExactOut { amount_out, .. }) => amm.buy_exact_out(slot, amount_out),
+        (Side::Sell, SwapType::ExactIn { amount_in, .. }) => amm.sell_exact_in(slot, amount_in),
+        (Side::Sell, SwapType::ExactOut { amount_out, .. }) => amm.sell_exact_out(slot, amount_out),
+    }?;
+    Ok(swap_result)
+}
+
+/// Quotes a swap against the pool at the given slot without mutating it. Slippage limits in
+/// `params` are not enforced.
+pub fn quote_swap_at_slot(amm: &Amm, slot: u64, params: SwapParams) -> PlasmaResult<SwapResult> {
+    let mut amm = *amm;
+    apply_swap(&mut amm, slot, params)
+}
+
+/// Quoting methods on `Amm` that take SDK instruction params. `Amm` is defined in the state crate,
+/// which does not know about `SwapParams`, so these are provided as an extension trait.
+pub trait QuoteSwap {
+    /// Runs the same matching as the on-chain `Swap` at `slot` without mutating the pool, and
+    /// returns the full `SwapResult`, including the fee and the split between the virtual limit
+    /// order and the curve. Errors such as `SwapExactInTooLarge` and `SwapExactOutTooLarge` are
+    /// returned as the program would raise them. Slippage limits in `params` are not enforced.
+    fn quote_swap(&self, slot: SlotWindow, params: SwapParams) -> PlasmaResult<SwapResult>;
+}
+
+impl QuoteSwap for Amm {
+    fn quote_swap(&self, slot: SlotWindow, params: SwapParams) -> PlasmaResult<SwapResult> {
+        quote_swap_at_slot(self, slot, params)
+    }
+}
+
+/// Returns the `(base, quote)` reserves the pool would be left with after the swap, without
+/// mutating it. Routers can check these against their own minimum-reserve policy before
+/// submitting a swap.
+pub fn remaining_reserves_after(amm: &Amm, params: SwapParams) -> PlasmaResult<(u64, u64)> {
+    let mut amm = *amm;
+    let slot = amm.get_slot();
+    apply_swap(&mut amm, slot, params)?;
+    Ok((amm.base_reserves, amm.quote_reserves))
+}
+
+/// Returns how far the average execution price of a swap (excluding fees) deviates from the
+/// pool's price before the swap, in basis points. Saturates at `u64::MAX`.
+pub fn price_impact_bps(amm: &Amm, swap_result: &SwapResult) -> u64 {
+    let base_traded = swap_result.base_amount_to_transfer as u128;
+    let quote_traded = match swap_result.side {
+        Side::Buy => swap_result.quote_amount_to_transfer - swap_result.fee_in_quote,
+        Side::Sell => swap_result.quote_amount_to_transfer + swap_result.fee_in_quote,
+    } as u128;
+    if base_traded == 0 || amm.base_reserves == 0 || amm.quote_reserves == 0 {
+        return 0;
+    }
+    // |quote_traded / base_traded - quote_reserves / base_reserves| / (quote_reserves / base_reserves)
+    quote_traded
+        .checked_mul(amm.base_reserves as u128)
+        .zip(base_traded.checked_mul(amm.quote_reserves as u128))
+        .and_then(|(execution, mid)| {
+            execution
+                .abs_diff(mid)
+                .checked_mul(BPS_BASE)
+                .map(|deviation| deviation / mid)
+        })
+        .and_then(|impact| u64::try_from(impact).ok())
+        .unwrap_or(u64::MAX)
+}
+
+/// Quotes a swap against hypothetical reserves without needing a real pool.
+///
+/// A synthetic `Amm` is built with the snapshot set to the given reserves, so no virtual
+/// limit order is active and the swap goes entirely through the curve. Slippage limits in
+/// `params` are not enforced.
+pub fn quote_swap_with_reserves(
+    base_reserves: u64,
+    quote_reserves: u64,
+    total_lp_shares: u64,
+    lp_fee_bps: u32,
+    params: SwapParams,
+) -> PlasmaResult<SwapResult> {
+    let mut amm = Amm::new(lp_fee_bps, 0, 0, 0);
+    amm.base_reserves = base_reserves;
+    amm.quote_reserves = quote_reserves;
+    amm.base_reserves_snapshot = base_reserves;
+    amm.quote_reserves_snapshot = quote_reserves;
+    amm.total_lp_shares = total_lp_shares;
+
+    quote_swap_at_slot(&amm, amm.get_slot(), params)
+}
+
+/// Returns the trade on `pool_a` that brings both pools to a common price when an arbitrageur
+/// trades the same base amount on opposite sides of `pool_b`: buying base on the cheaper pool
+/// and selling it into the other. The amount is the base traded on each pool, and the side is
+/// the side of the trade on `pool_a`. Pools that already agree on price return `(Buy, 0)`.
+///
+/// This is an idealized computation: both pools are treated as pure constant-product curves at
+/// their current reserves, so fees and the virtual limit order are ignored and the result is
+/// only a starting point for sizing an arbitrage.
+///
+/// Returns `UninitializedPool` if either pool has no liquidity.
+pub fn equalizing_swap(pool_a: &Amm, pool_b: &Amm) -> PlasmaResult<(Side, u64)> {
+    if !pool_a.has_liquidity() || !pool_b.has_liquidity() {
+        return Err(PlasmaError::UninitializedPool);
+    }
+    let sqrt_k = |amm: &Amm| integer_sqrt(amm.base_reserves as u128 * amm.quote_reserves as u128);
+    let (sqrt_k_a, sqrt_k_b) = (sqrt_k(pool_a), sqrt_k(pool_b));
+    // The price of a pool is k / base^2, so trading `dx` base on both pools equalizes the prices
+    // when sqrt(k_a) * (base_b ± dx) == sqrt(k_b) * (base_a ∓ dx)
+    let lhs = sqrt_k_a * pool_b.base_reserves as u128;
+    let rhs = sqrt_k_b * pool_a.base_reserves as u128;
+    let base_amount = lhs.abs_diff(rhs) / (sqrt_k_a + sqrt_k_b);
+    // Pool A is cheaper when lhs < rhs, so the arbitrage buys base from it
+    let side = if lhs <= rhs { Side::Buy } else { Side::Sell };
+    Ok((side, base_amount as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plasma_amm_state::test_utils::new_pool;
+
+    const RESERVES: [u64; 5] = [
+        10_000_000,
+        100_000_000,
+        1_000_000_000,
+        10_000_000_000,
+        100_000_000_000,
+    ];
+
+    fn exact_in(side: Side, amount_in: u64) -> SwapParams {
+        SwapParams {
+            side,
+            swap_type: SwapType::ExactIn {
+                amount_in,
+                min_amount_out: 0,
+            },
+            deadline_slot: None,
+        }
+    }
+
+    #[test]
+    fn test_quote_swap_with_reserves_monotonic_in_reserves() {
+        for quote_reserves in RESERVES {
+            let mut last_base_out = 0;
+            for base_reserves in RESERVES {
+                // More base in the pool means a lower price, so buying yields more base
+                let result = quote_swap_with_reserves(
+                    base_reserves,
+                    quote_reserves,
+                    1,
+                    30,
+                    exact_in(Side::Buy, 1_000_000),
+                )
+                .unwrap();
+                assert!(result.base_amount_to_transfer >= last_base_out);
+                last_base_out = result.base_amount_to_transfer;
+            }
+        }
+
+        for base_reserves in RESERVES {
+            let mut last_quote_out = 0;
+            for quote_reserves in RESERVES {
+                // More quote in the pool means a higher price, so selling yields more quote
+                let result = quote_swap_with_reserves(
+                    base_reserves,
+                    quote_reserves,
+                    1,
+                    30,
+                    exact_in(Side::Sell, 1_000_000),
+                )
+                .unwrap();
+                assert!(result.quote_amount_to_transfer >= last_quote_out);
+                last_quote_out = result.quote_amount_to_transfer;
+            }
+        }
+    }
+
+    #[test]
+    fn test_quote_swap_with_reserves_monotonic_in_size() {
+        for reserves in RESERVES {
+            // The average price paid never improves as the trade gets larger
+            let mut last_price = 0.0;
+            for amount_in in [reserves / 1000, reserves / 100, reserves / 10, reserves] {
+                let result = quote_swap_with_reserves(
+                    reserves,
+                    reserves,
+                    1,
+                    30,
+                    exact_in(Side::Buy, amount_in),
+                )
+                .unwrap();
+                let price =
+                    result.quote_amount_to_transfer as f64 / result.base_amount_to_transfer as f64;
+                assert!(price >= last_price);
+                last_price = price;
+            }
+        }
+    }
+
+    #[test]
+    fn test_quote_swap_with_reserves_uninitialized() {
+        assert_eq!(
+            quote_swap_with_reserves(1_000, 1_000, 0, 30, exact_in(Side::Buy, 10)).unwrap_err(),
+            PlasmaError::UninitializedPool
+        );
+    }
+
+    #[test]
+    fn test_quote_swap_on_empty_pool() {
+        let mut amm = Amm::new(30, 0, 0, 0);
+        for params in [exact_in(Side::Buy, 10), exact_in(Side::Sell, 10)] {
+            assert_eq!(
+                quote_swap_at_slot(&amm, 0, params).unwrap_err(),
+                PlasmaError::UninitializedPool
+            );
+        }
+
+        // Shares without reserves are not liquidity either
+        amm.total_lp_shares = 1_000;
+        amm.base_reserves = 1_000;
+        assert_eq!(
+            apply_swap(&mut amm, 0, exact_in(Side::Buy, 10)).unwrap_err(),
+            PlasmaError::UninitializedPool
+        );
+        assert_eq!(
+            remaining_reserves_after(&amm, exact_in(Side::Sell, 10)).unwrap_err(),
+            PlasmaError::UninitializedPool
+        );
+    }
+
+    #[test]
+    fn test_price_impact_bps() {
+        let amm = new_pool(30);
+        let small = quote_swap_at_slot(&amm, 0, exact_in(Side::Buy, 1_000_000)).unwrap();
+        let large = quote_swap_at_slot(&amm, 0, exact_in(Side::Sell, 500_000_000)).unwrap();
+        // A swap of 0.1% of the reserves moves the price by roughly 0.1%
+        assert!(price_impact_bps(&amm, &small).abs_diff(10) <= 1);
+        // Selling half of the reserves executes at about 2/3 of the price
+        assert!(price_impact_bps(&amm, &large).abs_diff(3333) <= 1);
+    }
+
+    #[test]
+    fn test_remaining_reserves_after() {
+        let amm = new_pool(30);
+
+        // A small buy barely moves the reserves
+        let (base, quote) = remaining_reserves_after(&amm, exact_in(Side::Buy, 1_000)).unwrap();
+        assert!(base < amm.base_reserves && base > amm.base_reserves - 1_000);
+        assert!(quote > amm.quote_reserves && quote <= amm.quote_reserves + 1_000);
+
+        // A large buy drains most of the base reserves
+        let (base, quote) =
+            remaining_reserves_after(&amm, exact_in(Side::Buy, 99_000_000_000)).unwrap();
+        assert!(base < amm.base_reserves / 50);
+        assert!(quote > amm.quote_reserves * 50);
+
+        // The pool itself is untouched
+        assert_eq!(amm.base_reserves, 1_000_000_000);
+        assert_eq!(amm.quote_reserves, 1_000_000_000);
+    }
+
+    #[test]
+    fn test_equalizing_swap() {
+        let pool = |base_reserves: u64, quote_reserves: u64| {
+            let mut amm = Amm::new(30, 0, 0, 0);
+            amm.base_reserves = base_reserves;
+            amm.quote_reserves = quote_reserves;
+            amm.total_lp_shares = 1_000_000_000;
+            amm
+        };
+        // Pool A trades at a price of 1 and pool B at a price of 4
+        let pool_a = pool(1_000_000_000, 1_000_000_000);
+        let pool_b = pool(1_000_000_000, 4_000_000_000);
+
+        let (side, base_amount) = equalizing_swap(&pool_a, &pool_b).unwrap();
+        assert_eq!(side, Side::Buy);
+        assert_eq!(base_amount, 333_333_333);
+        // Both pools end up at a price of 2.25
+        let price_after = |amm: &Amm, base_reserves: u64| {
+            let k = amm.base_reserves as u128 * amm.quote_reserves as u128;
+            k as f64 / (base_reserves as f64 * base_reserves as f64)
+        };
+        let price_a = price_after(&pool_a, 1_000_000_000 - base_amount);
+        let price_b = price_after(&pool_b, 1_000_000_000 + base_amount);
+        assert!((price_a - 2.25).abs() < 1e-6);
+        assert!((price_b - 2.25).abs() < 1e-6);
+
+        // From pool B's side the arbitrage sells the same amount into it
+        assert_eq!(
+            equalizing_swap(&pool_b, &pool_a).unwrap(),
+            (Side::Sell, base_amount)
+        );
+        assert_eq!(equalizing_swap(&pool_a, &pool_a).unwrap(), (Side::Buy, 0));
+        assert_eq!(
+            equalizing_swap(&pool_a, &Amm::new(30, 0, 0, 0)).unwrap_err(),
+            PlasmaError::UninitializedPool
+        );
+    }
+
+    #[test]
+    fn test_amm_quote_swap() {
+        let amm = new_pool(30);
+        let before = amm.base_reserves;
+
+        let result = amm.quote_swap(4, exact_in(Side::Buy, 10_000_000)).unwrap();
+        assert_eq!(result.quote_amount_to_transfer, 10_000_000);
+        assert!(result.fee_in_quote > 0);
+        assert_eq!(
+            result.base_amount_to_transfer,
+            result.base_matched_as_limit_order + result.base_matched_as_swap
+        );
+        // The pool is not modified
+        assert_eq!(amm.base_reserves, before);
+        let executed = amm.clone().buy_exact_in(4, 10_000_000).unwrap();
+        assert_eq!(
+            result.base_amount_to_transfer,
+            executed.base_amount_to_transfer
+        );
+        assert_eq!(result.fee_in_quote, executed.fee_in_quote);
+
+        let exact_out = |side, amount_out| SwapParams {
+            side,
+            swap_type: SwapType::ExactOut {
+                amount_out,
+                max_amount_in: u64::MAX,
+            },
+            deadline_slot: None,
+        };
+        let result = amm.quote_swap(4, exact_out(Side::Sell, 5_000_000)).unwrap();
+        assert_eq!(result.quote_amount_to_transfer, 5_000_000);
+
+        assert_eq!(
+            amm.quote_swap(4, exact_out(Side::Buy, 2_000_000_000))
+                .unwrap_err(),
+            PlasmaError::SwapExactOutTooLarge
+        );
+        assert_eq!(
+            amm.quote_swap(4, exact_in(Side::Sell, u64::MAX))
+                .unwrap_err(),
+            PlasmaError::SwapExactInTooLarge
+        );
+    }
+}
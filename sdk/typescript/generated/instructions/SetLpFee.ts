@@ -0,0 +1,46 @@
+import { TransactionInstruction, PublicKey, AccountMeta } from "@solana/web3.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import BN from "bn.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as borsh from "@coral-xyz/borsh" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as types from "../types" // eslint-disable-line @typescript-eslint/no-unused-vars
+import { PROGRAM_ID } from "../programId"
+
+export interface SetLpFeeArgs {
+  params: types.SetLpFeeIxParamsFields
+}
+
+export interface SetLpFeeAccounts {
+  /** Plasma program */
+  plasmaProgram: PublicKey
+  /** Plasma log authority */
+  logAuthority: PublicKey
+  /** This account holds the pool state */
+  pool: PublicKey
+  /** The authority of the pool */
+  poolAuthority: PublicKey
+}
+
+export const layout = borsh.struct([types.SetLpFeeIxParams.layout("params")])
+
+export function SetLpFee(
+  args: SetLpFeeArgs,
+  accounts: SetLpFeeAccounts,
+  programId: PublicKey = PROGRAM_ID
+) {
+  const keys: Array<AccountMeta> = [
+    { pubkey: accounts.plasmaProgram, isSigner: false, isWritable: false },
+    { pubkey: accounts.logAuthority, isSigner: false, isWritable: false },
+    { pubkey: accounts.pool, isSigner: false, isWritable: true },
+    { pubkey: accounts.poolAuthority, isSigner: true, isWritable: false },
+  ]
+  const identifier = Buffer.from([10])
+  const buffer = Buffer.alloc(1000)
+  const len = layout.encode(
+    {
+      params: types.SetLpFeeIxParams.toEncodable(args.params),
+    },
+    buffer
+  )
+  const data = Buffer.concat([identifier, buffer]).slice(0, 1 + len)
+  const ix = new TransactionInstruction({ keys, programId, data })
+  return ix
+}
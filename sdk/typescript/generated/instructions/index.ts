@@ -27,3 +27,15 @@ export { Log } from "./Log"
 export type { LogAccounts } from "./Log"
 export { TransferLiquidity } from "./TransferLiquidity"
 export type { TransferLiquidityAccounts } from "./TransferLiquidity"
+export { SetLpFee } from "./SetLpFee"
+export type { SetLpFeeArgs, SetLpFeeAccounts } from "./SetLpFee"
+export { BatchInitializeLpPositions } from "./BatchInitializeLpPositions"
+export type {
+  BatchInitializeLpPositionsAccounts,
+} from "./BatchInitializeLpPositions"
+export { CrankVesting } from "./CrankVesting"
+export type { CrankVestingAccounts } from "./CrankVesting"
+export { ReinvestProtocolFees } from "./ReinvestProtocolFees"
+export type { ReinvestProtocolFeesAccounts } from "./ReinvestProtocolFees"
+export { ReleaseVesting } from "./ReleaseVesting"
+export type { ReleaseVestingAccounts } from "./ReleaseVesting"
@@ -7,23 +7,30 @@ export interface AddLiquidityIxParamsFields {
   desiredBaseAmountIn: BN
   desiredQuoteAmountIn: BN
   initialLpShares: BN | null
+  expectedTransferFeeBps: types.TransferFeeBpsFields | null
 }
 
 export interface AddLiquidityIxParamsJSON {
   desiredBaseAmountIn: string
   desiredQuoteAmountIn: string
   initialLpShares: string | null
+  expectedTransferFeeBps: types.TransferFeeBpsJSON | null
 }
 
 export class AddLiquidityIxParams {
   readonly desiredBaseAmountIn: BN
   readonly desiredQuoteAmountIn: BN
   readonly initialLpShares: BN | null
+  readonly expectedTransferFeeBps: types.TransferFeeBps | null
 
   constructor(fields: AddLiquidityIxParamsFields) {
     this.desiredBaseAmountIn = fields.desiredBaseAmountIn
     this.desiredQuoteAmountIn = fields.desiredQuoteAmountIn
     this.initialLpShares = fields.initialLpShares
+    this.expectedTransferFeeBps =
+      (fields.expectedTransferFeeBps &&
+        new types.TransferFeeBps({ ...fields.expectedTransferFeeBps })) ||
+      null
   }
 
   static layout(property?: string) {
@@ -32,6 +39,7 @@ export class AddLiquidityIxParams {
         borsh.u64("desiredBaseAmountIn"),
         borsh.u64("desiredQuoteAmountIn"),
         borsh.option(borsh.u64(), "initialLpShares"),
+        borsh.option(types.TransferFeeBps.layout(), "expectedTransferFeeBps"),
       ],
       property
     )
@@ -43,6 +51,10 @@ export class AddLiquidityIxParams {
       desiredBaseAmountIn: obj.desiredBaseAmountIn,
       desiredQuoteAmountIn: obj.desiredQuoteAmountIn,
       initialLpShares: obj.initialLpShares,
+      expectedTransferFeeBps:
+        (obj.expectedTransferFeeBps &&
+          types.TransferFeeBps.fromDecoded(obj.expectedTransferFeeBps)) ||
+        null,
     })
   }
 
@@ -51,6 +63,10 @@ export class AddLiquidityIxParams {
       desiredBaseAmountIn: fields.desiredBaseAmountIn,
       desiredQuoteAmountIn: fields.desiredQuoteAmountIn,
       initialLpShares: fields.initialLpShares,
+      expectedTransferFeeBps:
+        (fields.expectedTransferFeeBps &&
+          types.TransferFeeBps.toEncodable(fields.expectedTransferFeeBps)) ||
+        null,
     }
   }
 
@@ -60,6 +76,9 @@ export class AddLiquidityIxParams {
       desiredQuoteAmountIn: this.desiredQuoteAmountIn.toString(),
       initialLpShares:
         (this.initialLpShares && this.initialLpShares.toString()) || null,
+      expectedTransferFeeBps:
+        (this.expectedTransferFeeBps && this.expectedTransferFeeBps.toJSON()) ||
+        null,
     }
   }
 
@@ -69,6 +88,10 @@ export class AddLiquidityIxParams {
       desiredQuoteAmountIn: new BN(obj.desiredQuoteAmountIn),
       initialLpShares:
         (obj.initialLpShares && new BN(obj.initialLpShares)) || null,
+      expectedTransferFeeBps:
+        (obj.expectedTransferFeeBps &&
+          types.TransferFeeBps.fromJSON(obj.expectedTransferFeeBps)) ||
+        null,
     })
   }
 
@@ -6,6 +6,7 @@ import * as borsh from "@coral-xyz/borsh"
 export interface AmmFields {
   feeInBps: number
   protocolAllocationInPct: number
+  protocolAllocationInBps: number
   lpVestingWindow: BN
   rewardFactor: BN
   totalLpShares: BN
@@ -21,6 +22,7 @@ export interface AmmFields {
 export interface AmmJSON {
   feeInBps: number
   protocolAllocationInPct: number
+  protocolAllocationInBps: number
   lpVestingWindow: string
   rewardFactor: string
   totalLpShares: string
@@ -36,6 +38,7 @@ export interface AmmJSON {
 export class Amm {
   readonly feeInBps: number
   readonly protocolAllocationInPct: number
+  readonly protocolAllocationInBps: number
   readonly lpVestingWindow: BN
   readonly rewardFactor: BN
   readonly totalLpShares: BN
@@ -50,6 +53,7 @@ export class Amm {
   constructor(fields: AmmFields) {
     this.feeInBps = fields.feeInBps
     this.protocolAllocationInPct = fields.protocolAllocationInPct
+    this.protocolAllocationInBps = fields.protocolAllocationInBps
     this.lpVestingWindow = fields.lpVestingWindow
     this.rewardFactor = fields.rewardFactor
     this.totalLpShares = fields.totalLpShares
@@ -66,7 +70,8 @@ export class Amm {
     return borsh.struct(
       [
         borsh.u32("feeInBps"),
-        borsh.u32("protocolAllocationInPct"),
+        borsh.u16("protocolAllocationInPct"),
+        borsh.u16("protocolAllocationInBps"),
         borsh.u64("lpVestingWindow"),
         borsh.u128("rewardFactor"),
         borsh.u64("totalLpShares"),
@@ -87,6 +92,7 @@ export class Amm {
     return new Amm({
       feeInBps: obj.feeInBps,
       protocolAllocationInPct: obj.protocolAllocationInPct,
+      protocolAllocationInBps: obj.protocolAllocationInBps,
       lpVestingWindow: obj.lpVestingWindow,
       rewardFactor: obj.rewardFactor,
       totalLpShares: obj.totalLpShares,
@@ -104,6 +110,7 @@ export class Amm {
     return {
       feeInBps: fields.feeInBps,
       protocolAllocationInPct: fields.protocolAllocationInPct,
+      protocolAllocationInBps: fields.protocolAllocationInBps,
       lpVestingWindow: fields.lpVestingWindow,
       rewardFactor: fields.rewardFactor,
       totalLpShares: fields.totalLpShares,
@@ -121,6 +128,7 @@ export class Amm {
     return {
       feeInBps: this.feeInBps,
       protocolAllocationInPct: this.protocolAllocationInPct,
+      protocolAllocationInBps: this.protocolAllocationInBps,
       lpVestingWindow: this.lpVestingWindow.toString(),
       rewardFactor: this.rewardFactor.toString(),
       totalLpShares: this.totalLpShares.toString(),
@@ -138,6 +146,7 @@ export class Amm {
     return new Amm({
       feeInBps: obj.feeInBps,
       protocolAllocationInPct: obj.protocolAllocationInPct,
+      protocolAllocationInBps: obj.protocolAllocationInBps,
       lpVestingWindow: new BN(obj.lpVestingWindow),
       rewardFactor: new BN(obj.rewardFactor),
       totalLpShares: new BN(obj.totalLpShares),
@@ -0,0 +1,55 @@
+import { PublicKey } from "@solana/web3.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import BN from "bn.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as types from "../types" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as borsh from "@coral-xyz/borsh"
+
+export interface BatchInitializeLpPositionsEventFields {
+  owners: Array<PublicKey>
+}
+
+export interface BatchInitializeLpPositionsEventJSON {
+  owners: Array<string>
+}
+
+export class BatchInitializeLpPositionsEvent {
+  readonly owners: Array<PublicKey>
+
+  constructor(fields: BatchInitializeLpPositionsEventFields) {
+    this.owners = fields.owners
+  }
+
+  static layout(property?: string) {
+    return borsh.struct([borsh.vec(borsh.publicKey(), "owners")], property)
+  }
+
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  static fromDecoded(obj: any) {
+    return new BatchInitializeLpPositionsEvent({
+      owners: obj.owners,
+    })
+  }
+
+  static toEncodable(fields: BatchInitializeLpPositionsEventFields) {
+    return {
+      owners: fields.owners,
+    }
+  }
+
+  toJSON(): BatchInitializeLpPositionsEventJSON {
+    return {
+      owners: this.owners.map((item) => item.toString()),
+    }
+  }
+
+  static fromJSON(
+    obj: BatchInitializeLpPositionsEventJSON
+  ): BatchInitializeLpPositionsEvent {
+    return new BatchInitializeLpPositionsEvent({
+      owners: obj.owners.map((item) => new PublicKey(item)),
+    })
+  }
+
+  toEncodable() {
+    return BatchInitializeLpPositionsEvent.toEncodable(this)
+  }
+}
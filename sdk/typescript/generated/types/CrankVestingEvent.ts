@@ -0,0 +1,64 @@
+import { PublicKey } from "@solana/web3.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import BN from "bn.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as types from "../types" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as borsh from "@coral-xyz/borsh"
+
+export interface CrankVestingEventFields {
+  owner: PublicKey
+  sharesVested: BN
+}
+
+export interface CrankVestingEventJSON {
+  owner: string
+  sharesVested: string
+}
+
+export class CrankVestingEvent {
+  readonly owner: PublicKey
+  readonly sharesVested: BN
+
+  constructor(fields: CrankVestingEventFields) {
+    this.owner = fields.owner
+    this.sharesVested = fields.sharesVested
+  }
+
+  static layout(property?: string) {
+    return borsh.struct(
+      [borsh.publicKey("owner"), borsh.u64("sharesVested")],
+      property
+    )
+  }
+
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  static fromDecoded(obj: any) {
+    return new CrankVestingEvent({
+      owner: obj.owner,
+      sharesVested: obj.sharesVested,
+    })
+  }
+
+  static toEncodable(fields: CrankVestingEventFields) {
+    return {
+      owner: fields.owner,
+      sharesVested: fields.sharesVested,
+    }
+  }
+
+  toJSON(): CrankVestingEventJSON {
+    return {
+      owner: this.owner.toString(),
+      sharesVested: this.sharesVested.toString(),
+    }
+  }
+
+  static fromJSON(obj: CrankVestingEventJSON): CrankVestingEvent {
+    return new CrankVestingEvent({
+      owner: new PublicKey(obj.owner),
+      sharesVested: new BN(obj.sharesVested),
+    })
+  }
+
+  toEncodable() {
+    return CrankVestingEvent.toEncodable(this)
+  }
+}
@@ -8,6 +8,9 @@ export interface InitializePoolIxParamsFields {
   protocolLpFeeAllocationInPct: BN
   feeRecipientsParams: Array<types.ProtocolFeeRecipientParamsFields>
   numSlotsToVestLpShares: BN | null
+  minQuoteDeposit: BN | null
+  maxQuoteTvl: BN | null
+  protocolLpFeeAllocationInBps: BN | null
 }
 
 export interface InitializePoolIxParamsJSON {
@@ -15,6 +18,9 @@ export interface InitializePoolIxParamsJSON {
   protocolLpFeeAllocationInPct: string
   feeRecipientsParams: Array<types.ProtocolFeeRecipientParamsJSON>
   numSlotsToVestLpShares: string | null
+  minQuoteDeposit: string | null
+  maxQuoteTvl: string | null
+  protocolLpFeeAllocationInBps: string | null
 }
 
 export class InitializePoolIxParams {
@@ -22,6 +28,9 @@ export class InitializePoolIxParams {
   readonly protocolLpFeeAllocationInPct: BN
   readonly feeRecipientsParams: Array<types.ProtocolFeeRecipientParams>
   readonly numSlotsToVestLpShares: BN | null
+  readonly minQuoteDeposit: BN | null
+  readonly maxQuoteTvl: BN | null
+  readonly protocolLpFeeAllocationInBps: BN | null
 
   constructor(fields: InitializePoolIxParamsFields) {
     this.lpFeeInBps = fields.lpFeeInBps
@@ -30,6 +39,9 @@ export class InitializePoolIxParams {
       (item) => new types.ProtocolFeeRecipientParams({ ...item })
     )
     this.numSlotsToVestLpShares = fields.numSlotsToVestLpShares
+    this.minQuoteDeposit = fields.minQuoteDeposit
+    this.maxQuoteTvl = fields.maxQuoteTvl
+    this.protocolLpFeeAllocationInBps = fields.protocolLpFeeAllocationInBps
   }
 
   static layout(property?: string) {
@@ -43,6 +55,9 @@ export class InitializePoolIxParams {
           "feeRecipientsParams"
         ),
         borsh.option(borsh.u64(), "numSlotsToVestLpShares"),
+        borsh.option(borsh.u64(), "minQuoteDeposit"),
+        borsh.option(borsh.u64(), "maxQuoteTvl"),
+        borsh.option(borsh.u64(), "protocolLpFeeAllocationInBps"),
       ],
       property
     )
@@ -59,6 +74,9 @@ export class InitializePoolIxParams {
         ) => types.ProtocolFeeRecipientParams.fromDecoded(item)
       ),
       numSlotsToVestLpShares: obj.numSlotsToVestLpShares,
+      minQuoteDeposit: obj.minQuoteDeposit,
+      maxQuoteTvl: obj.maxQuoteTvl,
+      protocolLpFeeAllocationInBps: obj.protocolLpFeeAllocationInBps,
     })
   }
 
@@ -70,6 +88,9 @@ export class InitializePoolIxParams {
         types.ProtocolFeeRecipientParams.toEncodable(item)
       ),
       numSlotsToVestLpShares: fields.numSlotsToVestLpShares,
+      minQuoteDeposit: fields.minQuoteDeposit,
+      maxQuoteTvl: fields.maxQuoteTvl,
+      protocolLpFeeAllocationInBps: fields.protocolLpFeeAllocationInBps,
     }
   }
 
@@ -85,6 +106,13 @@ export class InitializePoolIxParams {
         (this.numSlotsToVestLpShares &&
           this.numSlotsToVestLpShares.toString()) ||
         null,
+      minQuoteDeposit:
+        (this.minQuoteDeposit && this.minQuoteDeposit.toString()) || null,
+      maxQuoteTvl: (this.maxQuoteTvl && this.maxQuoteTvl.toString()) || null,
+      protocolLpFeeAllocationInBps:
+        (this.protocolLpFeeAllocationInBps &&
+          this.protocolLpFeeAllocationInBps.toString()) ||
+        null,
     }
   }
 
@@ -98,6 +126,13 @@ export class InitializePoolIxParams {
       numSlotsToVestLpShares:
         (obj.numSlotsToVestLpShares && new BN(obj.numSlotsToVestLpShares)) ||
         null,
+      minQuoteDeposit:
+        (obj.minQuoteDeposit && new BN(obj.minQuoteDeposit)) || null,
+      maxQuoteTvl: (obj.maxQuoteTvl && new BN(obj.maxQuoteTvl)) || null,
+      protocolLpFeeAllocationInBps:
+        (obj.protocolLpFeeAllocationInBps &&
+          new BN(obj.protocolLpFeeAllocationInBps)) ||
+        null,
     })
   }
 
@@ -411,6 +411,359 @@ export class WithdrawProtocolFees {
   }
 }
 
+export type LogFields = {
+  header: types.PlasmaEventHeaderFields
+}
+export type LogValue = {
+  header: types.PlasmaEventHeader
+}
+
+export interface LogJSON {
+  kind: "Log"
+  value: {
+    header: types.PlasmaEventHeaderJSON
+  }
+}
+
+export class Log {
+  static readonly discriminator = 8
+  static readonly kind = "Log"
+  readonly discriminator = 8
+  readonly kind = "Log"
+  readonly value: LogValue
+
+  constructor(value: LogFields) {
+    this.value = {
+      header: new types.PlasmaEventHeader({ ...value.header }),
+    }
+  }
+
+  toJSON(): LogJSON {
+    return {
+      kind: "Log",
+      value: {
+        header: this.value.header.toJSON(),
+      },
+    }
+  }
+
+  toEncodable() {
+    return {
+      Log: {
+        header: types.PlasmaEventHeader.toEncodable(this.value.header),
+      },
+    }
+  }
+}
+
+export type TransferLiquidityFields = {
+  header: types.PlasmaEventHeaderFields
+  event: types.TransferLiquidityEventFields
+}
+export type TransferLiquidityValue = {
+  header: types.PlasmaEventHeader
+  event: types.TransferLiquidityEvent
+}
+
+export interface TransferLiquidityJSON {
+  kind: "TransferLiquidity"
+  value: {
+    header: types.PlasmaEventHeaderJSON
+    event: types.TransferLiquidityEventJSON
+  }
+}
+
+export class TransferLiquidity {
+  static readonly discriminator = 9
+  static readonly kind = "TransferLiquidity"
+  readonly discriminator = 9
+  readonly kind = "TransferLiquidity"
+  readonly value: TransferLiquidityValue
+
+  constructor(value: TransferLiquidityFields) {
+    this.value = {
+      header: new types.PlasmaEventHeader({ ...value.header }),
+      event: new types.TransferLiquidityEvent({ ...value.event }),
+    }
+  }
+
+  toJSON(): TransferLiquidityJSON {
+    return {
+      kind: "TransferLiquidity",
+      value: {
+        header: this.value.header.toJSON(),
+        event: this.value.event.toJSON(),
+      },
+    }
+  }
+
+  toEncodable() {
+    return {
+      TransferLiquidity: {
+        header: types.PlasmaEventHeader.toEncodable(this.value.header),
+        event: types.TransferLiquidityEvent.toEncodable(this.value.event),
+      },
+    }
+  }
+}
+
+export type SetLpFeeFields = {
+  header: types.PlasmaEventHeaderFields
+  event: types.SetLpFeeEventFields
+}
+export type SetLpFeeValue = {
+  header: types.PlasmaEventHeader
+  event: types.SetLpFeeEvent
+}
+
+export interface SetLpFeeJSON {
+  kind: "SetLpFee"
+  value: {
+    header: types.PlasmaEventHeaderJSON
+    event: types.SetLpFeeEventJSON
+  }
+}
+
+export class SetLpFee {
+  static readonly discriminator = 10
+  static readonly kind = "SetLpFee"
+  readonly discriminator = 10
+  readonly kind = "SetLpFee"
+  readonly value: SetLpFeeValue
+
+  constructor(value: SetLpFeeFields) {
+    this.value = {
+      header: new types.PlasmaEventHeader({ ...value.header }),
+      event: new types.SetLpFeeEvent({ ...value.event }),
+    }
+  }
+
+  toJSON(): SetLpFeeJSON {
+    return {
+      kind: "SetLpFee",
+      value: {
+        header: this.value.header.toJSON(),
+        event: this.value.event.toJSON(),
+      },
+    }
+  }
+
+  toEncodable() {
+    return {
+      SetLpFee: {
+        header: types.PlasmaEventHeader.toEncodable(this.value.header),
+        event: types.SetLpFeeEvent.toEncodable(this.value.event),
+      },
+    }
+  }
+}
+
+export type BatchInitializeLpPositionsFields = {
+  header: types.PlasmaEventHeaderFields
+  event: types.BatchInitializeLpPositionsEventFields
+}
+export type BatchInitializeLpPositionsValue = {
+  header: types.PlasmaEventHeader
+  event: types.BatchInitializeLpPositionsEvent
+}
+
+export interface BatchInitializeLpPositionsJSON {
+  kind: "BatchInitializeLpPositions"
+  value: {
+    header: types.PlasmaEventHeaderJSON
+    event: types.BatchInitializeLpPositionsEventJSON
+  }
+}
+
+export class BatchInitializeLpPositions {
+  static readonly discriminator = 11
+  static readonly kind = "BatchInitializeLpPositions"
+  readonly discriminator = 11
+  readonly kind = "BatchInitializeLpPositions"
+  readonly value: BatchInitializeLpPositionsValue
+
+  constructor(value: BatchInitializeLpPositionsFields) {
+    this.value = {
+      header: new types.PlasmaEventHeader({ ...value.header }),
+      event: new types.BatchInitializeLpPositionsEvent({ ...value.event }),
+    }
+  }
+
+  toJSON(): BatchInitializeLpPositionsJSON {
+    return {
+      kind: "BatchInitializeLpPositions",
+      value: {
+        header: this.value.header.toJSON(),
+        event: this.value.event.toJSON(),
+      },
+    }
+  }
+
+  toEncodable() {
+    return {
+      BatchInitializeLpPositions: {
+        header: types.PlasmaEventHeader.toEncodable(this.value.header),
+        event: types.BatchInitializeLpPositionsEvent.toEncodable(
+          this.value.event
+        ),
+      },
+    }
+  }
+}
+
+export type CrankVestingFields = {
+  header: types.PlasmaEventHeaderFields
+  event: types.CrankVestingEventFields
+}
+export type CrankVestingValue = {
+  header: types.PlasmaEventHeader
+  event: types.CrankVestingEvent
+}
+
+export interface CrankVestingJSON {
+  kind: "CrankVesting"
+  value: {
+    header: types.PlasmaEventHeaderJSON
+    event: types.CrankVestingEventJSON
+  }
+}
+
+export class CrankVesting {
+  static readonly discriminator = 12
+  static readonly kind = "CrankVesting"
+  readonly discriminator = 12
+  readonly kind = "CrankVesting"
+  readonly value: CrankVestingValue
+
+  constructor(value: CrankVestingFields) {
+    this.value = {
+      header: new types.PlasmaEventHeader({ ...value.header }),
+      event: new types.CrankVestingEvent({ ...value.event }),
+    }
+  }
+
+  toJSON(): CrankVestingJSON {
+    return {
+      kind: "CrankVesting",
+      value: {
+        header: this.value.header.toJSON(),
+        event: this.value.event.toJSON(),
+      },
+    }
+  }
+
+  toEncodable() {
+    return {
+      CrankVesting: {
+        header: types.PlasmaEventHeader.toEncodable(this.value.header),
+        event: types.CrankVestingEvent.toEncodable(this.value.event),
+      },
+    }
+  }
+}
+
+export type ReinvestProtocolFeesFields = {
+  header: types.PlasmaEventHeaderFields
+  event: types.ReinvestProtocolFeesEventFields
+}
+export type ReinvestProtocolFeesValue = {
+  header: types.PlasmaEventHeader
+  event: types.ReinvestProtocolFeesEvent
+}
+
+export interface ReinvestProtocolFeesJSON {
+  kind: "ReinvestProtocolFees"
+  value: {
+    header: types.PlasmaEventHeaderJSON
+    event: types.ReinvestProtocolFeesEventJSON
+  }
+}
+
+export class ReinvestProtocolFees {
+  static readonly discriminator = 13
+  static readonly kind = "ReinvestProtocolFees"
+  readonly discriminator = 13
+  readonly kind = "ReinvestProtocolFees"
+  readonly value: ReinvestProtocolFeesValue
+
+  constructor(value: ReinvestProtocolFeesFields) {
+    this.value = {
+      header: new types.PlasmaEventHeader({ ...value.header }),
+      event: new types.ReinvestProtocolFeesEvent({ ...value.event }),
+    }
+  }
+
+  toJSON(): ReinvestProtocolFeesJSON {
+    return {
+      kind: "ReinvestProtocolFees",
+      value: {
+        header: this.value.header.toJSON(),
+        event: this.value.event.toJSON(),
+      },
+    }
+  }
+
+  toEncodable() {
+    return {
+      ReinvestProtocolFees: {
+        header: types.PlasmaEventHeader.toEncodable(this.value.header),
+        event: types.ReinvestProtocolFeesEvent.toEncodable(this.value.event),
+      },
+    }
+  }
+}
+
+export type ReleaseVestingFields = {
+  header: types.PlasmaEventHeaderFields
+  event: types.ReleaseVestingEventFields
+}
+export type ReleaseVestingValue = {
+  header: types.PlasmaEventHeader
+  event: types.ReleaseVestingEvent
+}
+
+export interface ReleaseVestingJSON {
+  kind: "ReleaseVesting"
+  value: {
+    header: types.PlasmaEventHeaderJSON
+    event: types.ReleaseVestingEventJSON
+  }
+}
+
+export class ReleaseVesting {
+  static readonly discriminator = 14
+  static readonly kind = "ReleaseVesting"
+  readonly discriminator = 14
+  readonly kind = "ReleaseVesting"
+  readonly value: ReleaseVestingValue
+
+  constructor(value: ReleaseVestingFields) {
+    this.value = {
+      header: new types.PlasmaEventHeader({ ...value.header }),
+      event: new types.ReleaseVestingEvent({ ...value.event }),
+    }
+  }
+
+  toJSON(): ReleaseVestingJSON {
+    return {
+      kind: "ReleaseVesting",
+      value: {
+        header: this.value.header.toJSON(),
+        event: this.value.event.toJSON(),
+      },
+    }
+  }
+
+  toEncodable() {
+    return {
+      ReleaseVesting: {
+        header: types.PlasmaEventHeader.toEncodable(this.value.header),
+        event: types.ReleaseVestingEvent.toEncodable(this.value.event),
+      },
+    }
+  }
+}
+
 // eslint-disable-next-line @typescript-eslint/no-explicit-any
 export function fromDecoded(obj: any): types.PlasmaEventKind {
   if (typeof obj !== "object") {
@@ -473,6 +826,54 @@ export function fromDecoded(obj: any): types.PlasmaEventKind {
       event: types.WithdrawProtocolFeesEvent.fromDecoded(val["event"]),
     })
   }
+  if ("Log" in obj) {
+    const val = obj["Log"]
+    return new Log({
+      header: types.PlasmaEventHeader.fromDecoded(val["header"]),
+    })
+  }
+  if ("TransferLiquidity" in obj) {
+    const val = obj["TransferLiquidity"]
+    return new TransferLiquidity({
+      header: types.PlasmaEventHeader.fromDecoded(val["header"]),
+      event: types.TransferLiquidityEvent.fromDecoded(val["event"]),
+    })
+  }
+  if ("SetLpFee" in obj) {
+    const val = obj["SetLpFee"]
+    return new SetLpFee({
+      header: types.PlasmaEventHeader.fromDecoded(val["header"]),
+      event: types.SetLpFeeEvent.fromDecoded(val["event"]),
+    })
+  }
+  if ("BatchInitializeLpPositions" in obj) {
+    const val = obj["BatchInitializeLpPositions"]
+    return new BatchInitializeLpPositions({
+      header: types.PlasmaEventHeader.fromDecoded(val["header"]),
+      event: types.BatchInitializeLpPositionsEvent.fromDecoded(val["event"]),
+    })
+  }
+  if ("CrankVesting" in obj) {
+    const val = obj["CrankVesting"]
+    return new CrankVesting({
+      header: types.PlasmaEventHeader.fromDecoded(val["header"]),
+      event: types.CrankVestingEvent.fromDecoded(val["event"]),
+    })
+  }
+  if ("ReinvestProtocolFees" in obj) {
+    const val = obj["ReinvestProtocolFees"]
+    return new ReinvestProtocolFees({
+      header: types.PlasmaEventHeader.fromDecoded(val["header"]),
+      event: types.ReinvestProtocolFeesEvent.fromDecoded(val["event"]),
+    })
+  }
+  if ("ReleaseVesting" in obj) {
+    const val = obj["ReleaseVesting"]
+    return new ReleaseVesting({
+      header: types.PlasmaEventHeader.fromDecoded(val["header"]),
+      event: types.ReleaseVestingEvent.fromDecoded(val["event"]),
+    })
+  }
 
   throw new Error("Invalid enum object")
 }
@@ -527,6 +928,47 @@ export function fromJSON(obj: types.PlasmaEventJSON): types.PlasmaEventKind {
         event: types.WithdrawProtocolFeesEvent.fromJSON(obj.value.event),
       })
     }
+    case "Log": {
+      return new Log({
+        header: types.PlasmaEventHeader.fromJSON(obj.value.header),
+      })
+    }
+    case "TransferLiquidity": {
+      return new TransferLiquidity({
+        header: types.PlasmaEventHeader.fromJSON(obj.value.header),
+        event: types.TransferLiquidityEvent.fromJSON(obj.value.event),
+      })
+    }
+    case "SetLpFee": {
+      return new SetLpFee({
+        header: types.PlasmaEventHeader.fromJSON(obj.value.header),
+        event: types.SetLpFeeEvent.fromJSON(obj.value.event),
+      })
+    }
+    case "BatchInitializeLpPositions": {
+      return new BatchInitializeLpPositions({
+        header: types.PlasmaEventHeader.fromJSON(obj.value.header),
+        event: types.BatchInitializeLpPositionsEvent.fromJSON(obj.value.event),
+      })
+    }
+    case "CrankVesting": {
+      return new CrankVesting({
+        header: types.PlasmaEventHeader.fromJSON(obj.value.header),
+        event: types.CrankVestingEvent.fromJSON(obj.value.event),
+      })
+    }
+    case "ReinvestProtocolFees": {
+      return new ReinvestProtocolFees({
+        header: types.PlasmaEventHeader.fromJSON(obj.value.header),
+        event: types.ReinvestProtocolFeesEvent.fromJSON(obj.value.event),
+      })
+    }
+    case "ReleaseVesting": {
+      return new ReleaseVesting({
+        header: types.PlasmaEventHeader.fromJSON(obj.value.header),
+        event: types.ReleaseVestingEvent.fromJSON(obj.value.event),
+      })
+    }
   }
 }
 
@@ -588,6 +1030,49 @@ export function layout(property?: string) {
       ],
       "WithdrawProtocolFees"
     ),
+    borsh.struct([types.PlasmaEventHeader.layout("header")], "Log"),
+    borsh.struct(
+      [
+        types.PlasmaEventHeader.layout("header"),
+        types.TransferLiquidityEvent.layout("event"),
+      ],
+      "TransferLiquidity"
+    ),
+    borsh.struct(
+      [
+        types.PlasmaEventHeader.layout("header"),
+        types.SetLpFeeEvent.layout("event"),
+      ],
+      "SetLpFee"
+    ),
+    borsh.struct(
+      [
+        types.PlasmaEventHeader.layout("header"),
+        types.BatchInitializeLpPositionsEvent.layout("event"),
+      ],
+      "BatchInitializeLpPositions"
+    ),
+    borsh.struct(
+      [
+        types.PlasmaEventHeader.layout("header"),
+        types.CrankVestingEvent.layout("event"),
+      ],
+      "CrankVesting"
+    ),
+    borsh.struct(
+      [
+        types.PlasmaEventHeader.layout("header"),
+        types.ReinvestProtocolFeesEvent.layout("event"),
+      ],
+      "ReinvestProtocolFees"
+    ),
+    borsh.struct(
+      [
+        types.PlasmaEventHeader.layout("header"),
+        types.ReleaseVestingEvent.layout("event"),
+      ],
+      "ReleaseVesting"
+    ),
   ])
   if (property !== undefined) {
     return ret.replicate(property)
@@ -9,6 +9,12 @@ export interface PoolHeaderFields {
   quoteParams: types.TokenParamsFields
   feeRecipients: types.ProtocolFeeRecipientsFields
   swapSequenceNumber: BN
+  authority: PublicKey
+  minQuoteDeposit: BN
+  maxQuoteTvl: BN
+  creationSlot: BN
+  lastPriceUpdateSlot: BN
+  cumulativePriceBits: Array<BN>
   padding: Array<BN>
 }
 
@@ -18,6 +24,12 @@ export interface PoolHeaderJSON {
   quoteParams: types.TokenParamsJSON
   feeRecipients: types.ProtocolFeeRecipientsJSON
   swapSequenceNumber: string
+  authority: string
+  minQuoteDeposit: string
+  maxQuoteTvl: string
+  creationSlot: string
+  lastPriceUpdateSlot: string
+  cumulativePriceBits: Array<string>
   padding: Array<string>
 }
 
@@ -27,6 +39,12 @@ export class PoolHeader {
   readonly quoteParams: types.TokenParams
   readonly feeRecipients: types.ProtocolFeeRecipients
   readonly swapSequenceNumber: BN
+  readonly authority: PublicKey
+  readonly minQuoteDeposit: BN
+  readonly maxQuoteTvl: BN
+  readonly creationSlot: BN
+  readonly lastPriceUpdateSlot: BN
+  readonly cumulativePriceBits: Array<BN>
   readonly padding: Array<BN>
 
   constructor(fields: PoolHeaderFields) {
@@ -37,6 +55,12 @@ export class PoolHeader {
       ...fields.feeRecipients,
     })
     this.swapSequenceNumber = fields.swapSequenceNumber
+    this.authority = fields.authority
+    this.minQuoteDeposit = fields.minQuoteDeposit
+    this.maxQuoteTvl = fields.maxQuoteTvl
+    this.creationSlot = fields.creationSlot
+    this.lastPriceUpdateSlot = fields.lastPriceUpdateSlot
+    this.cumulativePriceBits = fields.cumulativePriceBits
     this.padding = fields.padding
   }
 
@@ -48,7 +72,13 @@ export class PoolHeader {
         types.TokenParams.layout("quoteParams"),
         types.ProtocolFeeRecipients.layout("feeRecipients"),
         borsh.u64("swapSequenceNumber"),
-        borsh.array(borsh.u64(), 12, "padding"),
+        borsh.publicKey("authority"),
+        borsh.u64("minQuoteDeposit"),
+        borsh.u64("maxQuoteTvl"),
+        borsh.u64("creationSlot"),
+        borsh.u64("lastPriceUpdateSlot"),
+        borsh.array(borsh.u64(), 2, "cumulativePriceBits"),
+        borsh.array(borsh.u64(), 2, "padding"),
       ],
       property
     )
@@ -62,6 +92,12 @@ export class PoolHeader {
       quoteParams: types.TokenParams.fromDecoded(obj.quoteParams),
       feeRecipients: types.ProtocolFeeRecipients.fromDecoded(obj.feeRecipients),
       swapSequenceNumber: obj.swapSequenceNumber,
+      authority: obj.authority,
+      minQuoteDeposit: obj.minQuoteDeposit,
+      maxQuoteTvl: obj.maxQuoteTvl,
+      creationSlot: obj.creationSlot,
+      lastPriceUpdateSlot: obj.lastPriceUpdateSlot,
+      cumulativePriceBits: obj.cumulativePriceBits,
       padding: obj.padding,
     })
   }
@@ -75,6 +111,12 @@ export class PoolHeader {
         fields.feeRecipients
       ),
       swapSequenceNumber: fields.swapSequenceNumber,
+      authority: fields.authority,
+      minQuoteDeposit: fields.minQuoteDeposit,
+      maxQuoteTvl: fields.maxQuoteTvl,
+      creationSlot: fields.creationSlot,
+      lastPriceUpdateSlot: fields.lastPriceUpdateSlot,
+      cumulativePriceBits: fields.cumulativePriceBits,
       padding: fields.padding,
     }
   }
@@ -86,6 +128,14 @@ export class PoolHeader {
       quoteParams: this.quoteParams.toJSON(),
       feeRecipients: this.feeRecipients.toJSON(),
       swapSequenceNumber: this.swapSequenceNumber.toString(),
+      authority: this.authority.toString(),
+      minQuoteDeposit: this.minQuoteDeposit.toString(),
+      maxQuoteTvl: this.maxQuoteTvl.toString(),
+      creationSlot: this.creationSlot.toString(),
+      lastPriceUpdateSlot: this.lastPriceUpdateSlot.toString(),
+      cumulativePriceBits: this.cumulativePriceBits.map((item) =>
+        item.toString()
+      ),
       padding: this.padding.map((item) => item.toString()),
     }
   }
@@ -97,6 +147,12 @@ export class PoolHeader {
       quoteParams: types.TokenParams.fromJSON(obj.quoteParams),
       feeRecipients: types.ProtocolFeeRecipients.fromJSON(obj.feeRecipients),
       swapSequenceNumber: new BN(obj.swapSequenceNumber),
+      authority: new PublicKey(obj.authority),
+      minQuoteDeposit: new BN(obj.minQuoteDeposit),
+      maxQuoteTvl: new BN(obj.maxQuoteTvl),
+      creationSlot: new BN(obj.creationSlot),
+      lastPriceUpdateSlot: new BN(obj.lastPriceUpdateSlot),
+      cumulativePriceBits: obj.cumulativePriceBits.map((item) => new BN(item)),
       padding: obj.padding.map((item) => new BN(item)),
     })
   }
@@ -0,0 +1,66 @@
+import { PublicKey } from "@solana/web3.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import BN from "bn.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as types from "../types" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as borsh from "@coral-xyz/borsh"
+
+export interface ReinvestProtocolFeesEventFields {
+  protocolFeeRecipient: PublicKey
+  feesReinvested: BN
+}
+
+export interface ReinvestProtocolFeesEventJSON {
+  protocolFeeRecipient: string
+  feesReinvested: string
+}
+
+export class ReinvestProtocolFeesEvent {
+  readonly protocolFeeRecipient: PublicKey
+  readonly feesReinvested: BN
+
+  constructor(fields: ReinvestProtocolFeesEventFields) {
+    this.protocolFeeRecipient = fields.protocolFeeRecipient
+    this.feesReinvested = fields.feesReinvested
+  }
+
+  static layout(property?: string) {
+    return borsh.struct(
+      [borsh.publicKey("protocolFeeRecipient"), borsh.u64("feesReinvested")],
+      property
+    )
+  }
+
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  static fromDecoded(obj: any) {
+    return new ReinvestProtocolFeesEvent({
+      protocolFeeRecipient: obj.protocolFeeRecipient,
+      feesReinvested: obj.feesReinvested,
+    })
+  }
+
+  static toEncodable(fields: ReinvestProtocolFeesEventFields) {
+    return {
+      protocolFeeRecipient: fields.protocolFeeRecipient,
+      feesReinvested: fields.feesReinvested,
+    }
+  }
+
+  toJSON(): ReinvestProtocolFeesEventJSON {
+    return {
+      protocolFeeRecipient: this.protocolFeeRecipient.toString(),
+      feesReinvested: this.feesReinvested.toString(),
+    }
+  }
+
+  static fromJSON(
+    obj: ReinvestProtocolFeesEventJSON
+  ): ReinvestProtocolFeesEvent {
+    return new ReinvestProtocolFeesEvent({
+      protocolFeeRecipient: new PublicKey(obj.protocolFeeRecipient),
+      feesReinvested: new BN(obj.feesReinvested),
+    })
+  }
+
+  toEncodable() {
+    return ReinvestProtocolFeesEvent.toEncodable(this)
+  }
+}
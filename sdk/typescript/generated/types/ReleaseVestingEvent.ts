@@ -0,0 +1,64 @@
+import { PublicKey } from "@solana/web3.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import BN from "bn.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as types from "../types" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as borsh from "@coral-xyz/borsh"
+
+export interface ReleaseVestingEventFields {
+  owner: PublicKey
+  sharesReleased: BN
+}
+
+export interface ReleaseVestingEventJSON {
+  owner: string
+  sharesReleased: string
+}
+
+export class ReleaseVestingEvent {
+  readonly owner: PublicKey
+  readonly sharesReleased: BN
+
+  constructor(fields: ReleaseVestingEventFields) {
+    this.owner = fields.owner
+    this.sharesReleased = fields.sharesReleased
+  }
+
+  static layout(property?: string) {
+    return borsh.struct(
+      [borsh.publicKey("owner"), borsh.u64("sharesReleased")],
+      property
+    )
+  }
+
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  static fromDecoded(obj: any) {
+    return new ReleaseVestingEvent({
+      owner: obj.owner,
+      sharesReleased: obj.sharesReleased,
+    })
+  }
+
+  static toEncodable(fields: ReleaseVestingEventFields) {
+    return {
+      owner: fields.owner,
+      sharesReleased: fields.sharesReleased,
+    }
+  }
+
+  toJSON(): ReleaseVestingEventJSON {
+    return {
+      owner: this.owner.toString(),
+      sharesReleased: this.sharesReleased.toString(),
+    }
+  }
+
+  static fromJSON(obj: ReleaseVestingEventJSON): ReleaseVestingEvent {
+    return new ReleaseVestingEvent({
+      owner: new PublicKey(obj.owner),
+      sharesReleased: new BN(obj.sharesReleased),
+    })
+  }
+
+  toEncodable() {
+    return ReleaseVestingEvent.toEncodable(this)
+  }
+}
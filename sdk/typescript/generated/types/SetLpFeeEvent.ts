@@ -0,0 +1,64 @@
+import { PublicKey } from "@solana/web3.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import BN from "bn.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as types from "../types" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as borsh from "@coral-xyz/borsh"
+
+export interface SetLpFeeEventFields {
+  oldLpFeeInBps: BN
+  newLpFeeInBps: BN
+}
+
+export interface SetLpFeeEventJSON {
+  oldLpFeeInBps: string
+  newLpFeeInBps: string
+}
+
+export class SetLpFeeEvent {
+  readonly oldLpFeeInBps: BN
+  readonly newLpFeeInBps: BN
+
+  constructor(fields: SetLpFeeEventFields) {
+    this.oldLpFeeInBps = fields.oldLpFeeInBps
+    this.newLpFeeInBps = fields.newLpFeeInBps
+  }
+
+  static layout(property?: string) {
+    return borsh.struct(
+      [borsh.u64("oldLpFeeInBps"), borsh.u64("newLpFeeInBps")],
+      property
+    )
+  }
+
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  static fromDecoded(obj: any) {
+    return new SetLpFeeEvent({
+      oldLpFeeInBps: obj.oldLpFeeInBps,
+      newLpFeeInBps: obj.newLpFeeInBps,
+    })
+  }
+
+  static toEncodable(fields: SetLpFeeEventFields) {
+    return {
+      oldLpFeeInBps: fields.oldLpFeeInBps,
+      newLpFeeInBps: fields.newLpFeeInBps,
+    }
+  }
+
+  toJSON(): SetLpFeeEventJSON {
+    return {
+      oldLpFeeInBps: this.oldLpFeeInBps.toString(),
+      newLpFeeInBps: this.newLpFeeInBps.toString(),
+    }
+  }
+
+  static fromJSON(obj: SetLpFeeEventJSON): SetLpFeeEvent {
+    return new SetLpFeeEvent({
+      oldLpFeeInBps: new BN(obj.oldLpFeeInBps),
+      newLpFeeInBps: new BN(obj.newLpFeeInBps),
+    })
+  }
+
+  toEncodable() {
+    return SetLpFeeEvent.toEncodable(this)
+  }
+}
@@ -0,0 +1,53 @@
+import { PublicKey } from "@solana/web3.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import BN from "bn.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as types from "../types" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as borsh from "@coral-xyz/borsh"
+
+export interface SetLpFeeIxParamsFields {
+  lpFeeInBps: BN
+}
+
+export interface SetLpFeeIxParamsJSON {
+  lpFeeInBps: string
+}
+
+export class SetLpFeeIxParams {
+  readonly lpFeeInBps: BN
+
+  constructor(fields: SetLpFeeIxParamsFields) {
+    this.lpFeeInBps = fields.lpFeeInBps
+  }
+
+  static layout(property?: string) {
+    return borsh.struct([borsh.u64("lpFeeInBps")], property)
+  }
+
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  static fromDecoded(obj: any) {
+    return new SetLpFeeIxParams({
+      lpFeeInBps: obj.lpFeeInBps,
+    })
+  }
+
+  static toEncodable(fields: SetLpFeeIxParamsFields) {
+    return {
+      lpFeeInBps: fields.lpFeeInBps,
+    }
+  }
+
+  toJSON(): SetLpFeeIxParamsJSON {
+    return {
+      lpFeeInBps: this.lpFeeInBps.toString(),
+    }
+  }
+
+  static fromJSON(obj: SetLpFeeIxParamsJSON): SetLpFeeIxParams {
+    return new SetLpFeeIxParams({
+      lpFeeInBps: new BN(obj.lpFeeInBps),
+    })
+  }
+
+  toEncodable() {
+    return SetLpFeeIxParams.toEncodable(this)
+  }
+}
@@ -12,6 +12,8 @@ export interface SwapEventFields {
   snapshotBaseLiquidity: BN
   snapshotQuoteLiquidity: BN
   swapResult: types.SwapResultFields
+  lpFeeQuote: BN
+  protocolFeeQuote: BN
 }
 
 export interface SwapEventJSON {
@@ -23,6 +25,8 @@ export interface SwapEventJSON {
   snapshotBaseLiquidity: string
   snapshotQuoteLiquidity: string
   swapResult: types.SwapResultJSON
+  lpFeeQuote: string
+  protocolFeeQuote: string
 }
 
 export class SwapEvent {
@@ -34,6 +38,8 @@ export class SwapEvent {
   readonly snapshotBaseLiquidity: BN
   readonly snapshotQuoteLiquidity: BN
   readonly swapResult: types.SwapResult
+  readonly lpFeeQuote: BN
+  readonly protocolFeeQuote: BN
 
   constructor(fields: SwapEventFields) {
     this.swapSequenceNumber = fields.swapSequenceNumber
@@ -44,6 +50,8 @@ export class SwapEvent {
     this.snapshotBaseLiquidity = fields.snapshotBaseLiquidity
     this.snapshotQuoteLiquidity = fields.snapshotQuoteLiquidity
     this.swapResult = new types.SwapResult({ ...fields.swapResult })
+    this.lpFeeQuote = fields.lpFeeQuote
+    this.protocolFeeQuote = fields.protocolFeeQuote
   }
 
   static layout(property?: string) {
@@ -57,6 +65,8 @@ export class SwapEvent {
         borsh.u64("snapshotBaseLiquidity"),
         borsh.u64("snapshotQuoteLiquidity"),
         types.SwapResult.layout("swapResult"),
+        borsh.u64("lpFeeQuote"),
+        borsh.u64("protocolFeeQuote"),
       ],
       property
     )
@@ -73,6 +83,8 @@ export class SwapEvent {
       snapshotBaseLiquidity: obj.snapshotBaseLiquidity,
       snapshotQuoteLiquidity: obj.snapshotQuoteLiquidity,
       swapResult: types.SwapResult.fromDecoded(obj.swapResult),
+      lpFeeQuote: obj.lpFeeQuote,
+      protocolFeeQuote: obj.protocolFeeQuote,
     })
   }
 
@@ -86,6 +98,8 @@ export class SwapEvent {
       snapshotBaseLiquidity: fields.snapshotBaseLiquidity,
       snapshotQuoteLiquidity: fields.snapshotQuoteLiquidity,
       swapResult: types.SwapResult.toEncodable(fields.swapResult),
+      lpFeeQuote: fields.lpFeeQuote,
+      protocolFeeQuote: fields.protocolFeeQuote,
     }
   }
 
@@ -99,6 +113,8 @@ export class SwapEvent {
       snapshotBaseLiquidity: this.snapshotBaseLiquidity.toString(),
       snapshotQuoteLiquidity: this.snapshotQuoteLiquidity.toString(),
       swapResult: this.swapResult.toJSON(),
+      lpFeeQuote: this.lpFeeQuote.toString(),
+      protocolFeeQuote: this.protocolFeeQuote.toString(),
     }
   }
 
@@ -112,6 +128,8 @@ export class SwapEvent {
       snapshotBaseLiquidity: new BN(obj.snapshotBaseLiquidity),
       snapshotQuoteLiquidity: new BN(obj.snapshotQuoteLiquidity),
       swapResult: types.SwapResult.fromJSON(obj.swapResult),
+      lpFeeQuote: new BN(obj.lpFeeQuote),
+      protocolFeeQuote: new BN(obj.protocolFeeQuote),
     })
   }
 
@@ -6,25 +6,33 @@ import * as borsh from "@coral-xyz/borsh"
 export interface SwapIxParamsFields {
   side: types.SideKind
   swapType: types.SwapTypeKind
+  deadlineSlot: BN | null
 }
 
 export interface SwapIxParamsJSON {
   side: types.SideJSON
   swapType: types.SwapTypeJSON
+  deadlineSlot: string | null
 }
 
 export class SwapIxParams {
   readonly side: types.SideKind
   readonly swapType: types.SwapTypeKind
+  readonly deadlineSlot: BN | null
 
   constructor(fields: SwapIxParamsFields) {
     this.side = fields.side
     this.swapType = fields.swapType
+    this.deadlineSlot = fields.deadlineSlot
   }
 
   static layout(property?: string) {
     return borsh.struct(
-      [types.Side.layout("side"), types.SwapType.layout("swapType")],
+      [
+        types.Side.layout("side"),
+        types.SwapType.layout("swapType"),
+        borsh.option(borsh.u64(), "deadlineSlot"),
+      ],
       property
     )
   }
@@ -34,6 +42,7 @@ export class SwapIxParams {
     return new SwapIxParams({
       side: types.Side.fromDecoded(obj.side),
       swapType: types.SwapType.fromDecoded(obj.swapType),
+      deadlineSlot: obj.deadlineSlot,
     })
   }
 
@@ -41,6 +50,7 @@ export class SwapIxParams {
     return {
       side: fields.side.toEncodable(),
       swapType: fields.swapType.toEncodable(),
+      deadlineSlot: fields.deadlineSlot,
     }
   }
 
@@ -48,6 +58,7 @@ export class SwapIxParams {
     return {
       side: this.side.toJSON(),
       swapType: this.swapType.toJSON(),
+      deadlineSlot: (this.deadlineSlot && this.deadlineSlot.toString()) || null,
     }
   }
 
@@ -55,6 +66,7 @@ export class SwapIxParams {
     return new SwapIxParams({
       side: types.Side.fromJSON(obj.side),
       swapType: types.SwapType.fromJSON(obj.swapType),
+      deadlineSlot: (obj.deadlineSlot && new BN(obj.deadlineSlot)) || null,
     })
   }
 
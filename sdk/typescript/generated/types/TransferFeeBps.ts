@@ -0,0 +1,61 @@
+import { PublicKey } from "@solana/web3.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import BN from "bn.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as types from "../types" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as borsh from "@coral-xyz/borsh"
+
+export interface TransferFeeBpsFields {
+  base: number
+  quote: number
+}
+
+export interface TransferFeeBpsJSON {
+  base: number
+  quote: number
+}
+
+export class TransferFeeBps {
+  readonly base: number
+  readonly quote: number
+
+  constructor(fields: TransferFeeBpsFields) {
+    this.base = fields.base
+    this.quote = fields.quote
+  }
+
+  static layout(property?: string) {
+    return borsh.struct([borsh.u16("base"), borsh.u16("quote")], property)
+  }
+
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  static fromDecoded(obj: any) {
+    return new TransferFeeBps({
+      base: obj.base,
+      quote: obj.quote,
+    })
+  }
+
+  static toEncodable(fields: TransferFeeBpsFields) {
+    return {
+      base: fields.base,
+      quote: fields.quote,
+    }
+  }
+
+  toJSON(): TransferFeeBpsJSON {
+    return {
+      base: this.base,
+      quote: this.quote,
+    }
+  }
+
+  static fromJSON(obj: TransferFeeBpsJSON): TransferFeeBps {
+    return new TransferFeeBps({
+      base: obj.base,
+      quote: obj.quote,
+    })
+  }
+
+  toEncodable() {
+    return TransferFeeBps.toEncodable(this)
+  }
+}
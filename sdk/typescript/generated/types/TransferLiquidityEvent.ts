@@ -0,0 +1,76 @@
+import { PublicKey } from "@solana/web3.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import BN from "bn.js" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as types from "../types" // eslint-disable-line @typescript-eslint/no-unused-vars
+import * as borsh from "@coral-xyz/borsh"
+
+export interface TransferLiquidityEventFields {
+  src: PublicKey
+  dst: PublicKey
+  lpSharesTransferred: BN
+}
+
+export interface TransferLiquidityEventJSON {
+  src: string
+  dst: string
+  lpSharesTransferred: string
+}
+
+export class TransferLiquidityEvent {
+  readonly src: PublicKey
+  readonly dst: PublicKey
+  readonly lpSharesTransferred: BN
+
+  constructor(fields: TransferLiquidityEventFields) {
+    this.src = fields.src
+    this.dst = fields.dst
+    this.lpSharesTransferred = fields.lpSharesTransferred
+  }
+
+  static layout(property?: string) {
+    return borsh.struct(
+      [
+        borsh.publicKey("src"),
+        borsh.publicKey("dst"),
+        borsh.u64("lpSharesTransferred"),
+      ],
+      property
+    )
+  }
+
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  static fromDecoded(obj: any) {
+    return new TransferLiquidityEvent({
+      src: obj.src,
+      dst: obj.dst,
+      lpSharesTransferred: obj.lpSharesTransferred,
+    })
+  }
+
+  static toEncodable(fields: TransferLiquidityEventFields) {
+    return {
+      src: fields.src,
+      dst: fields.dst,
+      lpSharesTransferred: fields.lpSharesTransferred,
+    }
+  }
+
+  toJSON(): TransferLiquidityEventJSON {
+    return {
+      src: this.src.toString(),
+      dst: this.dst.toString(),
+      lpSharesTransferred: this.lpSharesTransferred.toString(),
+    }
+  }
+
+  static fromJSON(obj: TransferLiquidityEventJSON): TransferLiquidityEvent {
+    return new TransferLiquidityEvent({
+      src: new PublicKey(obj.src),
+      dst: new PublicKey(obj.dst),
+      lpSharesTransferred: new BN(obj.lpSharesTransferred),
+    })
+  }
+
+  toEncodable() {
+    return TransferLiquidityEvent.toEncodable(this)
+  }
+}
@@ -37,6 +37,8 @@ export type {
   AddLiquidityIxParamsFields,
   AddLiquidityIxParamsJSON,
 } from "./AddLiquidityIxParams"
+export { TransferFeeBps } from "./TransferFeeBps"
+export type { TransferFeeBpsFields, TransferFeeBpsJSON } from "./TransferFeeBps"
 export { RemoveLiquidityIxParams } from "./RemoveLiquidityIxParams"
 export type {
   RemoveLiquidityIxParamsFields,
@@ -49,6 +51,11 @@ export type {
   RenounceLiquidityIxParamsFields,
   RenounceLiquidityIxParamsJSON,
 } from "./RenounceLiquidityIxParams"
+export { SetLpFeeIxParams } from "./SetLpFeeIxParams"
+export type {
+  SetLpFeeIxParamsFields,
+  SetLpFeeIxParamsJSON,
+} from "./SetLpFeeIxParams"
 export { PlasmaEventHeader } from "./PlasmaEventHeader"
 export type {
   PlasmaEventHeaderFields,
@@ -96,6 +103,35 @@ export type {
   WithdrawProtocolFeesEventFields,
   WithdrawProtocolFeesEventJSON,
 } from "./WithdrawProtocolFeesEvent"
+export { TransferLiquidityEvent } from "./TransferLiquidityEvent"
+export type {
+  TransferLiquidityEventFields,
+  TransferLiquidityEventJSON,
+} from "./TransferLiquidityEvent"
+export { SetLpFeeEvent } from "./SetLpFeeEvent"
+export type { SetLpFeeEventFields, SetLpFeeEventJSON } from "./SetLpFeeEvent"
+export {
+  BatchInitializeLpPositionsEvent,
+} from "./BatchInitializeLpPositionsEvent"
+export type {
+  BatchInitializeLpPositionsEventFields,
+  BatchInitializeLpPositionsEventJSON,
+} from "./BatchInitializeLpPositionsEvent"
+export { CrankVestingEvent } from "./CrankVestingEvent"
+export type {
+  CrankVestingEventFields,
+  CrankVestingEventJSON,
+} from "./CrankVestingEvent"
+export { ReinvestProtocolFeesEvent } from "./ReinvestProtocolFeesEvent"
+export type {
+  ReinvestProtocolFeesEventFields,
+  ReinvestProtocolFeesEventJSON,
+} from "./ReinvestProtocolFeesEvent"
+export { ReleaseVestingEvent } from "./ReleaseVestingEvent"
+export type {
+  ReleaseVestingEventFields,
+  ReleaseVestingEventJSON,
+} from "./ReleaseVestingEvent"
 export { Side }
 
 export type SideKind = Side.Buy | Side.Sell
@@ -117,6 +153,13 @@ export type PlasmaEventKind =
   | PlasmaEvent.InitializeLpPosition
   | PlasmaEvent.InitializePool
   | PlasmaEvent.WithdrawProtocolFees
+  | PlasmaEvent.Log
+  | PlasmaEvent.TransferLiquidity
+  | PlasmaEvent.SetLpFee
+  | PlasmaEvent.BatchInitializeLpPositions
+  | PlasmaEvent.CrankVesting
+  | PlasmaEvent.ReinvestProtocolFees
+  | PlasmaEvent.ReleaseVesting
 export type PlasmaEventJSON =
   | PlasmaEvent.SwapJSON
   | PlasmaEvent.AddLiquidityJSON
@@ -126,3 +169,10 @@ export type PlasmaEventJSON =
   | PlasmaEvent.InitializeLpPositionJSON
   | PlasmaEvent.InitializePoolJSON
   | PlasmaEvent.WithdrawProtocolFeesJSON
+  | PlasmaEvent.LogJSON
+  | PlasmaEvent.TransferLiquidityJSON
+  | PlasmaEvent.SetLpFeeJSON
+  | PlasmaEvent.BatchInitializeLpPositionsJSON
+  | PlasmaEvent.CrankVestingJSON
+  | PlasmaEvent.ReinvestProtocolFeesJSON
+  | PlasmaEvent.ReleaseVestingJSON
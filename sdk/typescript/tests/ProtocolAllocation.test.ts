@@ -0,0 +1,16 @@
+import {protocolAllocationInBps} from '../util/ProtocolAllocation';
+import assert from 'assert';
+
+describe('protocolAllocationInBps', () => {
+  it('should read the basis points of new pools', () => {
+    assert.strictEqual(protocolAllocationInBps({protocolAllocationInPct: 0, protocolAllocationInBps: 250}), 250);
+  });
+
+  it('should convert the percentage of legacy pools', () => {
+    assert.strictEqual(protocolAllocationInBps({protocolAllocationInPct: 20, protocolAllocationInBps: 0}), 2000);
+  });
+
+  it('should return zero when no allocation is set', () => {
+    assert.strictEqual(protocolAllocationInBps({protocolAllocationInPct: 0, protocolAllocationInBps: 0}), 0);
+  });
+});
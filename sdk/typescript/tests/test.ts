@@ -147,6 +147,9 @@ const initPool = async (
             protocolLpFeeAllocationInPct: new BN(10),
             feeRecipientsParams: feeRecipients,
             numSlotsToVestLpShares: new BN(1),
+            minQuoteDeposit: null,
+            maxQuoteTvl: null,
+            protocolLpFeeAllocationInBps: null,
           },
         },
         {
@@ -202,6 +205,7 @@ const initPool = async (
             desiredBaseAmountIn: new BN(baseAmountIn),
             desiredQuoteAmountIn: new BN(solAmountIn),
             initialLpShares,
+            expectedTransferFeeBps: null,
           },
         },
         {
@@ -536,6 +540,7 @@ describe("Plasma AMM", async () => {
                   amountIn: new BN(2_000_000_000),
                   minAmountOut: new BN(0),
                 }),
+                deadlineSlot: null,
               },
             },
             {
@@ -574,6 +579,7 @@ describe("Plasma AMM", async () => {
                   amountIn: new BN(1000_000_000),
                   minAmountOut: new BN(0),
                 }),
+                deadlineSlot: null,
               },
             },
             {
@@ -671,6 +677,7 @@ describe("Plasma AMM", async () => {
                 amountIn: attackerMintBalance,
                 minAmountOut: new BN(2_000_000_000),
               }),
+              deadlineSlot: null,
             },
           },
           {
@@ -790,6 +797,7 @@ describe("Plasma AMM", async () => {
                   amountIn: new BN(2_000_000_000),
                   minAmountOut: new BN(0),
                 }),
+                deadlineSlot: null,
               },
             },
             {
@@ -828,6 +836,7 @@ describe("Plasma AMM", async () => {
                   amountIn: new BN(1_000_000_000),
                   minAmountOut: new BN(0),
                 }),
+                deadlineSlot: null,
               },
             },
             {
@@ -903,6 +912,7 @@ describe("Plasma AMM", async () => {
                 amountIn: attackerMintBalance,
                 minAmountOut: new BN(0),
               }),
+              deadlineSlot: null,
             },
           },
           {
@@ -1007,6 +1017,7 @@ describe("Plasma AMM", async () => {
                   amountIn: new BN(2_000_000_000),
                   minAmountOut: new BN(0),
                 }),
+                deadlineSlot: null,
               },
             },
 
@@ -1420,6 +1431,7 @@ describe("Plasma AMM", async () => {
               desiredBaseAmountIn: new BN(10_000_000),
               desiredQuoteAmountIn: new BN(5_000_000),
               initialLpShares: null,
+              expectedTransferFeeBps: null,
             },
           },
           {
@@ -1557,6 +1569,7 @@ describe("Plasma AMM", async () => {
               desiredBaseAmountIn: new BN(100_000_000_000),
               desiredQuoteAmountIn: new BN(50_000_000_000),
               initialLpShares: null,
+              expectedTransferFeeBps: null,
             },
           },
           {
@@ -1603,6 +1616,7 @@ describe("Plasma AMM", async () => {
                   amountIn: new BN(1_000_000),
                   minAmountOut: new BN(0),
                 }),
+                deadlineSlot: null,
               },
             },
             {
@@ -1778,6 +1792,7 @@ describe("Plasma AMM", async () => {
                 desiredBaseAmountIn: new BN(50_000_000_000),
                 desiredQuoteAmountIn: new BN(50_000_000_000),
                 initialLpShares: null,
+                expectedTransferFeeBps: null,
               },
             },
             {
@@ -1832,6 +1847,7 @@ describe("Plasma AMM", async () => {
                   amountIn: new BN(1_000_000_000),
                   minAmountOut: new BN(0),
                 }),
+                deadlineSlot: null,
               },
             },
             {
@@ -2021,6 +2037,7 @@ describe("Plasma AMM", async () => {
               desiredBaseAmountIn: new BN(25_000_000_000),
               desiredQuoteAmountIn: new BN(5_000_000_000),
               initialLpShares: null,
+              expectedTransferFeeBps: null,
             },
           },
           {
@@ -2159,6 +2176,7 @@ describe("Plasma AMM", async () => {
               desiredBaseAmountIn: new BN(50_000_000_000),
               desiredQuoteAmountIn: new BN(50_000_000_000),
               initialLpShares: null,
+              expectedTransferFeeBps: null,
             },
           },
           {
@@ -2305,6 +2323,7 @@ describe("Plasma AMM", async () => {
                 amountIn: new BN(1_000_000),
                 minAmountOut: new BN(0),
               }),
+              deadlineSlot: null,
             },
           },
           {
@@ -2351,6 +2370,7 @@ describe("Plasma AMM", async () => {
                 amountIn: new BN(498747),
                 minAmountOut: new BN(498748),
               }),
+              deadlineSlot: null,
             },
           },
           {
@@ -0,0 +1,19 @@
+import { Amm } from "../generated/types";
+
+/**
+ * Returns the share of the swap fees a pool allocates to the protocol, in basis points.
+ *
+ * Pools created before basis points were supported only set `protocolAllocationInPct`,
+ * newer pools only set `protocolAllocationInBps`.
+ *
+ * @param {Pick<Amm, "protocolAllocationInPct" | "protocolAllocationInBps">} amm - The pool's AMM state.
+ * @returns {number} The protocol allocation in basis points.
+ */
+export function protocolAllocationInBps(
+    amm: Pick<Amm, "protocolAllocationInPct" | "protocolAllocationInBps">
+): number {
+    if (amm.protocolAllocationInBps !== 0) {
+        return amm.protocolAllocationInBps;
+    }
+    return amm.protocolAllocationInPct * 100;
+}